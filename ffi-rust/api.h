@@ -13,6 +13,10 @@ ByteBuffer accuracy_to_privacy_usage(const uint8_t *request_ptr, int32_t request
 
 ByteBuffer compute_privacy_usage(const uint8_t *request_ptr, int32_t request_length);
 
+ByteBuffer compute_epsilon(const uint8_t *request_ptr, int32_t request_length);
+
+ByteBuffer compute_privacy_odometer(const uint8_t *request_ptr, int32_t request_length);
+
 ByteBuffer expand_component(const uint8_t *request_ptr, int32_t request_length);
 
 ByteBuffer get_properties(const uint8_t *request_ptr, int32_t request_length);
@@ -25,6 +29,8 @@ ByteBuffer validate_analysis(const uint8_t *request_ptr, int32_t request_length)
 
 ByteBuffer release(const uint8_t *request_ptr, int32_t request_length);
 
+ByteBuffer get_remaining_budget(const uint8_t *request_ptr, int32_t request_length);
+
 void whitenoise_destroy_bytebuffer(ByteBuffer buffer);
 
 // direct api
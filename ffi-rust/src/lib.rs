@@ -125,6 +125,112 @@ pub extern "C" fn compute_privacy_usage(
     buffer_to_ptr(response)
 }
 
+/// FFI wrapper for [compute_privacy_odometer](../fn.compute_privacy_odometer.html)
+///
+/// # Arguments
+/// - `request_ptr` - a pointer to an array containing the serialized protobuf of [RequestComputePrivacyOdometer](../proto/struct.RequestComputePrivacyOdometer.html)
+/// - `request_length` - the length of the array
+///
+/// # Returns
+/// a [ByteBufferValidator struct](struct.ByteBufferValidator.html) containing a pointer to and length of the serialized protobuf of [proto::ResponseComputePrivacyOdometer](../proto/struct.ResponseComputePrivacyOdometer.html)
+#[no_mangle]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn compute_privacy_odometer(
+    request_ptr: *const u8, request_length: i32,
+) -> ffi_support::ByteBuffer {
+    let request_buffer = unsafe { ptr_to_buffer(request_ptr, request_length) };
+
+    let response = proto::ResponseComputePrivacyOdometer {
+        value: match proto::RequestComputePrivacyOdometer::decode(request_buffer) {
+            Ok(request) => {
+                let proto::RequestComputePrivacyOdometer {
+                    analysis, release
+                } = request;
+
+
+                let run = || -> Result<proto::PrivacyUsage> {
+                    let proto::Analysis {
+                        privacy_definition, computation_graph
+                    } = analysis
+                        .ok_or_else(|| Error::from("analysis must be defined"))?;
+                    let release = parse_release(release
+                        .ok_or_else(|| Error::from("release must be defined"))?);
+
+                    let privacy_definition = privacy_definition
+                        .ok_or_else(|| Error::from("privacy_definition must be defined"))?;
+                    let computation_graph = computation_graph
+                        .ok_or_else(|| Error::from("computation_graph must be defined"))?.value;
+
+                    whitenoise_validator::compute_privacy_odometer(privacy_definition, computation_graph, release)
+                };
+
+                match run() {
+                    Ok(x) =>
+                        Some(proto::response_compute_privacy_odometer::Value::Data(x)),
+                    Err(err) =>
+                        Some(proto::response_compute_privacy_odometer::Value::Error(serialize_error(err))),
+                }
+            }
+            Err(_) =>
+                Some(proto::response_compute_privacy_odometer::Value::Error(serialize_error("unable to parse protobuf".into())))
+        }
+    };
+    buffer_to_ptr(response)
+}
+
+/// FFI wrapper for [compute_epsilon](../fn.compute_epsilon.html)
+///
+/// # Arguments
+/// - `request_ptr` - a pointer to an array containing the serialized protobuf of [RequestComputeEpsilon](../proto/struct.RequestComputeEpsilon.html)
+/// - `request_length` - the length of the array
+///
+/// # Returns
+/// a [ByteBufferValidator struct](struct.ByteBufferValidator.html) containing a pointer to and length of the serialized protobuf of [proto::ResponseComputeEpsilon](../proto/struct.ResponseComputeEpsilon.html)
+#[no_mangle]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn compute_epsilon(
+    request_ptr: *const u8, request_length: i32,
+) -> ffi_support::ByteBuffer {
+    let request_buffer = unsafe { ptr_to_buffer(request_ptr, request_length) };
+
+    let response = proto::ResponseComputeEpsilon {
+        value: match proto::RequestComputeEpsilon::decode(request_buffer) {
+            Ok(request) => {
+                let proto::RequestComputeEpsilon {
+                    analysis, release, delta
+                } = request;
+
+
+                let run = || -> Result<f64> {
+                    let proto::Analysis {
+                        privacy_definition, computation_graph
+                    } = analysis
+                        .ok_or_else(|| Error::from("analysis must be defined"))?;
+                    let release = parse_release(release
+                        .ok_or_else(|| Error::from("release must be defined"))?);
+
+                    let privacy_definition = privacy_definition
+                        .ok_or_else(|| Error::from("privacy_definition must be defined"))?;
+                    let computation_graph = computation_graph
+                        .ok_or_else(|| Error::from("computation_graph must be defined"))?.value;
+
+                    whitenoise_validator::compute_epsilon(privacy_definition, computation_graph, release, delta)
+                };
+
+                match run() {
+                    Ok(x) =>
+                        Some(proto::response_compute_epsilon::Value::Data(x)),
+                    Err(err) =>
+                        Some(proto::response_compute_epsilon::Value::Error(serialize_error(err))),
+                }
+            }
+            Err(_) =>
+                Some(proto::response_compute_epsilon::Value::Error(serialize_error("unable to parse protobuf".into())))
+        }
+    };
+    buffer_to_ptr(response)
+}
+
 /// FFI wrapper for [generate_report](../fn.generate_report.html)
 ///
 /// # Arguments
@@ -412,7 +518,7 @@ pub extern "C" fn release(
         value: match proto::RequestRelease::decode(request_buffer) {
             Ok(request) => {
                 let proto::RequestRelease {
-                    analysis, release, stack_trace, filter_level
+                    analysis, release, stack_trace, filter_level, ledger
                 } = request;
 
 
@@ -428,8 +534,8 @@ pub extern "C" fn release(
                     let filter_level = proto::FilterLevel::from_i32(filter_level)
                         .ok_or_else(|| Error::from(format!("unrecognized filter level {:?}", filter_level)))?;
 
-                    let (release, warnings) = whitenoise_runtime::release(
-                        privacy_definition, computation_graph, release, filter_level)?;
+                    let (release, warnings) = whitenoise_runtime::release_with_ledger(
+                        privacy_definition, computation_graph, release, filter_level, ledger.as_ref())?;
 
                     Ok((release, warnings.into_iter().map(serialize_error).collect()))
                 };
@@ -452,5 +558,50 @@ pub extern "C" fn release(
     buffer_to_ptr(response)
 }
 
+/// FFI wrapper for [get_remaining_budget](../whitenoise_runtime/ledger/fn.get_remaining_budget.html)
+///
+/// # Arguments
+/// - `request_ptr` - a pointer to an array containing the serialized protobuf of [RequestGetRemainingBudget](proto/struct.RequestGetRemainingBudget.html)
+/// - `request_length` - the length of the array
+///
+/// # Returns
+/// a [ByteBufferRuntime struct](struct.ByteBufferRuntime.html) containing a pointer to and length of the serialized protobuf of [proto::ResponseGetRemainingBudget](proto/struct.ResponseGetRemainingBudget.html)
+#[cfg(feature = "use-runtime")]
+#[no_mangle]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn get_remaining_budget(
+    request_ptr: *const u8, request_length: i32,
+) -> ffi_support::ByteBuffer {
+    let request_buffer = unsafe { ptr_to_buffer(request_ptr, request_length) };
+
+    let response = proto::ResponseGetRemainingBudget {
+        value: match proto::RequestGetRemainingBudget::decode(request_buffer) {
+            Ok(request) => {
+                let proto::RequestGetRemainingBudget {
+                    privacy_definition, ledger
+                } = request;
+
+                let run = || -> Result<proto::PrivacyUsage> {
+                    let privacy_definition = privacy_definition
+                        .ok_or_else(|| Error::from("privacy_definition must be defined"))?;
+                    let ledger = ledger
+                        .ok_or_else(|| Error::from("ledger must be defined"))?;
+
+                    whitenoise_runtime::ledger::get_remaining_budget(&ledger, &privacy_definition)
+                };
+
+                match run() {
+                    Ok(x) =>
+                        Some(proto::response_get_remaining_budget::Value::Data(x)),
+                    Err(err) =>
+                        Some(proto::response_get_remaining_budget::Value::Error(serialize_error(err))),
+                }
+            }
+            Err(_) => Some(proto::response_get_remaining_budget::Value::Error(serialize_error("unable to parse protobuf".into())))
+        }
+    };
+    buffer_to_ptr(response)
+}
+
 
 ffi_support::define_bytebuffer_destructor!(whitenoise_destroy_bytebuffer);
@@ -10,20 +10,23 @@ use whitenoise_validator::{proto, Float, Integer};
 use std::hash::Hash;
 
 impl Evaluable for proto::Clamp {
-    fn evaluate(&self, _privacy_definition: &Option<proto::PrivacyDefinition>, mut arguments: NodeArguments) -> Result<ReleaseNode> {
+    fn evaluate(&self, privacy_definition: &Option<proto::PrivacyDefinition>, mut arguments: NodeArguments) -> Result<ReleaseNode> {
+        let enforce_constant_time = privacy_definition.as_ref()
+            .map(|v| v.protect_elapsed_time).unwrap_or(false);
+
         // if categories argument was provided, clamp data as if they are categorical (regardless of atomic type)
         if arguments.contains_key::<IndexKey>(&"categories".into()) {
             match (take_argument(&mut arguments, "data")?, take_argument(&mut arguments, "categories")?, take_argument(&mut arguments, "null_value")?) {
                 (Value::Array(data), Value::Jagged(categories), Value::Array(nulls)) => Ok(match (data, categories, nulls) {
                     (Array::Bool(data), Jagged::Bool(categories), Array::Bool(nulls)) =>
-                        clamp_categorical(data, categories, nulls)?.into(),
+                        clamp_categorical(data, categories, nulls, enforce_constant_time)?.into(),
                     (Array::Float(_), Jagged::Float(_), Array::Float(_)) =>
                         return Err("float clamping is not supported".into()),
 //                        clamp_categorical(&data, &categories, &nulls)?.into(),
                     (Array::Int(data), Jagged::Int(categories), Array::Int(nulls)) =>
-                        clamp_categorical(data, categories, nulls)?.into(),
+                        clamp_categorical(data, categories, nulls, enforce_constant_time)?.into(),
                     (Array::Str(data), Jagged::Str(categories), Array::Str(nulls)) =>
-                        clamp_categorical(data, categories, nulls)?.into(),
+                        clamp_categorical(data, categories, nulls, enforce_constant_time)?.into(),
                     _ => return Err("types of data, categories, and null must be consistent".into())
                 }),
                 _ => return Err("data must be ArrayND, categories must be Vector2DJagged, and null must be ArrayND".into())
@@ -34,7 +37,7 @@ impl Evaluable for proto::Clamp {
             match (take_argument(&mut arguments, "data")?, take_argument(&mut arguments, "lower")?, take_argument(&mut arguments, "upper")?) {
                 (Value::Array(data), Value::Array(lower), Value::Array(upper)) => Ok(match (data, lower, upper) {
                     (Array::Float(data), Array::Float(lower), Array::Float(upper)) =>
-                        clamp_numeric_float(data, lower, upper)?.into(),
+                        clamp_numeric_float(data, lower, upper, enforce_constant_time)?.into(),
                     (Array::Int(data), Array::Int(lower), Array::Int(upper)) =>
                         clamp_numeric_integer(data, lower, upper)?.into(),
                     _ => return Err("data, lower, and upper must all have type f64".into())
@@ -51,6 +54,7 @@ impl Evaluable for proto::Clamp {
 /// * `data` - Data to be clamped.
 /// * `lower` - Desired lower bound for each column of the data.
 /// * `upper` - Desired upper bound for each column of the data.
+/// * `enforce_constant_time` - Whether to process every cell identically, regardless of whether it is `NAN`.
 ///
 /// # Return
 /// Data clamped to desired bounds.
@@ -64,26 +68,40 @@ impl Evaluable for proto::Clamp {
 /// let lower: ArrayD<Float> = arr1(&[0.5, 8., 4.]).into_dyn();
 /// let upper: ArrayD<Float> = arr1(&[2.5, 10., 12.]).into_dyn();
 ///
-/// let clamped_data = clamp_numeric_float(data, lower, upper).unwrap();
+/// let clamped_data = clamp_numeric_float(data, lower, upper, false).unwrap();
 /// assert_eq!(clamped_data, arr2(&[ [1., 8., 4.], [2.5, 10., 9.] ]).into_dyn());
 /// ```
 pub fn clamp_numeric_float(
-    mut data: ArrayD<Float>, lower: ArrayD<Float>, upper: ArrayD<Float>
+    mut data: ArrayD<Float>, lower: ArrayD<Float>, upper: ArrayD<Float>,
+    enforce_constant_time: bool
 )-> Result<ArrayD<Float>> {
 
     let num_columns = get_num_columns(&data)?;
 
+    let lower = standardize_numeric_argument(lower, num_columns)?;
+    let upper = standardize_numeric_argument(upper, num_columns)?;
+
     // iterate over the generalized columns
-    data.gencolumns_mut().into_iter()
+    let columns = data.gencolumns_mut().into_iter()
         // pair generalized columns with arguments
-        .zip(standardize_numeric_argument(lower, num_columns)?.into_iter())
-        .zip(standardize_numeric_argument(upper, num_columns)?.into_iter())
+        .zip(lower.into_iter())
+        .zip(upper.into_iter());
+
+    if enforce_constant_time {
+        // clamp every cell, and only keep the clamped value if the cell is not nan
+        columns.for_each(|((mut column, min), max)| column.iter_mut()
+            .for_each(|v| {
+                let clamped = min.max(max.min(*v));
+                *v = if v.is_nan() { *v } else { clamped }
+            }));
+    } else {
         // for each pairing, iterate over the cells
-        .for_each(|((mut column, min), max)| column.iter_mut()
+        columns.for_each(|((mut column, min), max)| column.iter_mut()
             // ignore nan values
             .filter(|v| !v.is_nan())
             // mutate the cell via the operator
             .for_each(|v| *v = min.max(max.min(*v))));
+    }
 
     Ok(data)
 }
@@ -143,6 +161,7 @@ pub fn clamp_numeric_integer(
 /// * `data` - Data to be clamped.
 /// * `categories` - For each column, the set of categories you want to be represented.
 /// * `null_value` - For each column, the value to which elements not included in `categories` will be mapped.
+/// * `enforce_constant_time` - Whether to compare every cell against every category, and write every cell.
 ///
 /// # Return
 /// Data clamped to desired bounds.
@@ -160,29 +179,78 @@ pub fn clamp_numeric_integer(
 ///                                         "not_a_letter".to_string(),
 ///                                         "not_a_letter".to_string()]).into_dyn();
 ///
-/// let clamped_data = clamp_categorical(data, categories, null_value).unwrap();
+/// let clamped_data = clamp_categorical(data, categories, null_value, false).unwrap();
 /// assert_eq!(clamped_data, arr2(&[["a".to_string(), "b".to_string(), "not_a_letter".to_string()],
 ///                                ["a".to_string(), "not_a_letter".to_string(), "b".to_string()]]).into_dyn());
 /// ```
-pub fn clamp_categorical<T: Ord + Hash + Clone>(
+pub fn clamp_categorical<T: Ord + Hash + Clone + Default>(
     mut data: ArrayD<T>,
     categories: Vec<Vec<T>>,
-    null_value: ArrayD<T>
-) -> Result<ArrayD<T>> where T:Clone, T:PartialEq, T:Default {
+    null_value: ArrayD<T>,
+    enforce_constant_time: bool
+) -> Result<ArrayD<T>> {
 
     let num_columns = get_num_columns(&data)?;
 
     // iterate over the generalized columns
-    data.gencolumns_mut().into_iter()
+    let columns = data.gencolumns_mut().into_iter()
         // pair generalized columns with arguments
         .zip(standardize_categorical_argument(categories.to_vec(), num_columns)?)
-        .zip(standardize_null_target_argument(null_value, num_columns)?)
+        .zip(standardize_null_target_argument(null_value, num_columns)?);
+
+    if enforce_constant_time {
+        // check membership without stopping at the first match, and write every cell
+        columns.for_each(|((mut column, categories), null)| column.iter_mut()
+            .for_each(|v| {
+                let is_known = categories.iter()
+                    .fold(false, |is_known, category| is_known | (*category == *v));
+                *v = if is_known { v.clone() } else { null.clone() }
+            }));
+    } else {
         // for each pairing, iterate over the cells
-        .for_each(|((mut column, categories), null)| column.iter_mut()
+        columns.for_each(|((mut column, categories), null)| column.iter_mut()
             // ignore known values
             .filter(|v| !categories.contains(v))
             // mutate the cell via the operator
             .for_each(|v| *v = null.clone()));
+    }
 
     Ok(data)
 }
+
+
+#[cfg(test)]
+mod test_clamp {
+    use ndarray::{arr1, Array, ArrayD};
+
+    use whitenoise_validator::{Float, Integer};
+
+    use crate::components::clamp::{clamp_categorical, clamp_numeric_float};
+    use crate::utilities::test_constant_time::assert_data_independent_time;
+
+    #[test]
+    #[ignore]
+    fn test_timing_float() {
+        let datasets: Vec<ArrayD<Float>> = vec![
+            Array::zeros(vec![50_000]),
+            Array::from_elem(vec![50_000], 100.),
+            Array::from_elem(vec![50_000], Float::NAN)];
+
+        assert_data_independent_time(&datasets, |data| {
+            clamp_numeric_float(data, arr1(&[-1.]).into_dyn(), arr1(&[1.]).into_dyn(), true).unwrap();
+        });
+    }
+
+    #[test]
+    #[ignore]
+    fn test_timing_categorical() {
+        let datasets: Vec<ArrayD<Integer>> = vec![
+            Array::zeros(vec![50_000]),
+            Array::from_elem(vec![50_000], 9),
+            Array::from_elem(vec![50_000], 100)];
+
+        assert_data_independent_time(&datasets, |data| {
+            clamp_categorical(data, vec![(0..10).collect()], arr1(&[-1]).into_dyn(), true).unwrap();
+        });
+    }
+}
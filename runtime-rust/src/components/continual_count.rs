@@ -0,0 +1,130 @@
+use std::ops::Add;
+
+use ndarray::ArrayD;
+
+use whitenoise_validator::{Float, proto};
+use whitenoise_validator::base::{Array, ReleaseNode};
+use whitenoise_validator::components::continual_count::get_num_levels;
+use whitenoise_validator::errors::*;
+use whitenoise_validator::utilities::take_argument;
+
+use crate::components::Evaluable;
+use crate::NodeArguments;
+use crate::utilities::get_num_columns;
+
+
+impl Evaluable for proto::ContinualCount {
+    fn evaluate(&self, _privacy_definition: &Option<proto::PrivacyDefinition>, mut arguments: NodeArguments) -> Result<ReleaseNode> {
+        Ok(ReleaseNode::new(prefix_sums(
+            &take_argument(&mut arguments, "data")?.array()?.float()?)?.into()))
+    }
+}
+
+impl Evaluable for proto::DyadicSum {
+    fn evaluate(&self, _privacy_definition: &Option<proto::PrivacyDefinition>, mut arguments: NodeArguments) -> Result<ReleaseNode> {
+        Ok(ReleaseNode::new(match take_argument(&mut arguments, "data")?.array()? {
+            Array::Float(data) => dyadic_sum(&data)?.into(),
+            Array::Int(data) => dyadic_sum(&data)?.into(),
+            _ => return Err("data must be numeric".into())
+        }))
+    }
+}
+
+/// Sum a stream over the dyadic intervals of a binary tree.
+///
+/// Row `t` of column `h` contains the sum of the `2^h` elements ending at row `t`,
+/// when `t + 1` is a multiple of `2^h`, and zero otherwise.
+/// Each level is summed from the level below, so each interval is summed pairwise.
+///
+/// # Arguments
+/// * `data` - Time-ordered data with one column.
+///
+/// # Return
+/// Array with one row per element of the data, and one column per level of the tree.
+///
+/// # Example
+/// ```
+/// use ndarray::{arr1, arr2};
+/// use whitenoise_runtime::components::continual_count::dyadic_sum;
+/// let sums = dyadic_sum(&arr1(&[1, 2, 3, 4]).into_dyn()).unwrap();
+/// assert_eq!(sums, arr2(&[[1, 0, 0], [2, 3, 0], [3, 0, 0], [4, 7, 10]]).into_dyn());
+/// ```
+pub fn dyadic_sum<T: Copy + Default + Add<Output=T>>(data: &ArrayD<T>) -> Result<ArrayD<T>> {
+    if get_num_columns(data)? != 1 {
+        return Err("data must contain one column".into())
+    }
+    let data = data.iter().cloned().collect::<Vec<T>>();
+    let num_records = data.len();
+    let num_levels = get_num_levels(num_records as i64) as usize;
+
+    let mut sums = ndarray::Array::from_elem((num_records, num_levels), T::default());
+    sums.column_mut(0).iter_mut().zip(data.into_iter()).for_each(|(sum, v)| *sum = v);
+
+    (1..num_levels).for_each(|level| {
+        let (width, half_width) = (1 << level, 1 << (level - 1));
+        (width - 1..num_records).step_by(width).for_each(|t| {
+            let sum = sums[[t - half_width, level - 1]] + sums[[t, level - 1]];
+            sums[[t, level]] = sum;
+        })
+    });
+
+    Ok(sums.into_dyn())
+}
+
+/// Reconstruct the sum of every prefix of a stream from its dyadic sums.
+///
+/// The first `t` elements are the union of at most one dyadic interval per level, one for each set bit of `t`.
+///
+/// # Arguments
+/// * `sums` - Dyadic sums, as computed by `dyadic_sum`.
+///
+/// # Return
+/// The sum of the first `t` elements, for each `t` from one to the length of the stream.
+///
+/// # Example
+/// ```
+/// use ndarray::{arr1, arr2};
+/// use whitenoise_runtime::components::continual_count::prefix_sums;
+/// let sums = arr2(&[[1., 0., 0.], [2., 3., 0.], [3., 0., 0.], [4., 7., 10.]]).into_dyn();
+/// assert_eq!(prefix_sums(&sums).unwrap(), arr1(&[1., 3., 6., 10.]).into_dyn());
+/// ```
+pub fn prefix_sums(sums: &ArrayD<Float>) -> Result<ArrayD<Float>> {
+    if sums.ndim() != 2 {
+        return Err("dyadic sums must be two-dimensional".into())
+    }
+    let num_records = sums.shape()[0];
+    let num_levels = sums.shape()[1];
+    if num_levels as i64 != get_num_levels(num_records as i64) {
+        return Err("number of columns must match the number of levels of the binary tree".into())
+    }
+
+    Ok(ndarray::Array::from((1..=num_records)
+        .map(|t| {
+            let mut start = 0;
+            (0..num_levels).rev()
+                .filter(|level| t & (1 << level) != 0)
+                .map(|level| {
+                    start += 1 << level;
+                    sums[[start - 1, level]]
+                })
+                .sum::<Float>()
+        })
+        .collect::<Vec<Float>>()).into_dyn())
+}
+
+
+#[cfg(test)]
+mod test_continual_count {
+    use ndarray::Array;
+
+    use crate::components::continual_count::{dyadic_sum, prefix_sums};
+
+    #[test]
+    fn test_prefix_sums() {
+        let data = Array::from_shape_fn(vec![37], |idx| idx[0] as f64);
+        let sums = prefix_sums(&dyadic_sum(&data).unwrap()).unwrap();
+
+        (1..=37).zip(sums.iter())
+            .for_each(|(t, sum)| assert_eq!(*sum, (t * (t - 1) / 2) as f64));
+    }
+}
@@ -7,20 +7,21 @@ use ndarray::{ArrayD, Axis, arr0};
 use whitenoise_validator::{proto, Integer};
 use whitenoise_validator::utilities::take_argument;
 use std::collections::HashSet;
-use crate::utilities::get_num_columns;
-use std::iter::FromIterator;
+use crate::utilities::{get_num_columns, take_number_records};
 use std::hash::Hash;
 use noisy_float::types::n64;
 
 
 impl Evaluable for proto::Count {
-    fn evaluate(&self, _privacy_definition: &Option<proto::PrivacyDefinition>, mut arguments: NodeArguments) -> Result<ReleaseNode> {
+    fn evaluate(&self, privacy_definition: &Option<proto::PrivacyDefinition>, mut arguments: NodeArguments) -> Result<ReleaseNode> {
         Ok(ReleaseNode::new(if self.distinct {
+            // only the distinct count allocates space that would otherwise depend on the data
+            let number_records = take_number_records(privacy_definition, &mut arguments)?;
             match take_argument(&mut arguments, "data")?.array()? {
-                Array::Bool(data) => count_distinct(&data)?.into(),
-                Array::Float(data) => count_distinct(&data.mapv(|v| n64(v as f64)))?.into(),
-                Array::Int(data) => count_distinct(&data)?.into(),
-                Array::Str(data) => count_distinct(&data)?.into()
+                Array::Bool(data) => count_distinct(&data, number_records)?.into(),
+                Array::Float(data) => count_distinct(&data.mapv(|v| n64(v as f64)), number_records)?.into(),
+                Array::Int(data) => count_distinct(&data, number_records)?.into(),
+                Array::Str(data) => count_distinct(&data, number_records)?.into()
             }
         } else {
             match take_argument(&mut arguments, "data")? {
@@ -64,6 +65,7 @@ pub fn count<T>(data: &ArrayD<T>) -> Result<ArrayD<Integer>> {
 ///
 /// # Arguments
 /// * `data` - Data for which you want a distinct count.
+/// * `number_records` - When protecting memory utilization, the public number of records to allocate space for, regardless of how many are distinct.
 ///
 /// # Return
 /// Number of rows in data.
@@ -73,12 +75,17 @@ pub fn count<T>(data: &ArrayD<T>) -> Result<ArrayD<Integer>> {
 /// use ndarray::{ArrayD, arr1, arr2};
 /// use whitenoise_runtime::components::count::count_distinct;
 /// let data = arr2(&[ [false, false, true], [true, false, true] ]).into_dyn();
-/// let distinct = count_distinct(&data).unwrap();
+/// let distinct = count_distinct(&data, None).unwrap();
 /// assert_eq!(distinct, arr2(&[ [2, 1, 1] ]).into_dyn());
 /// ```
-pub fn count_distinct<T: Eq + Hash>(data: &ArrayD<T>) -> Result<ArrayD<Integer>> {
+pub fn count_distinct<T: Eq + Hash>(
+    data: &ArrayD<T>, number_records: Option<usize>
+) -> Result<ArrayD<Integer>> {
     let counts = data.gencolumns().into_iter().map(|column| {
-        HashSet::<&T>::from_iter(column.iter()).len() as Integer
+        // when protecting memory utilization, the set is never resized, regardless of the number of distinct values
+        let mut distinct = HashSet::<&T>::with_capacity(number_records.unwrap_or(0));
+        distinct.extend(column.iter());
+        distinct.len() as Integer
     }).collect::<Vec<Integer>>();
 
     // ensure counts are of correct dimension
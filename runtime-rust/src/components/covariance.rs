@@ -8,32 +8,38 @@ use ndarray::{ArrayD, Array};
 
 use whitenoise_validator::{proto, Float};
 use crate::components::mean::mean;
+use crate::utilities::check_float_overflow;
 use ndarray::prelude::*;
 use std::iter::FromIterator;
 
 impl Evaluable for proto::Covariance {
-    fn evaluate(&self, _privacy_definition: &Option<proto::PrivacyDefinition>, mut arguments: NodeArguments) -> Result<ReleaseNode> {
+    fn evaluate(&self, privacy_definition: &Option<proto::PrivacyDefinition>, mut arguments: NodeArguments) -> Result<ReleaseNode> {
+        let protect_overflow = privacy_definition.as_ref()
+            .map(|v| v.protect_overflow).unwrap_or(false);
         let delta_degrees_of_freedom = if self.finite_sample_correction {1} else {0} as usize;
-        if arguments.contains_key::<IndexKey>(&"data".into()) {
+
+        let covariances = if arguments.contains_key::<IndexKey>(&"data".into()) {
             let data = take_argument(&mut arguments, "data")?.array()?.float()?;
             let covariances = matrix_covariance(&data, delta_degrees_of_freedom)?.into_iter()
                 .flatten()
                 .collect::<Vec<Float>>();
 
             // flatten into a row vector, every column is a release
-            return Ok(ReleaseNode::new(arr1(&covariances).insert_axis(Axis(0)).into_dyn().into()));
-        }
-        if arguments.contains_key::<IndexKey>(&"left".into()) && arguments.contains_key::<IndexKey>(&"right".into()) {
+            arr1(&covariances).insert_axis(Axis(0)).into_dyn()
+        } else if arguments.contains_key::<IndexKey>(&"left".into()) && arguments.contains_key::<IndexKey>(&"right".into()) {
             let left = take_argument(&mut arguments, "left")?.array()?.float()?;
             let right = take_argument(&mut arguments, "right")?.array()?.float()?;
 
             let cross_covariances = matrix_cross_covariance(&left, &right, delta_degrees_of_freedom)?;
 
             // flatten into a row vector, every column is a release
-            return Ok(ReleaseNode::new(Array::from_iter(cross_covariances.iter())
-                .insert_axis(Axis(0)).into_dyn().mapv(|v| *v).into()));
-        }
-        Err("insufficient data supplied to Covariance".into())
+            Array::from_iter(cross_covariances.iter().copied())
+                .insert_axis(Axis(0)).into_dyn()
+        } else {
+            return Err("insufficient data supplied to Covariance".into())
+        };
+
+        Ok(ReleaseNode::new(if protect_overflow { check_float_overflow(covariances)? } else { covariances }.into()))
     }
 }
 
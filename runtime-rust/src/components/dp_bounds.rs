@@ -0,0 +1,161 @@
+use indexmap::indexmap;
+
+use whitenoise_validator::{Float, Integer, proto};
+use whitenoise_validator::base::{Array, ReleaseNode, Value};
+use whitenoise_validator::errors::*;
+use whitenoise_validator::utilities::privacy::{get_epsilon, spread_privacy_usage};
+use whitenoise_validator::utilities::take_argument;
+
+use crate::components::Evaluable;
+use crate::NodeArguments;
+use crate::utilities::get_num_columns;
+use crate::utilities::mechanisms::simple_geometric_mechanism;
+
+
+impl Evaluable for proto::DpBounds {
+    fn evaluate(&self, privacy_definition: &Option<proto::PrivacyDefinition>, mut arguments: NodeArguments) -> Result<ReleaseNode> {
+        let privacy_definition = privacy_definition.as_ref()
+            .ok_or_else(|| Error::from("privacy_definition must be defined"))?;
+
+        // a substitution may decrement the count of one bin and increment the count of another
+        use proto::privacy_definition::Neighboring;
+        let sensitivity = match Neighboring::from_i32(privacy_definition.neighboring)
+            .ok_or_else(|| Error::from("neighboring definition must be either \"AddRemove\" or \"Substitute\""))? {
+            Neighboring::AddRemove => 1.,
+            Neighboring::Substitute => 2.
+        };
+
+        let data = match take_argument(&mut arguments, "data")?.array()? {
+            Array::Float(data) => data,
+            Array::Int(data) => data.mapv(|v| v as Float),
+            _ => return Err("data must be numeric".into())
+        };
+        let num_columns = get_num_columns(&data)? as usize;
+
+        let usages = spread_privacy_usage(&self.privacy_usage, num_columns)?;
+        let epsilon = usages.iter().map(get_epsilon).collect::<Result<Vec<f64>>>()?;
+
+        let (lower, upper): (Vec<Float>, Vec<Float>) = data.gencolumns().into_iter()
+            .zip(epsilon.iter())
+            .map(|(column, epsilon)| dp_bounds(
+                &column.to_vec(), *epsilon, sensitivity,
+                self.min_exponent, self.max_exponent, self.failure_probability,
+                privacy_definition.protect_elapsed_time))
+            .collect::<Result<Vec<(Float, Float)>>>()?
+            .into_iter().unzip();
+
+        Ok(ReleaseNode {
+            value: Value::Dataframe(indexmap![
+                "lower".into() => ndarray::Array::from(lower).into_dyn().into(),
+                "upper".into() => ndarray::Array::from(upper).into_dyn().into()
+            ]),
+            privacy_usages: Some(usages),
+            public: true,
+        })
+    }
+}
+
+/// Estimate the bounds of the data from a noisy histogram over signed powers of two.
+///
+/// The bin edges are `0` and `±2^k` for each exponent `k` from `min_exponent` to `max_exponent`,
+/// so each record is counted in exactly one bin, and records of greater magnitude are counted in the outermost bins.
+/// Each count is perturbed with Geometric noise of scale `sensitivity / epsilon`.
+/// The threshold is `ceil(sensitivity ln(num_bins / failure_probability) / epsilon)`,
+/// so that a bin without records is found above the threshold with probability at most `failure_probability / num_bins`.
+/// The bounds are the outer edges of the outermost bins found above the threshold,
+/// or the outermost edges if no bin is found above the threshold.
+///
+/// # Arguments
+/// * `data` - Records of one column of the data. Null values are ignored.
+/// * `epsilon` - Multiplicative privacy loss parameter.
+/// * `sensitivity` - Number of counts changed by one record, 1 under AddRemove and 2 under Substitute neighboring.
+/// * `min_exponent` - Exponent of the smallest non-zero bin edge.
+/// * `max_exponent` - Exponent of the largest bin edge.
+/// * `failure_probability` - Probability that any bin without records is found above the threshold.
+/// * `enforce_constant_time` - Whether to force the noise to be sampled in constant time.
+///
+/// # Return
+/// The estimated lower and upper bounds.
+///
+/// # Example
+/// ```
+/// use whitenoise_runtime::components::dp_bounds::dp_bounds;
+/// let data = vec![3.; 1_000];
+/// let (lower, upper) = dp_bounds(&data, 1., 1., -10, 30, 1e-2, false).unwrap();
+/// assert!(lower < upper);
+/// ```
+pub fn dp_bounds(
+    data: &[Float], epsilon: f64, sensitivity: f64,
+    min_exponent: i32, max_exponent: i32, failure_probability: f64,
+    enforce_constant_time: bool,
+) -> Result<(Float, Float)> {
+    if epsilon <= 0. {
+        return Err("epsilon must be positive".into())
+    }
+    if failure_probability <= 0. || failure_probability >= 1. {
+        return Err("failure_probability must be within (0, 1)".into())
+    }
+    if min_exponent >= max_exponent {
+        return Err("min_exponent must be less than max_exponent".into())
+    }
+
+    // edges are -2^max, ..., -2^min, 0, 2^min, ..., 2^max
+    let magnitudes = (min_exponent..=max_exponent)
+        .map(|exponent| (2. as Float).powi(exponent))
+        .collect::<Vec<Float>>();
+    let edges = magnitudes.iter().rev().map(|v| -v)
+        .chain(vec![0.])
+        .chain(magnitudes.iter().cloned())
+        .collect::<Vec<Float>>();
+    if !edges.iter().all(|edge| edge.is_finite()) {
+        return Err("powers of two must be finite".into())
+    }
+    let num_bins = edges.len() - 1;
+
+    // the bin of each record is the number of interior edges at or below it
+    let mut counts = vec![0 as Integer; num_bins];
+    data.iter().filter(|v| !v.is_nan())
+        .for_each(|v| counts[edges[1..num_bins].iter().filter(|edge| *edge <= v).count()] += 1);
+
+    let threshold = get_bounds_threshold(epsilon, sensitivity, num_bins, failure_probability);
+    let above_threshold = counts.into_iter()
+        .map(|count| Ok(count + simple_geometric_mechanism(
+            epsilon, sensitivity, 0, Integer::MAX, enforce_constant_time)? >= threshold))
+        .collect::<Result<Vec<bool>>>()?;
+
+    Ok(match (above_threshold.iter().position(|v| *v), above_threshold.iter().rposition(|v| *v)) {
+        (Some(first), Some(last)) => (edges[first], edges[last + 1]),
+        _ => (edges[0], edges[num_bins])
+    })
+}
+
+/// The smallest noisy count of a bin that may widen the bounds.
+pub fn get_bounds_threshold(epsilon: f64, sensitivity: f64, num_bins: usize, failure_probability: f64) -> Integer {
+    (sensitivity * (num_bins as f64 / failure_probability).ln() / epsilon).ceil() as Integer
+}
+
+
+#[cfg(test)]
+mod test_dp_bounds {
+    use crate::components::dp_bounds::dp_bounds;
+
+    #[test]
+    fn test_bounds() {
+        let data = (0..1_000).map(|i| match i % 2 {
+            0 => 100.,
+            _ => -0.1
+        }).collect::<Vec<f64>>();
+
+        let (lower, upper) = dp_bounds(&data, 1., 1., -10, 30, 1e-6, false).unwrap();
+
+        // the bins of the records are found, and the empty bins are not
+        assert_eq!(lower, -0.125);
+        assert_eq!(upper, 128.);
+    }
+
+    #[test]
+    fn test_no_records() {
+        let (lower, upper) = dp_bounds(&[], 1., 1., -10, 30, 1e-6, false).unwrap();
+        assert_eq!((lower, upper), (-(2f64).powi(30), (2f64).powi(30)));
+    }
+}
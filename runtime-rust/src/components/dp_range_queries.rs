@@ -0,0 +1,163 @@
+use ndarray::ArrayD;
+
+use whitenoise_validator::{Float, Integer, proto};
+use whitenoise_validator::base::{IndexKey, ReleaseNode};
+use whitenoise_validator::errors::*;
+
+use crate::components::Evaluable;
+use crate::NodeArguments;
+
+
+impl Evaluable for proto::DpRangeQueries {
+    fn evaluate(&self, _privacy_definition: &Option<proto::PrivacyDefinition>, mut arguments: NodeArguments) -> Result<ReleaseNode> {
+        let queries = match arguments.remove::<IndexKey>(&"queries".into()) {
+            Some(queries) => Some(queries.array()?.int()?),
+            None => None
+        };
+
+        // the final count of each level is of the records outside of the edges, and is not part of the tree
+        let levels = (0..arguments.len())
+            .map(|level| {
+                let counts = arguments.remove::<IndexKey>(&IndexKey::Int(level as Integer))
+                    .ok_or_else(|| Error::from(format!("level {}: missing", level)))?
+                    .array()?.float()?;
+                let num_bins = counts.len().checked_sub(1)
+                    .ok_or_else(|| Error::from(format!("level {}: must not be empty", level)))?;
+                Ok(counts.iter().take(num_bins).cloned().collect())
+            })
+            .collect::<Result<Vec<Vec<Float>>>>()?;
+
+        let counts = constrained_inference(&levels, self.branching as usize)?;
+
+        Ok(ReleaseNode::new(match queries {
+            Some(queries) => range_queries(&counts, &queries)?,
+            None => ndarray::Array::from(counts).into_dyn()
+        }.into()))
+    }
+}
+
+/// Make the noisy counts of a tree consistent, so that the count of each node is the sum of the counts of its children.
+///
+/// Computes the least squares estimate of the counts of the bins, where the noise of every node has equal variance,
+/// as in [Hay, Rastogi, Miklau & Suciu (2010)](https://arxiv.org/abs/0904.0942).
+/// The first pass combines the count of each node with the estimates of its children, from the leaves up,
+/// weighted by the inverse of their variances.
+/// The second pass distributes the difference between each node and the sum of its children, from the root down.
+/// Unlike the closed form of Hay et al., the final node of each level may have fewer than `branching` children.
+///
+/// # Arguments
+/// * `levels` - Noisy counts of the nodes of each level, from the leaves to the root.
+/// * `branching` - Number of children of each node.
+///
+/// # Return
+/// Consistent counts of the leaves.
+///
+/// # Example
+/// ```
+/// use whitenoise_runtime::components::dp_range_queries::constrained_inference;
+/// let counts = constrained_inference(&vec![vec![1., 2.], vec![6.]], 2).unwrap();
+/// assert!((counts[0] - 2.).abs() < 1e-8 && (counts[1] - 3.).abs() < 1e-8);
+/// ```
+pub fn constrained_inference(levels: &[Vec<Float>], branching: usize) -> Result<Vec<Float>> {
+    if branching < 2 {
+        return Err("branching must be at least two".into())
+    }
+    let leaves = levels.first().ok_or_else(|| Error::from("levels must not be empty"))?;
+
+    // the children of node j are nodes [j * branching, (j + 1) * branching) of the level below
+    let get_children = |parent: usize, num_children: usize| (parent * branching)..num_children.min((parent + 1) * branching);
+
+    // bottom-up: estimates and variances of each node, given the counts of the node and its descendants
+    let mut estimates = vec![leaves.clone()];
+    let mut variances = vec![vec![1.; leaves.len()]];
+    for counts in levels.iter().skip(1) {
+        let (children_estimates, children_variances) = (estimates.last().unwrap(), variances.last().unwrap());
+        if counts.len() != children_estimates.len().div_ceil(branching) {
+            return Err("the number of nodes of each level must be consistent with the branching factor".into())
+        }
+
+        let (level_estimates, level_variances) = counts.iter().enumerate()
+            .map(|(parent, count)| {
+                let children = get_children(parent, children_estimates.len());
+                let children_sum = children_estimates[children.clone()].iter().sum::<Float>();
+                let children_variance = children_variances[children].iter().sum::<Float>();
+
+                let variance = 1. / (1. + 1. / children_variance);
+                (variance * (count + children_sum / children_variance), variance)
+            })
+            .unzip::<Float, Float, Vec<Float>, Vec<Float>>();
+        estimates.push(level_estimates);
+        variances.push(level_variances);
+    }
+
+    // top-down: distribute the residual of each node to its children, in proportion to their variances
+    let mut consistent = estimates.pop().unwrap();
+    variances.pop();
+    while let Some(mut children_estimates) = estimates.pop() {
+        let children_variances = variances.pop().unwrap();
+        consistent.iter().enumerate().for_each(|(parent, parent_estimate)| {
+            let children = get_children(parent, children_estimates.len());
+            let residual = parent_estimate - children_estimates[children.clone()].iter().sum::<Float>();
+            let children_variance = children_variances[children.clone()].iter().sum::<Float>();
+            children.for_each(|child| children_estimates[child] += residual * children_variances[child] / children_variance);
+        });
+        consistent = children_estimates;
+    }
+
+    Ok(consistent)
+}
+
+/// Answer interval queries from the counts of each bin.
+///
+/// # Arguments
+/// * `counts` - Count of each bin.
+/// * `queries` - Array with two columns, where each row `[i, j]` is a query for the total count of bins `i` through `j - 1`.
+///
+/// # Return
+/// The answer to each query.
+pub fn range_queries(counts: &[Float], queries: &ArrayD<Integer>) -> Result<ArrayD<Float>> {
+    if queries.ndim() != 2 || queries.shape()[1] != 2 {
+        return Err("queries must have two columns".into())
+    }
+
+    let mut prefix_sums = vec![0.];
+    counts.iter().for_each(|count| prefix_sums.push(prefix_sums.last().unwrap() + count));
+
+    Ok(ndarray::Array::from(queries.genrows().into_iter()
+        .map(|query| {
+            let (lower, upper) = (query[0], query[1]);
+            if lower < 0 || lower > upper || upper as usize > counts.len() {
+                return Err("queries must be ordered indices into the edges".into())
+            }
+            Ok(prefix_sums[upper as usize] - prefix_sums[lower as usize])
+        })
+        .collect::<Result<Vec<Float>>>()?).into_dyn())
+}
+
+
+#[cfg(test)]
+mod test_dp_range_queries {
+    use ndarray::arr2;
+
+    use crate::components::dp_range_queries::{constrained_inference, range_queries};
+
+    #[test]
+    fn test_constrained_inference() {
+        // the final node of the second level has a single child
+        let levels = vec![vec![1., 2., 3., 4., 5.], vec![3.5, 6.5, 5.5], vec![10., 5.], vec![16.]];
+        let counts = constrained_inference(&levels, 2).unwrap();
+
+        // consistent counts are unchanged
+        let levels = vec![counts.clone(), vec![
+            counts[0] + counts[1], counts[2] + counts[3], counts[4]
+        ], vec![
+            counts[0] + counts[1] + counts[2] + counts[3], counts[4]
+        ], vec![counts.iter().sum()]];
+        constrained_inference(&levels, 2).unwrap().iter().zip(counts.iter())
+            .for_each(|(a, b)| assert!((a - b).abs() < 1e-8));
+
+        let answers = range_queries(&counts, &arr2(&[[0, 5], [1, 3]]).into_dyn()).unwrap();
+        assert!((answers[0] - counts.iter().sum::<f64>()).abs() < 1e-8);
+        assert!((answers[1] - counts[1] - counts[2]).abs() < 1e-8);
+    }
+}
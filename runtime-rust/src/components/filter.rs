@@ -10,18 +10,21 @@ use ndarray::{ArrayD, Axis, Array1};
 use whitenoise_validator::proto;
 
 use whitenoise_validator::utilities::array::slow_select;
-use crate::utilities::to_nd;
+use crate::utilities::{select_rows_constant_time, select_rows_with_full_capacity, take_number_records, to_nd};
 
 
 impl Evaluable for proto::Filter {
-    fn evaluate(&self, _privacy_definition: &Option<proto::PrivacyDefinition>, mut arguments: NodeArguments) -> Result<ReleaseNode> {
+    fn evaluate(&self, privacy_definition: &Option<proto::PrivacyDefinition>, mut arguments: NodeArguments) -> Result<ReleaseNode> {
+        let number_records = take_number_records(privacy_definition, &mut arguments)?;
+        let enforce_constant_time = privacy_definition.as_ref()
+            .map(|v| v.protect_elapsed_time).unwrap_or(false);
         let mask = take_argument(&mut arguments, "mask")?.array()?.bool()?;
 
         Ok(ReleaseNode::new(match take_argument(&mut arguments, "data")?.array()? {
-            Array::Str(data) => filter(data, mask)?.into(),
-            Array::Float(data) => filter(data, mask)?.into(),
-            Array::Int(data) => filter(data, mask)?.into(),
-            Array::Bool(data) => filter(data, mask)?.into(),
+            Array::Str(data) => filter(data, mask, number_records, enforce_constant_time)?.into(),
+            Array::Float(data) => filter(data, mask, number_records, enforce_constant_time)?.into(),
+            Array::Int(data) => filter(data, mask, number_records, enforce_constant_time)?.into(),
+            Array::Bool(data) => filter(data, mask, number_records, enforce_constant_time)?.into(),
         }))
     }
 }
@@ -31,6 +34,8 @@ impl Evaluable for proto::Filter {
 /// # Arguments
 /// * `data` - Data to be filtered.
 /// * `mask` - Boolean mask giving whether or not each row should be kept.
+/// * `number_records` - When protecting memory utilization, the public number of records to allocate space for, regardless of how many are kept.
+/// * `enforce_constant_time` - Whether to copy every row of the data, regardless of how many are kept.
 ///
 /// # Return
 /// Data with only the desired rows.
@@ -42,16 +47,101 @@ impl Evaluable for proto::Filter {
 ///
 /// let data = arr2(&[ [1, 2, 3], [4, 5, 6], [7, 8, 9], [10, 11, 12] ]).into_dyn();
 /// let mask = arr1(&[true, false, true, false]).into_dyn();
-/// let filtered = filter(data, mask).unwrap();
+/// let filtered = filter(data, mask, None, false).unwrap();
 /// assert_eq!(filtered, arr2(&[ [1, 2, 3], [7, 8, 9] ]).into_dyn());
 /// ```
-pub fn filter<T: Clone + Default>(data: ArrayD<T>, mask: ArrayD<bool>) -> Result<ArrayD<T>> {
+pub fn filter<T: Clone + Default>(
+    data: ArrayD<T>, mask: ArrayD<bool>,
+    number_records: Option<usize>, enforce_constant_time: bool
+) -> Result<ArrayD<T>> {
 
     let columnar_mask: Array1<bool> = to_nd(mask, 1)?.into_dimensionality::<Ix1>()?;
 
-    let mask_indices: Vec<usize> = columnar_mask.iter().enumerate()
+    // the constant-time selection also allocates space for every row
+    if enforce_constant_time {
+        return select_rows_constant_time(&data, &columnar_mask.to_vec())
+    }
+
+    // when protecting memory utilization, reserve space for every public record up front
+    let mut mask_indices: Vec<usize> = Vec::with_capacity(number_records.unwrap_or(0));
+    mask_indices.extend(columnar_mask.iter().enumerate()
         .filter(|(_index, &v)| v)
-        .map(|(index, _)| index)
-        .collect();
-    Ok(slow_select(&data, Axis(0), &mask_indices))
-}
\ No newline at end of file
+        .map(|(index, _)| index));
+
+    match number_records {
+        Some(number_records) => select_rows_with_full_capacity(&data, &mask_indices, number_records),
+        None => Ok(slow_select(&data, Axis(0), &mask_indices))
+    }
+}
+
+#[cfg(test)]
+mod test_filter {
+    use ndarray::{arr1, Array, ArrayD};
+
+    use whitenoise_validator::bindings::Analysis;
+    use whitenoise_validator::proto;
+
+    use crate::components::filter::filter;
+    use crate::utilities::test_constant_time::assert_data_independent_time;
+
+    #[test]
+    fn test_capacity() {
+        let data: ArrayD<f64> = Array::zeros(vec![100, 4]);
+        let masks = vec![
+            Array::from_elem(vec![100], true),
+            Array::from_elem(vec![100], false),
+            Array::from_shape_fn(vec![100], |idx| idx[0] % 3 == 0)];
+
+        // the allocation is sized from the public number of records, regardless of how many rows are kept
+        masks.into_iter().for_each(|mask| assert_eq!(
+            filter(data.clone(), mask, Some(100), false).unwrap().into_raw_vec().capacity(), 400));
+    }
+
+    #[test]
+    fn test_protect_memory_utilization() {
+        let mut analysis = Analysis::new();
+        analysis.privacy_definition.protect_memory_utilization = true;
+        let data = analysis.literal()
+            .value(arr1(&[1., 2., 3., 4.]).into_dyn().into()).value_public(false).build();
+        let number_columns = analysis.literal().value(1.into()).value_public(true).build();
+        let number_rows = analysis.literal().value(4.into()).value_public(true).build();
+        let lower = analysis.literal().value(0.0.into()).value_public(true).build();
+        let upper = analysis.literal().value(10.0.into()).value_public(true).build();
+        let casted = analysis.to_float(data).build();
+        let resized = analysis.resize(casted)
+            .number_columns(number_columns).number_rows(number_rows).lower(lower).upper(upper)
+            .build();
+        let mask = analysis.literal()
+            .value(arr1(&[true, false, true, false]).into_dyn().into()).value_public(true).build();
+        let filtered = analysis.filter(resized, mask).build();
+
+        // the filter is expanded with the public number of records of the resized data
+        let (mut release, _warnings) = crate::release(
+            Some(analysis.privacy_definition.clone()), analysis.components.clone(),
+            analysis.release.clone(), proto::FilterLevel::All).unwrap();
+        let values = release.remove(&filtered).unwrap().value.array().unwrap().float().unwrap();
+        assert_eq!(values.len(), 2);
+        assert_eq!(values.into_raw_vec().capacity(), 4);
+
+        // the number of records is unknown after filtering, so filtering again is not evaluated
+        let refiltered = analysis.filter(filtered, mask).build();
+        let (release, warnings) = crate::release(
+            Some(analysis.privacy_definition.clone()), analysis.components,
+            analysis.release, proto::FilterLevel::All).unwrap();
+        assert!(!release.contains_key(&refiltered));
+        assert!(!warnings.is_empty());
+    }
+
+    #[test]
+    #[ignore]
+    fn test_timing() {
+        let data: ArrayD<f64> = Array::zeros(vec![10_000, 4]);
+        let masks = vec![
+            Array::from_elem(vec![10_000], true),
+            Array::from_elem(vec![10_000], false),
+            Array::from_shape_fn(vec![10_000], |idx| idx[0] % 2 == 0)];
+
+        assert_data_independent_time(
+            &masks, |mask| { filter(data.clone(), mask, None, true).unwrap(); });
+    }
+}
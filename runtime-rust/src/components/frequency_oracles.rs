@@ -0,0 +1,368 @@
+use ndarray::{ArrayD, Axis};
+
+use whitenoise_validator::{Float, Integer, proto};
+use whitenoise_validator::base::{Array, ReleaseNode};
+use whitenoise_validator::errors::*;
+use whitenoise_validator::utilities::privacy::{get_epsilon, spread_privacy_usage};
+use whitenoise_validator::utilities::take_argument;
+
+use crate::components::Evaluable;
+use crate::NodeArguments;
+use crate::utilities::noise;
+
+
+macro_rules! evaluate_oracle {
+    ($oracle:ident, $function:ident) => {
+        impl Evaluable for proto::$oracle {
+            fn evaluate(&self, privacy_definition: &Option<proto::PrivacyDefinition>, mut arguments: NodeArguments) -> Result<ReleaseNode> {
+                let enforce_constant_time = privacy_definition.as_ref()
+                    .map(|v| v.protect_elapsed_time).unwrap_or(false);
+
+                let usages = spread_privacy_usage(&self.privacy_usage, 1)?;
+                let epsilon = get_epsilon(&usages[0])?;
+
+                let data = match (take_argument(&mut arguments, "data")?.array()?, take_argument(&mut arguments, "categories")?.array()?) {
+                    (Array::Bool(data), Array::Bool(categories)) =>
+                        $function(&get_category_indices(&data, &categories.into_raw_vec())?, epsilon, enforce_constant_time)?.into(),
+                    (Array::Int(data), Array::Int(categories)) =>
+                        $function(&get_category_indices(&data, &categories.into_raw_vec())?, epsilon, enforce_constant_time)?.into(),
+                    (Array::Str(data), Array::Str(categories)) =>
+                        $function(&get_category_indices(&data, &categories.into_raw_vec())?, epsilon, enforce_constant_time)?.into(),
+                    _ => return Err("data and categories must be homogeneously typed, and not float".into())
+                };
+
+                Ok(ReleaseNode {
+                    value: data,
+                    privacy_usages: Some(usages),
+                    public: true,
+                })
+            }
+        }
+    }
+}
+
+evaluate_oracle!(OptimizedUnaryEncoding, optimized_unary_encoding);
+evaluate_oracle!(OptimizedLocalHashing, optimized_local_hashing);
+evaluate_oracle!(HadamardResponse, hadamard_response);
+
+impl Evaluable for proto::OptimizedUnaryEncodingEstimate {
+    fn evaluate(&self, _privacy_definition: &Option<proto::PrivacyDefinition>, mut arguments: NodeArguments) -> Result<ReleaseNode> {
+        let epsilon = get_epsilon(&spread_privacy_usage(&self.privacy_usage, 1)?[0])?;
+        let num_categories = take_argument(&mut arguments, "categories")?.array()?.num_records()?;
+        let data = take_argument(&mut arguments, "data")?.array()?.bool()?;
+        Ok(ReleaseNode::new(optimized_unary_encoding_estimate(&data, num_categories, epsilon)?.into()))
+    }
+}
+
+impl Evaluable for proto::OptimizedLocalHashingEstimate {
+    fn evaluate(&self, _privacy_definition: &Option<proto::PrivacyDefinition>, mut arguments: NodeArguments) -> Result<ReleaseNode> {
+        let epsilon = get_epsilon(&spread_privacy_usage(&self.privacy_usage, 1)?[0])?;
+        let num_categories = take_argument(&mut arguments, "categories")?.array()?.num_records()?;
+        let data = take_argument(&mut arguments, "data")?.array()?.int()?;
+        Ok(ReleaseNode::new(optimized_local_hashing_estimate(&data, num_categories, epsilon)?.into()))
+    }
+}
+
+impl Evaluable for proto::HadamardResponseEstimate {
+    fn evaluate(&self, _privacy_definition: &Option<proto::PrivacyDefinition>, mut arguments: NodeArguments) -> Result<ReleaseNode> {
+        let epsilon = get_epsilon(&spread_privacy_usage(&self.privacy_usage, 1)?[0])?;
+        let num_categories = take_argument(&mut arguments, "categories")?.array()?.num_records()?;
+        let data = take_argument(&mut arguments, "data")?.array()?.int()?;
+        Ok(ReleaseNode::new(hadamard_response_estimate(&data, num_categories, epsilon)?.into()))
+    }
+}
+
+
+/// Replace each value of categorical data with the index of its category.
+///
+/// # Arguments
+/// * `data` - Categorical data, where every value is a member of `categories`.
+/// * `categories` - The possible values of the data.
+///
+/// # Return
+/// The index of each value in `categories`, and the number of categories.
+pub fn get_category_indices<T: PartialEq>(data: &ArrayD<T>, categories: &[T]) -> Result<(Vec<usize>, usize)> {
+    if categories.len() < 2 {
+        return Err("there must be at least two categories".into())
+    }
+    Ok((data.iter()
+        .map(|v| categories.iter().position(|category| category == v)
+            .ok_or_else(|| Error::from("data must only contain members of categories")))
+        .collect::<Result<Vec<usize>>>()?, categories.len()))
+}
+
+/// Privatize category indices via Optimized Unary Encoding.
+///
+/// Each index is one-hot encoded. The bit of the true category is set with probability 1/2,
+/// and each other bit is set with probability `1 / (exp(epsilon) + 1)`.
+///
+/// # Arguments
+/// * `indices` - The index of the category of each row, and the number of categories `k`.
+/// * `epsilon` - Privacy loss parameter of each row.
+/// * `enforce_constant_time` - Whether to sample in constant time.
+///
+/// # Return
+/// Perturbed encodings, with one row for each index and `k` columns.
+///
+/// # Example
+/// ```
+/// use whitenoise_runtime::components::frequency_oracles::optimized_unary_encoding;
+/// let encoded = optimized_unary_encoding(&(vec![0, 2, 1], 3), 1., false).unwrap();
+/// assert_eq!(encoded.shape(), &[3, 3]);
+/// ```
+pub fn optimized_unary_encoding(
+    (indices, num_categories): &(Vec<usize>, usize), epsilon: f64, enforce_constant_time: bool
+) -> Result<ArrayD<bool>> {
+    if epsilon <= 0. {
+        return Err("epsilon must be positive".into())
+    }
+    let flip_prob = 1. / (epsilon.exp() + 1.);
+
+    let bits = indices.iter()
+        .map(|index| (0..*num_categories)
+            .map(|category| noise::sample_bit_prob(
+                if category == *index { 0.5 } else { flip_prob }, enforce_constant_time))
+            .collect::<Result<Vec<bool>>>())
+        .collect::<Result<Vec<Vec<bool>>>>()?;
+
+    Ok(ArrayD::from_shape_vec(vec![indices.len(), *num_categories], bits.concat())?)
+}
+
+/// Estimate the number of rows in each category from Optimized Unary Encoding.
+///
+/// # Arguments
+/// * `data` - Encodings released by `optimized_unary_encoding`.
+/// * `num_categories` - Number of categories `k`.
+/// * `epsilon` - Privacy loss parameter used to perturb each row.
+///
+/// # Return
+/// Unbiased estimate of the count of each category.
+pub fn optimized_unary_encoding_estimate(
+    data: &ArrayD<bool>, num_categories: usize, epsilon: f64
+) -> Result<ArrayD<Float>> {
+    if data.ndim() != 2 || data.len_of(Axis(1)) != num_categories {
+        return Err("data must have one column for each category".into())
+    }
+    let (keep_prob, flip_prob) = (0.5, 1. / (epsilon.exp() + 1.));
+    let num_records = data.len_of(Axis(0)) as f64;
+
+    Ok(ndarray::Array::from(data.gencolumns().into_iter()
+        .map(|column| column.iter().filter(|v| **v).count() as f64)
+        .map(|count| (count - num_records * flip_prob) / (keep_prob - flip_prob))
+        .collect::<Vec<Float>>()).into_dyn())
+}
+
+/// Number of hash buckets used by Optimized Local Hashing.
+fn get_num_buckets(epsilon: f64) -> Integer {
+    (epsilon.exp().round() as Integer + 1).max(2)
+}
+
+/// Hash a category index into one of `num_buckets` buckets, with a hash function selected by `seed`.
+///
+/// The hash family is public. The privacy of each row relies only on the randomized response over the buckets.
+fn local_hash(seed: Integer, index: usize, num_buckets: Integer) -> Integer {
+    // splitmix64 finalizer
+    let mut z = (seed as u64) ^ (index as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^= z >> 31;
+    (z % num_buckets as u64) as Integer
+}
+
+/// Privatize category indices via Optimized Local Hashing.
+///
+/// Each row samples a seed to select a hash function, hashes its index into `round(exp(epsilon)) + 1` buckets,
+/// and reports the bucket via randomized response over the buckets.
+///
+/// # Arguments
+/// * `indices` - The index of the category of each row, and the number of categories `k`.
+/// * `epsilon` - Privacy loss parameter of each row.
+/// * `enforce_constant_time` - Whether to sample in constant time.
+///
+/// # Return
+/// One row for each index, with the seed in the first column and the perturbed bucket in the second.
+///
+/// # Example
+/// ```
+/// use whitenoise_runtime::components::frequency_oracles::optimized_local_hashing;
+/// let hashed = optimized_local_hashing(&(vec![0, 2, 1], 3), 1., false).unwrap();
+/// assert_eq!(hashed.shape(), &[3, 2]);
+/// ```
+pub fn optimized_local_hashing(
+    (indices, _num_categories): &(Vec<usize>, usize), epsilon: f64, enforce_constant_time: bool
+) -> Result<ArrayD<Integer>> {
+    if epsilon <= 0. {
+        return Err("epsilon must be positive".into())
+    }
+    let num_buckets = get_num_buckets(epsilon);
+    let keep_prob = epsilon.exp() / (epsilon.exp() + (num_buckets - 1) as f64);
+
+    let reports = indices.iter()
+        .map(|index| {
+            let seed = noise::sample_uniform_int(0, u32::MAX as Integer)?;
+            let bucket = local_hash(seed, *index, num_buckets);
+
+            // both samples are always drawn, so that the time taken does not depend on the data
+            let keep = noise::sample_bit_prob(keep_prob, enforce_constant_time)?;
+            let other = if num_buckets == 2 { 0 } else { noise::sample_uniform_int(0, num_buckets - 2)? };
+            let other = if other >= bucket { other + 1 } else { other };
+
+            Ok(vec![seed, if keep { bucket } else { other }])
+        })
+        .collect::<Result<Vec<Vec<Integer>>>>()?;
+
+    Ok(ArrayD::from_shape_vec(vec![indices.len(), 2], reports.concat())?)
+}
+
+/// Estimate the number of rows in each category from Optimized Local Hashing.
+///
+/// A row supports every category that hashes to its reported bucket under its seed.
+///
+/// # Arguments
+/// * `data` - Reports released by `optimized_local_hashing`.
+/// * `num_categories` - Number of categories `k`.
+/// * `epsilon` - Privacy loss parameter used to perturb each row.
+///
+/// # Return
+/// Unbiased estimate of the count of each category.
+pub fn optimized_local_hashing_estimate(
+    data: &ArrayD<Integer>, num_categories: usize, epsilon: f64
+) -> Result<ArrayD<Float>> {
+    if data.ndim() != 2 || data.len_of(Axis(1)) != 2 {
+        return Err("data must have two columns".into())
+    }
+    let num_buckets = get_num_buckets(epsilon);
+    let keep_prob = epsilon.exp() / (epsilon.exp() + (num_buckets - 1) as f64);
+    let support_prob = 1. / num_buckets as f64;
+    let num_records = data.len_of(Axis(0)) as f64;
+
+    Ok(ndarray::Array::from((0..num_categories)
+        .map(|index| data.genrows().into_iter()
+            .filter(|report| local_hash(report[0], index, num_buckets) == report[1])
+            .count() as f64)
+        .map(|count| (count - num_records * support_prob) / (keep_prob - support_prob))
+        .collect::<Vec<Float>>()).into_dyn())
+}
+
+/// Number of columns of the Hadamard matrix, where row zero is skipped.
+fn get_hadamard_size(num_categories: usize) -> Integer {
+    (num_categories + 1).next_power_of_two() as Integer
+}
+
+/// Whether the entry of the Hadamard matrix at the row of the category and the given column is positive.
+fn hadamard_is_positive(index: usize, column: Integer) -> bool {
+    ((index as Integer + 1) & column).count_ones().is_multiple_of(2)
+}
+
+/// Privatize category indices via Hadamard Response.
+///
+/// Category `i` is assigned row `i + 1` of a Hadamard matrix of size `K`, the smallest power of two greater than `k`.
+/// With probability `exp(epsilon) / (exp(epsilon) + 1)` a column is reported uniformly from where that row is positive,
+/// and otherwise uniformly from where it is negative.
+///
+/// # Arguments
+/// * `indices` - The index of the category of each row, and the number of categories `k`.
+/// * `epsilon` - Privacy loss parameter of each row.
+/// * `enforce_constant_time` - Whether to sample in constant time.
+///
+/// # Return
+/// A column of the Hadamard matrix for each index.
+///
+/// # Example
+/// ```
+/// use whitenoise_runtime::components::frequency_oracles::hadamard_response;
+/// let reports = hadamard_response(&(vec![0, 2, 1], 3), 1., false).unwrap();
+/// assert!(reports.iter().all(|v| 0 <= *v && *v < 4));
+/// ```
+pub fn hadamard_response(
+    (indices, num_categories): &(Vec<usize>, usize), epsilon: f64, enforce_constant_time: bool
+) -> Result<ArrayD<Integer>> {
+    if epsilon <= 0. {
+        return Err("epsilon must be positive".into())
+    }
+    let size = get_hadamard_size(*num_categories);
+    let positive_prob = epsilon.exp() / (epsilon.exp() + 1.);
+
+    let reports = indices.iter()
+        .map(|index| {
+            let column = noise::sample_uniform_int(0, size - 1)?;
+            let positive = noise::sample_bit_prob(positive_prob, enforce_constant_time)?;
+            // flipping the lowest set bit of the row index flips the sign, and is a bijection between the two halves
+            let row = *index as Integer + 1;
+            Ok(if hadamard_is_positive(*index, column) == positive { column } else { column ^ (row & -row) })
+        })
+        .collect::<Result<Vec<Integer>>>()?;
+
+    Ok(ndarray::Array::from(reports).into_dyn())
+}
+
+/// Estimate the number of rows in each category from Hadamard Response.
+///
+/// Rows of the Hadamard matrix are orthogonal, so a report lands on the positive half of any other category's row with probability 1/2.
+///
+/// # Arguments
+/// * `data` - Reports released by `hadamard_response`.
+/// * `num_categories` - Number of categories `k`.
+/// * `epsilon` - Privacy loss parameter used to perturb each row.
+///
+/// # Return
+/// Unbiased estimate of the count of each category.
+pub fn hadamard_response_estimate(
+    data: &ArrayD<Integer>, num_categories: usize, epsilon: f64
+) -> Result<ArrayD<Float>> {
+    let size = get_hadamard_size(num_categories);
+    if data.iter().any(|v| *v < 0 || *v >= size) {
+        return Err("data must be columns of the Hadamard matrix".into())
+    }
+    let positive_prob = epsilon.exp() / (epsilon.exp() + 1.);
+    let num_records = data.len() as f64;
+
+    Ok(ndarray::Array::from((0..num_categories)
+        .map(|index| data.iter().filter(|column| hadamard_is_positive(index, **column)).count() as f64)
+        .map(|count| (count - num_records * 0.5) / (positive_prob - 0.5))
+        .collect::<Vec<Float>>()).into_dyn())
+}
+
+
+#[cfg(test)]
+mod test_frequency_oracles {
+    use crate::components::frequency_oracles::*;
+
+    /// a quarter of the rows are in category 0, and the rest are in category 1
+    fn get_indices() -> (Vec<usize>, usize) {
+        ((0..20_000).map(|i| if i % 4 == 0 { 0 } else { 1 }).collect(), 4)
+    }
+
+    fn assert_estimates(estimates: ArrayD<Float>, tolerance: f64) {
+        [5_000., 15_000., 0., 0.].iter().zip(estimates.iter())
+            .for_each(|(expected, estimate)| assert!(
+                (expected - estimate).abs() < tolerance, "estimates: {:?}", estimates));
+    }
+
+    #[test]
+    fn test_optimized_unary_encoding() {
+        let encoded = optimized_unary_encoding(&get_indices(), 2., false).unwrap();
+        assert_estimates(optimized_unary_encoding_estimate(&encoded, 4, 2.).unwrap(), 1_000.);
+    }
+
+    #[test]
+    fn test_optimized_local_hashing() {
+        let hashed = optimized_local_hashing(&get_indices(), 2., false).unwrap();
+        assert_estimates(optimized_local_hashing_estimate(&hashed, 4, 2.).unwrap(), 1_500.);
+    }
+
+    #[test]
+    fn test_hadamard_response() {
+        let reports = hadamard_response(&get_indices(), 2., false).unwrap();
+        assert_estimates(hadamard_response_estimate(&reports, 4, 2.).unwrap(), 1_000.);
+    }
+
+    #[test]
+    fn test_hadamard_orthogonality() {
+        // every pair of distinct rows agree on exactly half of the columns
+        let size = get_hadamard_size(6);
+        (0..6).for_each(|i| (0..6).filter(|j| *j != i).for_each(|j| assert_eq!(
+            (0..size).filter(|c| hadamard_is_positive(i, *c) == hadamard_is_positive(j, *c)).count() as Integer,
+            size / 2)));
+    }
+}
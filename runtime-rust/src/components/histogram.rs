@@ -33,7 +33,9 @@ pub fn histogram<T: Clone + Eq + Ord + std::hash::Hash>(
     let zeros = categories.iter()
         .map(|cat| (cat, 0)).collect::<IndexMap<&T, Integer>>();
 
-    let counts = data.gencolumns().into_iter()
+    // buffers are sized from the public categories, so memory utilization is independent of the data
+    let mut counts = Vec::with_capacity(zeros.len() * get_num_columns(data)? as usize);
+    counts.extend(data.gencolumns().into_iter()
         .map(|column| {
             let mut counts = zeros.clone();
             column.into_iter().for_each(|v| {
@@ -42,7 +44,7 @@ pub fn histogram<T: Clone + Eq + Ord + std::hash::Hash>(
             categories.iter()
                 .map(|cat| counts.get(cat).unwrap())
                 .cloned().collect::<Vec<Integer>>()
-        }).flatten().collect::<Vec<Integer>>();
+        }).flatten());
 
     // ensure histogram is of correct dimension
     Ok(match data.ndim() {
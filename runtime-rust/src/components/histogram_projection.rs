@@ -0,0 +1,118 @@
+use ndarray::ArrayD;
+
+use whitenoise_validator::{Float, proto};
+use whitenoise_validator::base::{Array, IndexKey, ReleaseNode};
+use whitenoise_validator::errors::*;
+use whitenoise_validator::utilities::{prepend, take_argument};
+
+use crate::components::Evaluable;
+use crate::NodeArguments;
+use crate::utilities::get_num_columns;
+
+
+impl Evaluable for proto::HistogramProjection {
+    fn evaluate(&self, _privacy_definition: &Option<proto::PrivacyDefinition>, mut arguments: NodeArguments) -> Result<ReleaseNode> {
+        let to_float = |array: Array| -> Result<ArrayD<Float>> {
+            Ok(match array {
+                Array::Float(array) => array,
+                Array::Int(array) => array.mapv(|v| v as Float),
+                _ => return Err("atomic type must be numeric".into())
+            })
+        };
+
+        let mut data = to_float(take_argument(&mut arguments, "data")?.array()?)
+            .map_err(prepend("data:"))?;
+        let num_columns = get_num_columns(&data)? as usize;
+
+        let totals = match arguments.remove::<IndexKey>(&"total".into()) {
+            Some(total) => {
+                let total = to_float(total.array()?)
+                    .map_err(prepend("total:"))?;
+                match total.len() {
+                    1 => vec![Some(total.iter().next().cloned().unwrap()); num_columns],
+                    len if len == num_columns => total.iter().cloned().map(Some).collect(),
+                    _ => return Err("total: must contain either one total, or one total per column of the data".into())
+                }
+            },
+            None => vec![None; num_columns]
+        };
+
+        data.gencolumns_mut().into_iter().zip(totals.into_iter())
+            .try_for_each(|(mut column, total)| {
+                let counts = column.iter().cloned().collect::<Vec<Float>>();
+                // without a total, the noisy counts are projected onto histograms with the same sum
+                let total = total.unwrap_or_else(|| counts.iter().sum::<Float>().max(0.));
+                column.iter_mut().zip(project_simplex(&counts, total)?.into_iter())
+                    .for_each(|(count, projected)| *count = projected);
+                Ok::<_, Error>(())
+            })?;
+
+        Ok(ReleaseNode::new(data.into()))
+    }
+}
+
+/// Project a vector onto the set of non-negative vectors that sum to `total`, in L2 distance.
+///
+/// The closest such vector is `max(v_i - theta, 0)`, for the unique shift `theta` at which the elements sum to `total`.
+/// The shift is found by sorting, as in
+/// [Duchi, Shalev-Shwartz, Singer & Chandra (2008)](https://doi.org/10.1145/1390156.1390191).
+///
+/// # Arguments
+/// * `values` - Vector to project, for example the noisy counts of a histogram.
+/// * `total` - Sum of the projected vector. Must be non-negative.
+///
+/// # Return
+/// The least squares estimate of the vector, subject to non-negativity and the total.
+///
+/// # Example
+/// ```
+/// use whitenoise_runtime::components::histogram_projection::project_simplex;
+/// let projected = project_simplex(&vec![3., -1., 2.], 4.).unwrap();
+/// assert_eq!(projected, vec![2.5, 0., 1.5]);
+/// ```
+pub fn project_simplex(values: &[Float], total: Float) -> Result<Vec<Float>> {
+    if !total.is_finite() || total < 0. {
+        return Err("total must be non-negative".into())
+    }
+    if values.iter().any(|v| !v.is_finite()) {
+        return Err("values must be finite".into())
+    }
+    if values.is_empty() {
+        return Ok(Vec::new())
+    }
+
+    let mut sorted = values.to_vec();
+    sorted.sort_by(|a, b| b.partial_cmp(a).unwrap());
+
+    // the shift is determined by the largest elements, up to the first that would not remain positive
+    let mut cumulative_sum = 0.;
+    let mut theta = 0.;
+    for (k, value) in sorted.iter().enumerate() {
+        cumulative_sum += value;
+        let candidate = (cumulative_sum - total) / (k + 1) as Float;
+        if k > 0 && value - candidate <= 0. { break }
+        theta = candidate;
+    }
+
+    Ok(values.iter().map(|v| (v - theta).max(0.)).collect())
+}
+
+
+#[cfg(test)]
+mod test_histogram_projection {
+    use crate::components::histogram_projection::project_simplex;
+
+    #[test]
+    fn test_project_simplex() {
+        let projected = project_simplex(&[4., -2., 1., 0.5], 3.).unwrap();
+        assert!((projected.iter().sum::<f64>() - 3.).abs() < 1e-8);
+        assert!(projected.iter().all(|v| *v >= 0.));
+        assert_eq!(projected[1], 0.);
+
+        // vectors that already satisfy the constraints are unchanged
+        assert_eq!(project_simplex(&[1., 0., 2.], 3.).unwrap(), vec![1., 0., 2.]);
+
+        assert_eq!(project_simplex(&[1., 2.], 0.).unwrap(), vec![0., 0.]);
+        assert!(project_simplex(&[1., 2.], -1.).is_err());
+    }
+}
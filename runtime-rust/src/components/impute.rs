@@ -132,10 +132,17 @@ pub fn impute_float_uniform_arrayd(
 pub fn impute_float_uniform<'a, I: Iterator<Item=&'a mut Float>>(
     // column: &mut Vec<Float>,
     // column: &mut ndarray::ArrayBase<ndarray::ViewRepr<&mut Float>, ndarray::Ix1>,
-    column: I,
+    mut column: I,
     (lower, upper): (Float, Float),
     enforce_constant_time: bool
 ) -> Result<()> {
+    if enforce_constant_time {
+        // sample for every cell, so that the time taken does not depend on the number of nan values
+        return column.try_for_each(|v| noise::sample_uniform(
+            lower as f64, upper as f64, enforce_constant_time)
+            .map(|n| *v = if v.is_nan() { n as Float } else { *v }))
+    }
+
     column
         // ignore nan values
         .filter(|v| v.is_nan())
@@ -197,11 +204,19 @@ pub fn impute_float_gaussian_arrayd(
 
 pub fn impute_float_gaussian<'a, I: Iterator<Item=&'a mut Float>>(
     // column: &mut Vec<Float>,
-    column: I,
+    mut column: I,
     (min, max): (&Float, &Float),
     (shift, scale): (&Float, &Float),
     enforce_constant_time: bool
 ) -> Result<()> {
+    if enforce_constant_time {
+        // sample for every cell, so that the time taken does not depend on the number of nan values
+        return column.try_for_each(|v| noise::sample_gaussian_truncated(
+            *min as f64, *max as f64, *shift as f64, *scale as f64,
+            enforce_constant_time)
+            .map(|n| *v = if v.is_nan() { n as Float } else { *v }))
+    }
+
     column
         // ignore nan values
         .filter(|v| v.is_nan())
@@ -270,11 +285,23 @@ pub fn impute_categorical_arrayd<T: Clone>(
 
 fn impute_categorical<'a, T: 'a, I: Iterator<Item=&'a mut T>>(
     // column: &mut Vec<T>,
-    column: I,
+    mut column: I,
     categories: &Vec<T>, probabilities: &Vec<Float>, null_values: &Vec<T>,
     enforce_constant_time: bool
 ) -> Result<()>
     where T: Clone + PartialEq + Default + Ord + Hash {
+    if enforce_constant_time {
+        // check every null value and sample for every cell,
+        //   so that the time taken does not depend on the number of null values
+        return column.try_for_each(|v| utilities::sample_from_set(
+            &categories, &probabilities, enforce_constant_time)
+            .map(|n| {
+                let is_null = null_values.iter()
+                    .fold(false, |is_null, null| is_null | (*null == *v));
+                *v = if is_null { n } else { v.clone() }
+            }))
+    }
+
     column
         // ignore non null values
         .filter(|v| null_values.contains(v))
@@ -282,4 +309,41 @@ fn impute_categorical<'a, T: 'a, I: Iterator<Item=&'a mut T>>(
         .try_for_each(|v| utilities::sample_from_set(
             &categories, &probabilities, enforce_constant_time)
             .map(|n| *v = n))
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod test_impute {
+    use ndarray::{arr1, Array, ArrayD};
+
+    use whitenoise_validator::Float;
+
+    use crate::components::impute::{impute_categorical_arrayd, impute_float_uniform_arrayd};
+    use crate::utilities::test_constant_time::assert_data_independent_time;
+
+    #[test]
+    #[ignore]
+    fn test_timing_float() {
+        let datasets: Vec<ArrayD<Float>> = vec![
+            Array::zeros(vec![5_000]),
+            Array::from_elem(vec![5_000], Float::NAN),
+            Array::from_shape_fn(vec![5_000], |idx| if idx[0] % 2 == 0 { Float::NAN } else { 0. })];
+
+        assert_data_independent_time(&datasets, |data| {
+            impute_float_uniform_arrayd(data, arr1(&[0.]).into_dyn(), arr1(&[1.]).into_dyn(), true).unwrap();
+        });
+    }
+
+    #[test]
+    #[ignore]
+    fn test_timing_categorical() {
+        let datasets: Vec<ArrayD<i64>> = vec![
+            Array::zeros(vec![5_000]),
+            Array::from_elem(vec![5_000], -1),
+            Array::from_shape_fn(vec![5_000], |idx| if idx[0] % 2 == 0 { -1 } else { 0 })];
+
+        assert_data_independent_time(&datasets, |data| {
+            impute_categorical_arrayd(
+                data, vec![(0..10).collect()], None, vec![vec![-1]], true).unwrap();
+        });
+    }
+}
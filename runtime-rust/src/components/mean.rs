@@ -4,15 +4,21 @@ use crate::NodeArguments;
 use whitenoise_validator::base::{ReleaseNode};
 use whitenoise_validator::utilities::take_argument;
 use crate::components::Evaluable;
-use ndarray::{ArrayD, Array};
-use crate::utilities::get_num_columns;
+use ndarray::{ArrayD, Array, Axis};
+use crate::utilities::{check_float_overflow, get_num_columns};
+use crate::components::sum::pairwise_sum;
 use whitenoise_validator::{proto, Float};
 
 impl Evaluable for proto::Mean {
-    fn evaluate(&self, _privacy_definition: &Option<proto::PrivacyDefinition>, mut arguments: NodeArguments) -> Result<ReleaseNode> {
-        Ok(ReleaseNode::new(mean(
-            &take_argument(&mut arguments, "data")?.array()?.float()?
-        )?.into()))
+    fn evaluate(&self, privacy_definition: &Option<proto::PrivacyDefinition>, mut arguments: NodeArguments) -> Result<ReleaseNode> {
+        let protect_overflow = privacy_definition.as_ref()
+            .map(|v| v.protect_overflow).unwrap_or(false);
+        let protect_floating_point = privacy_definition.as_ref()
+            .map(|v| v.protect_floating_point).unwrap_or(false);
+        let data = take_argument(&mut arguments, "data")?.array()?.float()?;
+        let means = if protect_floating_point { pairwise_mean(&data)? } else { mean(&data)? };
+
+        Ok(ReleaseNode::new(if protect_overflow { check_float_overflow(means)? } else { means }.into()))
     }
 }
 
@@ -51,6 +57,32 @@ pub fn mean(data: &ArrayD<Float>) -> Result<ArrayD<Float>> {
     }
 }
 
+/// Calculates the arithmetic mean of each column, by dividing a pairwise sum by the number of records.
+///
+/// The rounding error is bounded independently of the order of the records.
+///
+/// # Arguments
+/// * `data` - Data for which you want the mean.
+///
+/// # Return
+/// Arithmetic mean(s) of the data in question.
+///
+/// # Example
+/// ```
+/// use ndarray::prelude::*;
+/// use whitenoise_runtime::components::mean::pairwise_mean;
+/// let data = arr2(&[ [1.,10.], [2., 20.], [3., 30.] ]).into_dyn();
+/// let means = pairwise_mean(&data).unwrap();
+/// assert_eq!(means, arr2(&[[2., 20.]]).into_dyn());
+/// ```
+pub fn pairwise_mean(data: &ArrayD<Float>) -> Result<ArrayD<Float>> {
+    if data.ndim() == 0 || data.len_of(Axis(0)) == 0 {
+        return Err("attempted mean of an empty column".into())
+    }
+    let num_records = data.len_of(Axis(0)) as Float;
+    Ok(pairwise_sum(data)?.mapv(|sum| sum / num_records))
+}
+
 
 #[cfg(test)]
 mod test_mean {
@@ -1,15 +1,17 @@
-use ndarray::{arr0};
+use ndarray::{arr0, ShapeBuilder};
 
 use whitenoise_validator::{Float, Integer, proto};
 use whitenoise_validator::base::{Array, ReleaseNode, Value};
 use whitenoise_validator::errors::*;
-use whitenoise_validator::utilities::{array::broadcast_ndarray, privacy::{get_delta, get_epsilon, spread_privacy_usage}, take_argument};
+use whitenoise_validator::utilities::{array::broadcast_ndarray, privacy::{get_delta, get_epsilon, get_mu, get_rho, spread_privacy_usage}, take_argument};
 
 use crate::components::Evaluable;
 use crate::NodeArguments;
 use crate::utilities;
 use crate::utilities::{get_num_columns, to_nd};
-use crate::utilities::mechanisms::exponential_mechanism;
+use crate::utilities::mechanisms::{continuous_exponential_mechanism, exponential_mechanism, joint_exponential_mechanism, permute_and_flip_mechanism, report_noisy_max_mechanism, sparse_vector_mechanism};
+use whitenoise_validator::components::discrete_gaussian_mechanism::get_discrete_gaussian_rho;
+use whitenoise_validator::components::sparse_vector::get_threshold;
 
 impl Evaluable for proto::LaplaceMechanism {
     fn evaluate(
@@ -75,20 +77,66 @@ impl Evaluable for proto::GaussianMechanism {
 
         let usages = spread_privacy_usage(&self.privacy_usage, num_columns)?;
 
-        let epsilon = usages.iter().map(get_epsilon).collect::<Result<Vec<f64>>>()?;
-        let delta = usages.iter().map(get_delta).collect::<Result<Vec<f64>>>()?;
-
         data.gencolumns_mut().into_iter()
             .zip(sensitivity.gencolumns().into_iter())
-            .zip(epsilon.into_iter().zip(delta.into_iter()))
-            .try_for_each(|((mut data_column, sensitivity), (epsilon, delta))| data_column.iter_mut()
+            .zip(usages.iter())
+            .try_for_each(|((mut data_column, sensitivity), usage)| data_column.iter_mut()
+                .zip(sensitivity.iter())
+                .try_for_each(|(v, sens)| match usage.distance {
+                    Some(proto::privacy_usage::Distance::Concentrated(_)) =>
+                        utilities::mechanisms::concentrated_gaussian_mechanism(
+                            get_rho(usage)?, *sens as f64,
+                            enforce_constant_time,
+                        ),
+                    // mu-GDP gaussian noise is rho-zCDP gaussian noise with rho = mu^2 / 2
+                    Some(proto::privacy_usage::Distance::Gaussian(_)) =>
+                        utilities::mechanisms::concentrated_gaussian_mechanism(
+                            get_mu(usage)?.powi(2) / 2., *sens as f64,
+                            enforce_constant_time,
+                        ),
+                    _ => utilities::mechanisms::gaussian_mechanism(
+                        get_epsilon(usage)?, get_delta(usage)?, *sens as f64, self.analytic,
+                        enforce_constant_time,
+                    )
+                }.map(|noise| *v += noise as Float)))?;
+
+        Ok(ReleaseNode {
+            value: data.into(),
+            privacy_usages: Some(usages),
+            public: true,
+        })
+    }
+}
+
+impl Evaluable for proto::DiscreteGaussianMechanism {
+    fn evaluate(
+        &self,
+        privacy_definition: &Option<proto::PrivacyDefinition>,
+        mut arguments: NodeArguments
+    ) -> Result<ReleaseNode> {
+
+        let enforce_constant_time = privacy_definition.as_ref()
+            .map(|v| v.protect_elapsed_time).unwrap_or(false);
+
+        let data = take_argument(&mut arguments, "data")?.array()?;
+        let num_columns = data.num_columns()?;
+        let mut data = data.int()?.to_owned();
+
+        let sensitivity = take_argument(&mut arguments, "sensitivity")?.array()?.float()?;
+
+        let usages = spread_privacy_usage(&self.privacy_usage, num_columns)?;
+        let rho = usages.iter().map(get_discrete_gaussian_rho).collect::<Result<Vec<f64>>>()?;
+
+        data.gencolumns_mut().into_iter()
+            .zip(sensitivity.gencolumns().into_iter().zip(rho.into_iter()))
+            .try_for_each(|(mut data_column, (sensitivity, rho))| data_column.iter_mut()
                 .zip(sensitivity.iter())
                 .try_for_each(|(v, sens)|
 
-                    utilities::mechanisms::gaussian_mechanism(
-                        epsilon, delta, *sens as f64, self.analytic,
+                    utilities::mechanisms::discrete_gaussian_mechanism(
+                        rho, *sens as f64,
                         enforce_constant_time,
-                    ).map(|noise| *v += noise as Float)))?;
+                    ).map(|noise| *v += noise as Integer)))?;
 
         Ok(ReleaseNode {
             value: data.into(),
@@ -189,6 +237,193 @@ impl Evaluable for proto::ExponentialMechanism {
     }
 }
 
+impl Evaluable for proto::ContinuousExponentialMechanism {
+    fn evaluate(
+        &self, privacy_definition: &Option<proto::PrivacyDefinition>, mut arguments: NodeArguments,
+    ) -> Result<ReleaseNode> {
+        let enforce_constant_time = privacy_definition.as_ref()
+            .map(|v| v.protect_elapsed_time).unwrap_or(false);
+
+        let data = match take_argument(&mut arguments, "data")?.array()? {
+            Array::Float(data) => data,
+            Array::Int(data) => data.mapv(|v| v as Float),
+            _ => return Err("data must be numeric".into())
+        };
+        let num_columns = get_num_columns(&data)? as usize;
+
+        let sensitivity = take_argument(&mut arguments, "sensitivity")?.array()?.float()?;
+        let lower = take_argument(&mut arguments, "lower")?.array()?.float()?;
+        let upper = take_argument(&mut arguments, "upper")?.array()?.float()?;
+        if sensitivity.len() != num_columns || lower.len() != num_columns || upper.len() != num_columns {
+            return Err("sensitivity, lower and upper must contain one value per column".into())
+        }
+
+        let usages = spread_privacy_usage(&self.privacy_usage, num_columns)?;
+        let epsilon = usages.iter().map(get_epsilon).collect::<Result<Vec<f64>>>()?;
+
+        let quantiles = data.gencolumns().into_iter()
+            .zip(sensitivity.iter().zip(epsilon.iter()))
+            .zip(lower.iter().zip(upper.iter()))
+            .map(|((column, (sens, eps)), (lower, upper))| continuous_exponential_mechanism(
+                *eps, *sens as f64, self.alpha,
+                &column.to_vec(), *lower, *upper,
+                enforce_constant_time))
+            .collect::<Result<Vec<Float>>>()?;
+
+        // one quantile per column, in the shape of a quantile over the first axis
+        let quantiles = ndarray::Array::from_shape_vec(data.shape()[1..].to_vec(), quantiles)?;
+
+        Ok(ReleaseNode {
+            value: quantiles.into(),
+            privacy_usages: Some(usages),
+            public: true,
+        })
+    }
+}
+
+impl Evaluable for proto::JointExponentialMechanism {
+    fn evaluate(
+        &self, privacy_definition: &Option<proto::PrivacyDefinition>, mut arguments: NodeArguments,
+    ) -> Result<ReleaseNode> {
+        let enforce_constant_time = privacy_definition.as_ref()
+            .map(|v| v.protect_elapsed_time).unwrap_or(false);
+
+        let data = match take_argument(&mut arguments, "data")?.array()? {
+            Array::Float(data) => data,
+            Array::Int(data) => data.mapv(|v| v as Float),
+            _ => return Err("data must be numeric".into())
+        };
+        let num_columns = get_num_columns(&data)? as usize;
+
+        let sensitivity = take_argument(&mut arguments, "sensitivity")?.array()?.float()?;
+        let lower = take_argument(&mut arguments, "lower")?.array()?.float()?;
+        let upper = take_argument(&mut arguments, "upper")?.array()?.float()?;
+        if sensitivity.len() != num_columns || lower.len() != num_columns || upper.len() != num_columns {
+            return Err("sensitivity, lower and upper must contain one value per column".into())
+        }
+
+        let usages = spread_privacy_usage(&self.privacy_usage, num_columns)?;
+        let epsilon = usages.iter().map(get_epsilon).collect::<Result<Vec<f64>>>()?;
+
+        let quantiles = data.gencolumns().into_iter()
+            .zip(sensitivity.iter().zip(epsilon.iter()))
+            .zip(lower.iter().zip(upper.iter()))
+            .map(|((column, (sens, eps)), (lower, upper))| joint_exponential_mechanism(
+                *eps, *sens as f64, &self.alphas,
+                &column.to_vec(), *lower, *upper,
+                enforce_constant_time))
+            .collect::<Result<Vec<Vec<Float>>>>()?;
+
+        // one row per quantile, and one column per column of the data
+        let mut shape = vec![self.alphas.len()];
+        shape.extend(data.shape()[1..].iter().cloned());
+        let quantiles = ndarray::Array::from_shape_vec(ndarray::IxDyn(&shape).f(), quantiles.concat())?;
+
+        Ok(ReleaseNode {
+            value: quantiles.into(),
+            privacy_usages: Some(usages),
+            public: true,
+        })
+    }
+}
+
+impl Evaluable for proto::ReportNoisyMaxMechanism {
+    fn evaluate(
+        &self, privacy_definition: &Option<proto::PrivacyDefinition>, mut arguments: NodeArguments,
+    ) -> Result<ReleaseNode> {
+        let enforce_constant_time = privacy_definition.as_ref()
+            .map(|v| v.protect_elapsed_time).unwrap_or(false);
+
+        let candidates = take_argument(&mut arguments, "candidates")?.array()?;
+
+        let sensitivity = take_argument(&mut arguments, "sensitivity")?.array()?.float()?
+            .iter().cloned().collect::<Vec<Float>>();
+
+        let usages = spread_privacy_usage(&self.privacy_usage, sensitivity.len())?;
+        let epsilon = usages.iter().map(get_epsilon).collect::<Result<Vec<f64>>>()?;
+
+        let utilities = take_argument(&mut arguments, "utilities")?.array()?.float()?;
+
+        macro_rules! apply_report_noisy_max {
+            ($candidates:ident) => {
+                {
+                    let mut release_vec = $candidates.gencolumns().into_iter()
+                        .zip(utilities.gencolumns().into_iter())
+                        .zip(sensitivity.iter().zip(epsilon.iter()))
+                        .map(|((cands, utils), (sens, eps))| report_noisy_max_mechanism(
+                            *eps, *sens as f64,
+                            &cands.to_vec(),
+                            utils.into_iter().map(|v| *v as f64).collect(),
+                            &self.distribution,
+                            enforce_constant_time))
+                        .collect::<Result<Vec<_>>>()?;
+
+                    Value::from(arr0(release_vec.remove(0)).into_dyn())
+                }
+            }
+        }
+
+        Ok(ReleaseNode {
+            value: match candidates {
+                Array::Float(candidates) => apply_report_noisy_max!(candidates),
+                Array::Int(candidates) => apply_report_noisy_max!(candidates),
+                Array::Str(candidates) => apply_report_noisy_max!(candidates),
+                Array::Bool(candidates) => apply_report_noisy_max!(candidates)
+            },
+            privacy_usages: Some(usages),
+            public: true,
+        })
+    }
+}
+
+impl Evaluable for proto::PermuteAndFlipMechanism {
+    fn evaluate(
+        &self, privacy_definition: &Option<proto::PrivacyDefinition>, mut arguments: NodeArguments,
+    ) -> Result<ReleaseNode> {
+        let enforce_constant_time = privacy_definition.as_ref()
+            .map(|v| v.protect_elapsed_time).unwrap_or(false);
+
+        let candidates = take_argument(&mut arguments, "candidates")?.array()?;
+
+        let sensitivity = take_argument(&mut arguments, "sensitivity")?.array()?.float()?
+            .iter().cloned().collect::<Vec<Float>>();
+
+        let usages = spread_privacy_usage(&self.privacy_usage, sensitivity.len())?;
+        let epsilon = usages.iter().map(get_epsilon).collect::<Result<Vec<f64>>>()?;
+
+        let utilities = take_argument(&mut arguments, "utilities")?.array()?.float()?;
+
+        macro_rules! apply_permute_and_flip {
+            ($candidates:ident) => {
+                {
+                    let mut release_vec = $candidates.gencolumns().into_iter()
+                        .zip(utilities.gencolumns().into_iter())
+                        .zip(sensitivity.iter().zip(epsilon.iter()))
+                        .map(|((cands, utils), (sens, eps))| permute_and_flip_mechanism(
+                            *eps, *sens as f64,
+                            &cands.to_vec(),
+                            utils.into_iter().map(|v| *v as f64).collect(),
+                            enforce_constant_time))
+                        .collect::<Result<Vec<_>>>()?;
+
+                    Value::from(arr0(release_vec.remove(0)).into_dyn())
+                }
+            }
+        }
+
+        Ok(ReleaseNode {
+            value: match candidates {
+                Array::Float(candidates) => apply_permute_and_flip!(candidates),
+                Array::Int(candidates) => apply_permute_and_flip!(candidates),
+                Array::Str(candidates) => apply_permute_and_flip!(candidates),
+                Array::Bool(candidates) => apply_permute_and_flip!(candidates)
+            },
+            privacy_usages: Some(usages),
+            public: true,
+        })
+    }
+}
+
 impl Evaluable for proto::SnappingMechanism {
     fn evaluate(&self, privacy_definition: &Option<proto::PrivacyDefinition>, mut arguments: NodeArguments) -> Result<ReleaseNode> {
         let mut data = match take_argument(&mut arguments, "data")?.array()? {
@@ -255,4 +490,42 @@ impl Evaluable for proto::SnappingMechanism {
             public: true
         })
     }
-}
\ No newline at end of file
+}
+
+impl Evaluable for proto::SparseVector {
+    fn evaluate(
+        &self, privacy_definition: &Option<proto::PrivacyDefinition>, mut arguments: NodeArguments,
+    ) -> Result<ReleaseNode> {
+        let enforce_constant_time = privacy_definition.as_ref()
+            .map(|v| v.protect_elapsed_time).unwrap_or(false);
+
+        let queries = match take_argument(&mut arguments, "data")?.array()? {
+            Array::Float(data) => data,
+            Array::Int(data) => data.mapv(|v| v as Float),
+            _ => return Err("data must be numeric".into())
+        };
+
+        let threshold = get_threshold(&take_argument(&mut arguments, "threshold")?.array()?)?;
+
+        // each query is bounded by the largest sensitivity
+        let sensitivity = take_argument(&mut arguments, "sensitivity")?.array()?.float()?
+            .iter().cloned().fold(0., Float::max);
+
+        let usages = spread_privacy_usage(&self.privacy_usage, 1)?;
+        let epsilon = get_epsilon(&usages[0])?;
+
+        let indices = sparse_vector_mechanism(
+            epsilon, sensitivity as f64,
+            &queries.iter().map(|v| *v as f64).collect::<Vec<f64>>(),
+            threshold as f64, self.cutoff,
+            enforce_constant_time)?;
+
+        Ok(ReleaseNode {
+            value: ndarray::Array::from(indices.into_iter()
+                .map(|index| index as Integer)
+                .collect::<Vec<Integer>>()).into_dyn().into(),
+            privacy_usages: Some(usages),
+            public: true,
+        })
+    }
+}
@@ -17,13 +17,18 @@ use whitenoise_validator::proto;
 //pub mod bin;
 pub mod cast;
 pub mod clamp;
+pub mod continual_count;
 pub mod count;
 pub mod covariance;
 pub mod column_bind;
 pub mod digitize;
+pub mod dp_bounds;
 pub mod dp_gumbel_median;
+pub mod dp_range_queries;
 pub mod filter;
+pub mod frequency_oracles;
 pub mod histogram;
+pub mod histogram_projection;
 pub mod impute;
 pub mod index;
 // pub mod linreg_noisy_stats;
@@ -31,10 +36,13 @@ pub mod materialize;
 pub mod mean;
 pub mod mechanisms;
 pub mod partition;
+pub mod poisson_sample;
 pub mod quantile;
+pub mod randomized_response;
 pub mod raw_moment;
 pub mod reshape;
 pub mod resize;
+pub mod stability_histogram;
 pub mod sum;
 pub mod theil_sen;
 pub mod to_dataframe;
@@ -85,16 +93,20 @@ impl Evaluable for proto::component::Variant {
 
         evaluate!(
             // INSERT COMPONENT LIST
-            Cast, Clamp, ColumnBind, Count, Covariance, Digitize, Filter, Histogram, Impute, Index,
-            Materialize, Mean, Partition,
+            Cast, Clamp, ColumnBind, ContinualCount, Count, Covariance, Digitize, DpRangeQueries, DyadicSum, Filter,
+            Histogram, HistogramProjection, Impute, Index,
+            Materialize, Mean, Partition, PoissonSample,
             Quantile, RawMoment, Reshape, Resize, Sum, ToDataframe, Union, Variance,
 
-            ExponentialMechanism, GaussianMechanism,
-            LaplaceMechanism, SnappingMechanism,
-            SimpleGeometricMechanism,
+            ContinuousExponentialMechanism, DiscreteGaussianMechanism, ExponentialMechanism, GaussianMechanism,
+            JointExponentialMechanism, LaplaceMechanism, PermuteAndFlipMechanism, RandomizedResponse, RandomizedResponseEstimate,
+            ReportNoisyMaxMechanism, SnappingMechanism, SimpleGeometricMechanism, SparseVector, StabilityHistogram,
+
+            HadamardResponse, HadamardResponseEstimate, OptimizedLocalHashing, OptimizedLocalHashingEstimate,
+            OptimizedUnaryEncoding, OptimizedUnaryEncodingEstimate,
 
             Abs, Add, LogicalAnd, Divide, Equal, GreaterThan, LessThan, Log, Modulo, Multiply,
-            Negate, Negative, LogicalOr, Power, RowMax, RowMin, Subtract, TheilSen, DpGumbelMedian
+            Negate, Negative, LogicalOr, Power, RowMax, RowMin, Subtract, TheilSen, DpBounds, DpGumbelMedian
         );
 
         Err(format!("Component type not implemented: {:?}", self).into())
@@ -6,6 +6,7 @@ use whitenoise_validator::utilities::{take_argument, get_common_value};
 use whitenoise_validator::components::partition::{even_split_lengths, make_dense_partition_keys};
 use crate::components::Evaluable;
 use ndarray::{ArrayD, Axis};
+use crate::utilities::{select_rows_with_full_capacity, take_number_records};
 
 use whitenoise_validator::{proto, Integer};
 
@@ -14,21 +15,22 @@ use indexmap::map::IndexMap;
 
 
 impl Evaluable for proto::Partition {
-    fn evaluate(&self, _privacy_definition: &Option<proto::PrivacyDefinition>, mut arguments: NodeArguments) -> Result<ReleaseNode> {
+    fn evaluate(&self, privacy_definition: &Option<proto::PrivacyDefinition>, mut arguments: NodeArguments) -> Result<ReleaseNode> {
         let data = take_argument(&mut arguments, "data")?;
         Ok(ReleaseNode::new(match arguments.remove::<IndexKey>(&"by".into()) {
             Some(by) => {
+                let number_records = take_number_records(privacy_definition, &mut arguments)?;
                 let categories = take_argument(&mut arguments, "categories")?.jagged()?;
                 let partitions = make_dense_partition_keys(
                     categories, Some(by.ref_array()?.shape().len() as i64))?;
 
                 match by.array()? {
-                    Array::Int(by) =>
-                        Value::Partitions(partition_by(&data, by.mapv(IndexKey::from), partitions)?),
-                    Array::Bool(by) =>
-                        Value::Partitions(partition_by(&data, by.mapv(IndexKey::from), partitions)?),
-                    Array::Str(by) =>
-                        Value::Partitions(partition_by(&data, by.mapv(IndexKey::from), partitions)?),
+                    Array::Int(by) => Value::Partitions(partition_by(
+                        &data, by.mapv(IndexKey::from), partitions, number_records)?),
+                    Array::Bool(by) => Value::Partitions(partition_by(
+                        &data, by.mapv(IndexKey::from), partitions, number_records)?),
+                    Array::Str(by) => Value::Partitions(partition_by(
+                        &data, by.mapv(IndexKey::from), partitions, number_records)?),
                     _ => return Err("by and categories must share the same type".into())
                 }
             },
@@ -132,12 +134,24 @@ pub fn partition_evenly(data: &Value, num_partitions: i64) -> Result<IndexMap<In
 
 }
 
+/// Partitions data into the partition of the key in `by` on each row.
+///
+/// # Arguments
+/// * `data` - Data to be partitioned.
+/// * `by` - The key of the partition each row is placed in.
+/// * `partition_keys` - Keys of the partitions returned.
+/// * `number_records` - When protecting memory utilization, the public number of records to allocate space for in every partition,
+///                      regardless of how many rows each partition receives.
+///
+/// # Return
+/// Indexmap with data partitions.
 pub fn partition_by(
-    data: &Value, by: ArrayD<IndexKey>, partition_keys: Vec<IndexKey>
+    data: &Value, by: ArrayD<IndexKey>, partition_keys: Vec<IndexKey>, number_records: Option<usize>
 ) -> Result<IndexMap<IndexKey, Value>> {
 
+    // when protecting memory utilization, every partition may hold every public record
     let mut indices = partition_keys.into_iter()
-        .map(|key| (key, vec![]))
+        .map(|key| (key, Vec::with_capacity(number_records.unwrap_or(0))))
         .collect::<IndexMap<IndexKey, Vec<usize>>>();
 
     match by.ndim() {
@@ -150,9 +164,25 @@ pub fn partition_by(
             .or_insert_with(Vec::new).push(idx));
 
     // partition either an array or a dataframe
-    fn value_partitioner(data: &Value, indices: &IndexMap<IndexKey, Vec<usize>>) -> Result<IndexMap<IndexKey, Value>> {
-        Ok(match data {
-            Value::Array(data) => match data {
+    fn value_partitioner(
+        data: &Value, indices: &IndexMap<IndexKey, Vec<usize>>, number_records: Option<usize>
+    ) -> Result<IndexMap<IndexKey, Value>> {
+        Ok(match (data, number_records) {
+            (Value::Array(data), Some(number_records)) => match data {
+                Array::Int(data) => indices.into_iter()
+                    .map(|(cat, idxs)| Ok((cat.clone(), select_rows_with_full_capacity(data, idxs, number_records)?.into())))
+                    .collect::<Result<IndexMap<IndexKey, Value>>>()?,
+                Array::Float(data) => indices.into_iter()
+                    .map(|(cat, idxs)| Ok((cat.clone(), select_rows_with_full_capacity(data, idxs, number_records)?.into())))
+                    .collect::<Result<IndexMap<IndexKey, Value>>>()?,
+                Array::Bool(data) => indices.into_iter()
+                    .map(|(cat, idxs)| Ok((cat.clone(), select_rows_with_full_capacity(data, idxs, number_records)?.into())))
+                    .collect::<Result<IndexMap<IndexKey, Value>>>()?,
+                Array::Str(data) => indices.into_iter()
+                    .map(|(cat, idxs)| Ok((cat.clone(), select_rows_with_full_capacity(data, idxs, number_records)?.into())))
+                    .collect::<Result<IndexMap<IndexKey, Value>>>()?
+            },
+            (Value::Array(data), None) => match data {
                 Array::Int(data) => indices.into_iter()
                     .map(|(cat, idxs)| (cat.clone(), data.select(ndarray::Axis(0), idxs).into()))
                     .collect::<IndexMap<IndexKey, Value>>(),
@@ -167,9 +197,9 @@ pub fn partition_by(
                     .collect::<IndexMap<IndexKey, Value>>()
             },
 
-            Value::Dataframe(data) => {
+            (Value::Dataframe(data), _) => {
                 let columnar_partitions = data.into_iter().map(|(k, v)|
-                    Ok((k.clone(), value_partitioner(v, indices)?)))
+                    Ok((k.clone(), value_partitioner(v, indices, number_records)?)))
                     .collect::<Result<IndexMap<ColName, IndexMap<IndexKey, Value>>>>()?;
 
                 indices.iter()
@@ -185,5 +215,5 @@ pub fn partition_by(
         })
     };
 
-    value_partitioner(data, &indices)
+    value_partitioner(data, &indices, number_records)
 }
\ No newline at end of file
@@ -0,0 +1,58 @@
+use whitenoise_validator::errors::*;
+
+use crate::NodeArguments;
+use whitenoise_validator::base::{Array, ReleaseNode};
+use whitenoise_validator::utilities::take_argument;
+use crate::components::Evaluable;
+use crate::components::filter::filter;
+use crate::utilities::noise::sample_bit_prob;
+use crate::utilities::take_number_records;
+use ndarray::{ArrayD, Axis};
+
+use whitenoise_validator::proto;
+
+
+impl Evaluable for proto::PoissonSample {
+    fn evaluate(&self, privacy_definition: &Option<proto::PrivacyDefinition>, mut arguments: NodeArguments) -> Result<ReleaseNode> {
+        let enforce_constant_time = privacy_definition.as_ref()
+            .map(|v| v.protect_elapsed_time).unwrap_or(false);
+        let number_records = take_number_records(privacy_definition, &mut arguments)?;
+
+        Ok(ReleaseNode::new(match take_argument(&mut arguments, "data")?.array()? {
+            Array::Str(data) => poisson_sample(data, self.sample_proportion, enforce_constant_time, number_records)?.into(),
+            Array::Float(data) => poisson_sample(data, self.sample_proportion, enforce_constant_time, number_records)?.into(),
+            Array::Int(data) => poisson_sample(data, self.sample_proportion, enforce_constant_time, number_records)?.into(),
+            Array::Bool(data) => poisson_sample(data, self.sample_proportion, enforce_constant_time, number_records)?.into(),
+        }))
+    }
+}
+
+/// Independently retains each row of the data with probability `sample_proportion`.
+///
+/// # Arguments
+/// * `data` - Data to be sampled.
+/// * `sample_proportion` - Probability that each row is retained.
+/// * `enforce_constant_time` - Whether or not to enforce the algorithm to run in constant time
+/// * `number_records` - When protecting memory utilization, the public number of records to allocate space for, regardless of how many are sampled
+///
+/// # Return
+/// Data with only the sampled rows.
+///
+/// # Example
+/// ```
+/// use ndarray::{ArrayD, arr2};
+/// use whitenoise_runtime::components::poisson_sample::poisson_sample;
+///
+/// let data = arr2(&[ [1, 2, 3], [4, 5, 6], [7, 8, 9], [10, 11, 12] ]).into_dyn();
+/// let sampled = poisson_sample(data.clone(), 1., false, None).unwrap();
+/// assert_eq!(sampled, data);
+/// ```
+pub fn poisson_sample<T: Clone + Default>(
+    data: ArrayD<T>, sample_proportion: f64, enforce_constant_time: bool, number_records: Option<usize>,
+) -> Result<ArrayD<T>> {
+    let mask = (0..data.len_of(Axis(0)))
+        .map(|_| sample_bit_prob(sample_proportion, enforce_constant_time))
+        .collect::<Result<Vec<bool>>>()?;
+
+    filter(data, ndarray::Array::from(mask).into_dyn(), number_records, enforce_constant_time)
+}
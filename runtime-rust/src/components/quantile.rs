@@ -14,10 +14,13 @@ use whitenoise_validator::utilities::take_argument;
 
 use crate::components::Evaluable;
 use crate::NodeArguments;
+use crate::utilities::sort_constant_time;
 use std::fmt::Debug;
 
 impl Evaluable for proto::Quantile {
-    fn evaluate(&self, _privacy_definition: &Option<proto::PrivacyDefinition>, mut arguments: NodeArguments) -> Result<ReleaseNode> {
+    fn evaluate(&self, privacy_definition: &Option<proto::PrivacyDefinition>, mut arguments: NodeArguments) -> Result<ReleaseNode> {
+        let enforce_constant_time = privacy_definition.as_ref()
+            .map(|v| v.protect_elapsed_time).unwrap_or(false);
         let data = take_argument(&mut arguments, "data")?.array()?;
 
         Ok(match arguments.remove::<IndexKey>(&"candidates".into()) {
@@ -32,23 +35,23 @@ impl Evaluable for proto::Quantile {
                             data.mapv(|v| n64(v as f64)),
                             lower.map(|v| v.array()?.first_float().map(n64)).transpose()?,
                             upper.map(|v| v.array()?.first_float().map(n64)).transpose()?,
-                            self.alpha as Float)?)),
+                            self.alpha as Float, enforce_constant_time)?)),
                     (Array::Int(candidates), Array::Int(data)) =>
                         Value::Array(Array::Float(quantile_utilities_arrayd(
                             candidates,
                             data,
                             lower.map(|v| v.array()?.first_int()).transpose()?,
                             upper.map(|v| v.array()?.first_int()).transpose()?,
-                            self.alpha as Float)?)),
+                            self.alpha as Float, enforce_constant_time)?)),
                     _ => return Err("data must be either f64 or i64".into())
                 }
             },
             None => match data {
                 Array::Float(data) =>
-                    quantile(data.mapv(|v| n64(v as f64)), self.alpha, &self.interpolation)?
+                    quantile(data.mapv(|v| n64(v as f64)), self.alpha, &self.interpolation, enforce_constant_time)?
                         .mapv(|v| v.raw() as Float).into(),
                 Array::Int(data) =>
-                    quantile(data, self.alpha, &self.interpolation)?.into(),
+                    quantile(data, self.alpha, &self.interpolation, enforce_constant_time)?.into(),
                 _ => return Err("data must be either f64 or i64".into())
             }
         }).map(ReleaseNode::new)
@@ -61,6 +64,8 @@ impl Evaluable for proto::Quantile {
 /// # Arguments
 /// * `data` - Array of data for which you would like the quantile.
 /// * `alpha` - Desired quantile.
+/// * `interpolation` - Interpolation strategy when the quantile lies between two values. One of "lower", "upper", "midpoint", "nearest" or "linear".
+/// * `enforce_constant_time` - Whether to sort each column with a sorting network, instead of a data-dependent selection.
 ///
 /// # Return
 /// Quantile of interest for each column of your data.
@@ -72,17 +77,21 @@ impl Evaluable for proto::Quantile {
 /// use noisy_float::types::n64;
 /// use whitenoise_validator::Float;
 /// let data: ArrayD<Float> = arr2(&[ [0., 1., 2.], [2., 3., 4.] ]).into_dyn();
-/// let median = quantile(data.mapv(|v| n64(v as f64)), 0.5, &"midpoint".to_string()).unwrap();
+/// let median = quantile(data.mapv(|v| n64(v as f64)), 0.5, &"midpoint".to_string(), false).unwrap();
 /// println!("{:?}", median);
 /// assert_eq!(median, arr1(& [1.0, 2.0, 3.0] ).into_dyn().mapv(|v| n64(v as f64)));
 /// ```
-pub fn quantile<T: FromPrimitive + Ord + Clone + Sub<Output=T> + Mul<Output=T> + Div<Output=T> + Add<Output=T> + Rem<Output=T> + ToPrimitive>(
-    mut data: ArrayD<T>, alpha: f64, interpolation: &str
+pub fn quantile<T: FromPrimitive + Ord + Copy + Sub<Output=T> + Mul<Output=T> + Div<Output=T> + Add<Output=T> + Rem<Output=T> + ToPrimitive>(
+    mut data: ArrayD<T>, alpha: f64, interpolation: &str, enforce_constant_time: bool
 ) -> Result<ArrayD<T>> {
     if 0. > alpha || alpha > 1. {
         return Err("q must be within [0, 1]".into());
     }
 
+    if enforce_constant_time {
+        return quantile_constant_time(data, alpha, interpolation)
+    }
+
     match match interpolation.to_lowercase().as_str() {
         "lower" => data.quantile_axis_mut(Axis(0), n64(alpha), &interpolate::Lower),
         "upper" => data.quantile_axis_mut(Axis(0), n64(alpha), &interpolate::Higher),
@@ -96,14 +105,63 @@ pub fn quantile<T: FromPrimitive + Ord + Clone + Sub<Output=T> + Mul<Output=T> +
     }
 }
 
+/// Sort each column with a sorting network, and interpolate between the values nearest to the quantile.
+///
+/// Both the sort and the indices read from the sorted column depend only on the shape of the data.
+/// Interpolation follows the same conventions as `ndarray_stats::interpolate`.
+fn quantile_constant_time<T: FromPrimitive + Ord + Copy + Sub<Output=T> + Div<Output=T> + Add<Output=T> + ToPrimitive>(
+    data: ArrayD<T>, alpha: f64, interpolation: &str
+) -> Result<ArrayD<T>> {
+    if data.ndim() == 0 {
+        return Err("data must have at least one dimension".into())
+    }
+    let num_records = data.len_of(Axis(0));
+    if num_records == 0 {
+        return Err("unable to compute quantiles".into())
+    }
+
+    let index = alpha * (num_records - 1) as f64;
+    let (lower, higher) = (index.floor() as usize, index.ceil() as usize);
+    let fraction = index.fract();
+
+    let interpolate = |lower: T, higher: T| -> Result<T> {
+        Ok(match interpolation.to_lowercase().as_str() {
+            "lower" => lower,
+            "upper" => higher,
+            "midpoint" => lower + (higher - lower) / T::from_u8(2)
+                .ok_or_else(|| Error::from("unable to compute midpoint"))?,
+            "nearest" => if fraction < 0.5 { lower } else { higher },
+            "linear" => {
+                let (lower_f64, higher_f64) = (
+                    lower.to_f64().ok_or_else(|| Error::from("unable to interpolate quantile"))?,
+                    higher.to_f64().ok_or_else(|| Error::from("unable to interpolate quantile"))?);
+                lower + T::from_f64(fraction * (higher_f64 - lower_f64))
+                    .ok_or_else(|| Error::from("unable to interpolate quantile"))?
+            }
+            _ => return Err(format!("interpolation type not recognized: {}", interpolation).into())
+        })
+    };
+
+    let shape = data.shape()[1..].to_vec();
+    let quantiles = data.gencolumns().into_iter()
+        .map(|column| {
+            let mut column = column.to_vec();
+            sort_constant_time(&mut column);
+            interpolate(column[lower], column[higher])
+        })
+        .collect::<Result<Vec<T>>>()?;
+
+    Ok(ArrayD::from_shape_vec(shape, quantiles)?)
+}
+
 pub fn quantile_utilities_arrayd<T: Ord + Clone + Copy + Debug>(
     candidates: ArrayD<T>, data: ArrayD<T>, lower: Option<T>, upper: Option<T>,
-    alpha: Float
+    alpha: Float, enforce_constant_time: bool
 ) -> Result<ArrayD<Float>> {
     Ok(ndarray::Array::from_shape_vec(candidates.shape(), candidates.gencolumns().into_iter()
         .zip(data.gencolumns().into_iter())
-        .map(|(candidates, column)|
-            quantile_utilities(candidates.to_vec(), column.to_vec(), lower, upper, alpha))
+        .map(|(candidates, column)| quantile_utilities(
+            candidates.to_vec(), column.to_vec(), lower, upper, alpha, enforce_constant_time))
         .collect::<Result<Vec<Vec<_>>>>()?.into_iter()
         .flatten().collect::<Vec<_>>())?.into_dyn())
 }
@@ -116,12 +174,14 @@ pub fn quantile_utilities_arrayd<T: Ord + Clone + Copy + Debug>(
 /// * `candidates` - values to be scored
 /// * `column` - dataset to score against
 /// * `alpha` - parameter for quantile. {0: min, 0.5: median, 1: max, ...}
+/// * `enforce_constant_time` - Whether to compare every candidate against every record, instead of merging sorted data
 ///
 /// # Returns
 /// Utility for each candidate
 fn quantile_utilities<T: Ord + Clone + Copy + Debug>(
     mut candidates: Vec<T>, mut column: Vec<T>,
     lower: Option<T>, upper: Option<T>, alpha: Float,
+    enforce_constant_time: bool
 ) -> Result<Vec<Float>> {
     match (lower, upper) {
         (Some(l), Some(u)) => {
@@ -132,6 +192,16 @@ fn quantile_utilities<T: Ord + Clone + Copy + Debug>(
         }
         _ => ()
     }
+
+    let constant = alpha.max(1. - alpha);
+
+    if enforce_constant_time {
+        return Ok(candidates.iter().map(|candidate| {
+            let (num_lt, num_gt) = column.iter().fold((0, 0), |(num_lt, num_gt), v|
+                (num_lt + (v < candidate) as usize, num_gt + (v > candidate) as usize));
+            constant * column.len() as f64 - score_candidate(num_lt, num_gt, alpha)
+        }).collect())
+    }
     // sort candidates but preserve original ordering
     let mut candidates = candidates.into_iter().enumerate().collect::<Vec<(usize, T)>>();
     candidates.sort_unstable_by_key(|v| v.1);
@@ -191,7 +261,6 @@ fn quantile_utilities<T: Ord + Clone + Copy + Debug>(
     utilities.extend((0..candidates.len() - utilities.len()).map(|_| candidate_score));
 
     // order the utilities by the order of the candidates before they were sorted, and shift the utility
-    let constant = alpha.max(1. - alpha);
     Ok(candidates.into_iter().map(|(idx, _)| constant * column.len() as f64 - utilities[idx]).collect())
 }
 
@@ -208,42 +277,45 @@ mod test_quantile_utilities {
 
     #[test]
     fn test_scoring() {
-        // no candidates, no score
-        assert_eq!(
-            quantile_utilities::<i64>(vec![], vec![], None, None, 0.5).unwrap(),
-            Vec::<f64>::new());
-        assert_eq!(
-            quantile_utilities(vec![], vec![1], None, None, 0.5).unwrap(),
-            Vec::<f64>::new());
-        // no data, score should be zero
-        assert_eq!(
-            quantile_utilities(vec![0], vec![], None, None, 0.5).unwrap(),
-            vec![0.]);
-        // 0.5 - 0.
-        assert_eq!(
-            quantile_utilities(vec![0], vec![0], None, None, 0.5).unwrap(),
-            vec![0.5]);
-        // 0.5 - |0.5 * 0. - 0.5 * 0.|
-        // 0.5 - |0.5 * 1. - 0.5 * 0.|
-        // 0.5 - |0.5 * 1. - 0.5 * 0.|
-        assert_eq!(
-            quantile_utilities(vec![0, 1, 2], vec![0], None, None, 0.5).unwrap(),
-            vec![0.5, 0., 0.]);
-        // 1.5 - |0.5 * 0. - 0.5 * 0.|
-        // 1.5 - |0.5 * 3. - 0.5 * 0.|
-        // 1.5 - |0.5 * 3. - 0.5 * 0.|
-        assert_eq!(
-            quantile_utilities(vec![0, 1, 2], vec![0, 0, 0], None, None, 0.5).unwrap(),
-            vec![1.5, 0., 0.]);
-        // // 1.5 - |0.5 * 0. - 0.5 * 3.|
-        // // 1.5 - |0.5 * 0. - 0.5 * 1.|
-        // // 1.5 - |0.5 * 0. - 0.5 * 1.|
-        assert_eq!(
-            quantile_utilities(vec![0, 1, 1], vec![1, 1, 2], None, None, 0.5).unwrap(),
-            vec![0., 1., 1.]);
-        assert_eq!(
-            quantile_utilities(vec![1, 0, 1], vec![2, 1, 1], None, None, 0.5).unwrap(),
-            vec![1., 0., 1.]);
+        // the constant-time scoring must agree with the merge-based scoring
+        [false, true].iter().for_each(|&enforce_constant_time| {
+            // no candidates, no score
+            assert_eq!(
+                quantile_utilities::<i64>(vec![], vec![], None, None, 0.5, enforce_constant_time).unwrap(),
+                Vec::<f64>::new());
+            assert_eq!(
+                quantile_utilities(vec![], vec![1], None, None, 0.5, enforce_constant_time).unwrap(),
+                Vec::<f64>::new());
+            // no data, score should be zero
+            assert_eq!(
+                quantile_utilities(vec![0], vec![], None, None, 0.5, enforce_constant_time).unwrap(),
+                vec![0.]);
+            // 0.5 - 0.
+            assert_eq!(
+                quantile_utilities(vec![0], vec![0], None, None, 0.5, enforce_constant_time).unwrap(),
+                vec![0.5]);
+            // 0.5 - |0.5 * 0. - 0.5 * 0.|
+            // 0.5 - |0.5 * 1. - 0.5 * 0.|
+            // 0.5 - |0.5 * 1. - 0.5 * 0.|
+            assert_eq!(
+                quantile_utilities(vec![0, 1, 2], vec![0], None, None, 0.5, enforce_constant_time).unwrap(),
+                vec![0.5, 0., 0.]);
+            // 1.5 - |0.5 * 0. - 0.5 * 0.|
+            // 1.5 - |0.5 * 3. - 0.5 * 0.|
+            // 1.5 - |0.5 * 3. - 0.5 * 0.|
+            assert_eq!(
+                quantile_utilities(vec![0, 1, 2], vec![0, 0, 0], None, None, 0.5, enforce_constant_time).unwrap(),
+                vec![1.5, 0., 0.]);
+            // // 1.5 - |0.5 * 0. - 0.5 * 3.|
+            // // 1.5 - |0.5 * 0. - 0.5 * 1.|
+            // // 1.5 - |0.5 * 0. - 0.5 * 1.|
+            assert_eq!(
+                quantile_utilities(vec![0, 1, 1], vec![1, 1, 2], None, None, 0.5, enforce_constant_time).unwrap(),
+                vec![0., 1., 1.]);
+            assert_eq!(
+                quantile_utilities(vec![1, 0, 1], vec![2, 1, 1], None, None, 0.5, enforce_constant_time).unwrap(),
+                vec![1., 0., 1.]);
+        })
     }
 
     #[test]
@@ -261,11 +333,41 @@ mod test_quantile_utilities {
             arr1(&[-10., -5., 0., 2., 5., 7., 10., 12.]).into_dyn().mapv(n64),
             arr1(&[0., 10., 5., 7., 6., 4., 3., 8., 7., 6., 5., 5.]).into_dyn().mapv(n64),
             None, None,
-            0.5,
+            0.5, false,
         ).unwrap().into_dimensionality::<ndarray::Ix1>().unwrap().to_vec();
 
         assert_eq!(utilities, vec![0., 0., 0.5, 1.0, 4.5, 3.0, 0.5, 0.]);
 
         // println!("utilities {:?}", utilities);
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod test_quantile {
+    use ndarray::{arr2, Array, ArrayD};
+
+    use crate::components::quantile::quantile;
+    use crate::utilities::test_constant_time::assert_data_independent_time;
+
+    #[test]
+    fn test_constant_time() {
+        let data = arr2(&[ [5, 1], [3, 8], [0, 4], [9, 9], [2, 7], [3, 2] ]).into_dyn();
+        ["lower", "upper", "midpoint", "nearest", "linear"].iter().for_each(|interpolation|
+            [0., 0.3, 0.5, 0.75, 1.].iter().for_each(|&alpha| assert_eq!(
+                quantile(data.clone(), alpha, interpolation, true).unwrap(),
+                quantile(data.clone(), alpha, interpolation, false).unwrap())));
+    }
+
+    #[test]
+    #[ignore]
+    fn test_timing() {
+        let datasets: Vec<ArrayD<i64>> = vec![
+            Array::zeros(vec![10_000]),
+            Array::from_shape_fn(vec![10_000], |idx| idx[0] as i64),
+            Array::from_shape_fn(vec![10_000], |idx| -(idx[0] as i64))];
+
+        assert_data_independent_time(&datasets, |data| {
+            quantile(data, 0.5, "midpoint", true).unwrap();
+        });
+    }
+}
@@ -0,0 +1,179 @@
+use std::hash::Hash;
+
+use indexmap::map::IndexMap;
+use ndarray::ArrayD;
+
+use whitenoise_validator::{Float, proto};
+use whitenoise_validator::base::{Array, ReleaseNode};
+use whitenoise_validator::errors::*;
+use whitenoise_validator::utilities::privacy::{get_epsilon, spread_privacy_usage};
+use whitenoise_validator::utilities::take_argument;
+
+use crate::components::Evaluable;
+use crate::NodeArguments;
+use crate::utilities::noise;
+
+
+impl Evaluable for proto::RandomizedResponse {
+    fn evaluate(&self, privacy_definition: &Option<proto::PrivacyDefinition>, mut arguments: NodeArguments) -> Result<ReleaseNode> {
+        let enforce_constant_time = privacy_definition.as_ref()
+            .map(|v| v.protect_elapsed_time).unwrap_or(false);
+
+        let usages = spread_privacy_usage(&self.privacy_usage, 1)?;
+        let epsilon = get_epsilon(&usages[0])?;
+
+        let data = match (take_argument(&mut arguments, "data")?.array()?, take_argument(&mut arguments, "categories")?.array()?) {
+            (Array::Bool(data), Array::Bool(categories)) =>
+                randomized_response(data, &categories.into_raw_vec(), epsilon, enforce_constant_time)?.into(),
+            (Array::Int(data), Array::Int(categories)) =>
+                randomized_response(data, &categories.into_raw_vec(), epsilon, enforce_constant_time)?.into(),
+            (Array::Str(data), Array::Str(categories)) =>
+                randomized_response(data, &categories.into_raw_vec(), epsilon, enforce_constant_time)?.into(),
+            _ => return Err("data and categories must be homogeneously typed, and not float".into())
+        };
+
+        Ok(ReleaseNode {
+            value: data,
+            privacy_usages: Some(usages),
+            public: true,
+        })
+    }
+}
+
+impl Evaluable for proto::RandomizedResponseEstimate {
+    fn evaluate(&self, _privacy_definition: &Option<proto::PrivacyDefinition>, mut arguments: NodeArguments) -> Result<ReleaseNode> {
+        let usages = spread_privacy_usage(&self.privacy_usage, 1)?;
+        let epsilon = get_epsilon(&usages[0])?;
+
+        Ok(ReleaseNode::new(match (take_argument(&mut arguments, "data")?.array()?, take_argument(&mut arguments, "categories")?.array()?) {
+            (Array::Bool(data), Array::Bool(categories)) =>
+                randomized_response_estimate(&data, &categories.into_raw_vec(), epsilon)?.into(),
+            (Array::Int(data), Array::Int(categories)) =>
+                randomized_response_estimate(&data, &categories.into_raw_vec(), epsilon)?.into(),
+            (Array::Str(data), Array::Str(categories)) =>
+                randomized_response_estimate(&data, &categories.into_raw_vec(), epsilon)?.into(),
+            _ => return Err("data and categories must be homogeneously typed, and not float".into())
+        }))
+    }
+}
+
+/// Privatize each value of categorical data via k-ary randomized response.
+///
+/// Each value is kept with probability `exp(epsilon) / (exp(epsilon) + k - 1)`,
+/// and otherwise replaced with one of the other `k - 1` categories, chosen uniformly at random.
+///
+/// # Arguments
+/// * `data` - Categorical data, where every value is a member of `categories`.
+/// * `categories` - The `k` possible values of the data.
+/// * `epsilon` - Privacy loss parameter of each value.
+/// * `enforce_constant_time` - Whether to sample in constant time.
+///
+/// # Return
+/// Data with each value perturbed.
+///
+/// # Example
+/// ```
+/// use ndarray::arr1;
+/// use whitenoise_runtime::components::randomized_response::randomized_response;
+/// let data = arr1(&[0, 1, 2, 1]).into_dyn();
+/// let perturbed = randomized_response(data, &[0, 1, 2], 1., false).unwrap();
+/// assert!(perturbed.iter().all(|v| [0, 1, 2].contains(v)));
+/// ```
+pub fn randomized_response<T: Clone + PartialEq>(
+    mut data: ArrayD<T>, categories: &[T], epsilon: f64, enforce_constant_time: bool
+) -> Result<ArrayD<T>> {
+    let num_categories = categories.len();
+    if num_categories < 2 {
+        return Err("there must be at least two categories".into())
+    }
+    if epsilon <= 0. {
+        return Err("epsilon must be positive".into())
+    }
+    let keep_prob = epsilon.exp() / (epsilon.exp() + (num_categories - 1) as f64);
+
+    data.iter_mut().try_for_each(|v| {
+        let index = categories.iter().position(|category| category == v)
+            .ok_or_else(|| Error::from("data must only contain members of categories"))?;
+
+        // both samples are always drawn, so that the time taken does not depend on the data
+        let keep = noise::sample_bit_prob(keep_prob, enforce_constant_time)?;
+        let other = if num_categories == 2 { 0 } else {
+            noise::sample_uniform_int(0, num_categories as i64 - 2)? as usize
+        };
+        // skip over the index of the true category
+        let other = if other >= index { other + 1 } else { other };
+
+        *v = categories[if keep { index } else { other }].clone();
+        Ok::<(), Error>(())
+    })?;
+    Ok(data)
+}
+
+/// Estimate the number of values in each category from data privatized by k-ary randomized response.
+///
+/// Each observed count is debiased by inverting the expected perturbation:
+/// `(count - n q) / (p - q)`, where `p` is the probability of keeping a value and `q` the probability of reporting each other category.
+///
+/// # Arguments
+/// * `data` - Data released by `randomized_response`.
+/// * `categories` - The `k` possible values of the data.
+/// * `epsilon` - Privacy loss parameter used to perturb each value.
+///
+/// # Return
+/// Unbiased estimate of the count of each category, in the order of `categories`.
+///
+/// # Example
+/// ```
+/// use ndarray::arr1;
+/// use whitenoise_runtime::components::randomized_response::randomized_response_estimate;
+/// let data = arr1(&[0, 1, 1, 1]).into_dyn();
+/// let counts = randomized_response_estimate(&data, &[0, 1], 1.).unwrap();
+/// assert!((counts.sum() - 4.).abs() < 1e-8);
+/// ```
+pub fn randomized_response_estimate<T: Clone + Eq + Hash>(
+    data: &ArrayD<T>, categories: &[T], epsilon: f64
+) -> Result<ArrayD<Float>> {
+    let num_categories = categories.len();
+    if num_categories < 2 {
+        return Err("there must be at least two categories".into())
+    }
+    let denominator = epsilon.exp() + (num_categories - 1) as f64;
+    let (keep_prob, other_prob) = (epsilon.exp() / denominator, 1. / denominator);
+
+    let mut counts = categories.iter()
+        .map(|category| (category, 0)).collect::<IndexMap<&T, i64>>();
+    data.iter().for_each(|v| { counts.entry(v).and_modify(|count| *count += 1); });
+
+    let num_records = data.len() as f64;
+    Ok(ndarray::Array::from(counts.values()
+        .map(|&count| (count as f64 - num_records * other_prob) / (keep_prob - other_prob))
+        .collect::<Vec<Float>>()).into_dyn())
+}
+
+
+#[cfg(test)]
+mod test_randomized_response {
+    use ndarray::Array;
+
+    use crate::components::randomized_response::{randomized_response, randomized_response_estimate};
+
+    #[test]
+    fn test_estimate() {
+        let categories = vec![0, 1, 2, 3];
+        let data = Array::from_shape_fn(vec![20_000], |idx| if idx[0] % 4 == 0 { 0 } else { 1 });
+
+        let perturbed = randomized_response(data, &categories, 2., false).unwrap();
+        let estimates = randomized_response_estimate(&perturbed, &categories, 2.).unwrap();
+
+        // the estimates always sum to the number of records
+        assert!((estimates.sum() - 20_000.).abs() < 1e-6);
+        [5_000., 15_000., 0., 0.].iter().zip(estimates.iter())
+            .for_each(|(expected, estimate)| assert!((expected - estimate).abs() < 1_000.));
+    }
+
+    #[test]
+    fn test_unknown_category() {
+        let data = ndarray::arr1(&[0, 5]).into_dyn();
+        assert!(randomized_response(data, &[0, 1], 1., false).is_err());
+    }
+}
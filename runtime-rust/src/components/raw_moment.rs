@@ -7,13 +7,18 @@ use crate::components::Evaluable;
 use whitenoise_validator::{proto, Float};
 use ndarray::ArrayD;
 use crate::components::mean::mean;
+use crate::utilities::check_float_overflow;
 
 use std::convert::TryFrom;
 
 impl Evaluable for proto::RawMoment {
-    fn evaluate(&self, _privacy_definition: &Option<proto::PrivacyDefinition>, mut arguments: NodeArguments) -> Result<ReleaseNode> {
+    fn evaluate(&self, privacy_definition: &Option<proto::PrivacyDefinition>, mut arguments: NodeArguments) -> Result<ReleaseNode> {
+        let protect_overflow = privacy_definition.as_ref()
+            .map(|v| v.protect_overflow).unwrap_or(false);
         let data = take_argument(&mut arguments, "data")?.array()?.float()?;
-        Ok(ReleaseNode::new(raw_moment(&data, self.order)?.into()))
+        let moments = raw_moment(&data, self.order)?;
+
+        Ok(ReleaseNode::new(if protect_overflow { check_float_overflow(moments)? } else { moments }.into()))
     }
 }
 
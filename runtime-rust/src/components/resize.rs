@@ -565,3 +565,34 @@ pub fn create_sampling_indices(k: i64, n: i64, enforce_constant_time: bool) -> R
     // create set of sampling indices
     create_subset(&index_vec, &weight_vec, k as usize, enforce_constant_time)
 }
+
+
+#[cfg(test)]
+mod test_resize {
+    use ndarray::{arr1, Array, ArrayD};
+
+    use whitenoise_validator::{Float, proto};
+
+    use crate::components::resize::{resize_float, RowResizeConfig};
+    use crate::utilities::test_constant_time::assert_data_independent_time;
+
+    #[test]
+    #[ignore]
+    fn test_timing() {
+        let privacy_definition = Some(proto::PrivacyDefinition {
+            protect_elapsed_time: true,
+            ..Default::default()
+        });
+        let datasets: Vec<ArrayD<Float>> = vec![
+            Array::zeros(vec![1_000, 2]),
+            Array::from_elem(vec![1_000, 2], 100.),
+            Array::from_elem(vec![1_000, 2], Float::NAN)];
+
+        [500, 2_000].iter().for_each(|&number_rows| assert_data_independent_time(&datasets, |data| {
+            resize_float(
+                data, RowResizeConfig::NumRows(number_rows), None, "uniform",
+                arr1(&[0., 0.]).into_dyn(), arr1(&[1., 1.]).into_dyn(), None, None,
+                &privacy_definition).unwrap();
+        }));
+    }
+}
@@ -0,0 +1,134 @@
+use std::collections::BTreeMap;
+
+use indexmap::indexmap;
+use ndarray::ArrayD;
+
+use whitenoise_validator::{Integer, proto};
+use whitenoise_validator::base::{Array, ReleaseNode, Value};
+use whitenoise_validator::errors::*;
+use whitenoise_validator::utilities::privacy::{get_delta, get_epsilon, spread_privacy_usage};
+use whitenoise_validator::utilities::take_argument;
+
+use crate::components::Evaluable;
+use crate::NodeArguments;
+use crate::utilities::mechanisms::simple_geometric_mechanism;
+
+
+impl Evaluable for proto::StabilityHistogram {
+    fn evaluate(&self, privacy_definition: &Option<proto::PrivacyDefinition>, mut arguments: NodeArguments) -> Result<ReleaseNode> {
+        let privacy_definition = privacy_definition.as_ref()
+            .ok_or_else(|| Error::from("privacy_definition must be defined"))?;
+
+        let usages = spread_privacy_usage(&self.privacy_usage, 1)?;
+        let (epsilon, delta) = (get_epsilon(&usages[0])?, get_delta(&usages[0])?);
+
+        // a substitution may decrement the count of one key and increment the count of another
+        use proto::privacy_definition::Neighboring;
+        let sensitivity = match Neighboring::from_i32(privacy_definition.neighboring)
+            .ok_or_else(|| Error::from("neighboring definition must be either \"AddRemove\" or \"Substitute\""))? {
+            Neighboring::AddRemove => 1.,
+            Neighboring::Substitute => 2.
+        };
+
+        let (categories, counts): (Value, Value) = match take_argument(&mut arguments, "data")?.array()? {
+            Array::Bool(data) => {
+                let (categories, counts) = stability_histogram(&data, epsilon, delta, sensitivity)?;
+                (categories.into(), counts.into())
+            }
+            Array::Int(data) => {
+                let (categories, counts) = stability_histogram(&data, epsilon, delta, sensitivity)?;
+                (categories.into(), counts.into())
+            }
+            Array::Str(data) => {
+                let (categories, counts) = stability_histogram(&data, epsilon, delta, sensitivity)?;
+                (categories.into(), counts.into())
+            }
+            Array::Float(_) => return Err("data: floats may not be used as keys".into())
+        };
+
+        Ok(ReleaseNode {
+            value: Value::Dataframe(indexmap!["categories".into() => categories, "counts".into() => counts]),
+            privacy_usages: Some(usages),
+            public: true,
+        })
+    }
+}
+
+/// Release the noisy counts of the keys observed in the data, dropping keys with small noisy counts.
+///
+/// Each observed count is perturbed with Geometric noise of scale `sensitivity / epsilon`.
+/// A key present in only one of two neighboring datasets has a count of one,
+/// so it is released with probability at most `delta / sensitivity` when the threshold is
+/// `1 + ceil(sensitivity ln(sensitivity / delta) / epsilon)`.
+/// Keys are released in sorted order, so that the order of the data is not revealed.
+///
+/// # Arguments
+/// * `data` - Data whose distinct values are the keys of the histogram.
+/// * `epsilon` - Multiplicative privacy loss parameter.
+/// * `delta` - Additive privacy loss parameter, the probability of releasing a key held by a single record.
+/// * `sensitivity` - Number of counts changed by one record, 1 under AddRemove and 2 under Substitute neighboring.
+///
+/// # Return
+/// The released keys, and their noisy counts.
+///
+/// # Example
+/// ```
+/// use ndarray::arr1;
+/// use whitenoise_runtime::components::stability_histogram::stability_histogram;
+/// let data = arr1(&[1, 1, 1, 2]).into_dyn();
+/// let (categories, counts) = stability_histogram(&data, 1., 1e-6, 1.).unwrap();
+/// assert_eq!(categories.len(), counts.len());
+/// ```
+pub fn stability_histogram<T: Clone + Ord>(
+    data: &ArrayD<T>, epsilon: f64, delta: f64, sensitivity: f64
+) -> Result<(ArrayD<T>, ArrayD<Integer>)> {
+    if epsilon <= 0. {
+        return Err("epsilon must be positive".into())
+    }
+    if delta <= 0. || delta >= 1. {
+        return Err("delta must be within (0, 1)".into())
+    }
+    let threshold = get_stability_threshold(epsilon, delta, sensitivity);
+
+    let mut counts = BTreeMap::<&T, Integer>::new();
+    data.iter().for_each(|v| *counts.entry(v).or_insert(0) += 1);
+
+    let (categories, counts) = counts.into_iter()
+        .map(|(category, count)| Ok((category, count + simple_geometric_mechanism(
+            epsilon, sensitivity, 0, Integer::MAX, false)?)))
+        .collect::<Result<Vec<(&T, Integer)>>>()?.into_iter()
+        .filter(|(_, count)| *count >= threshold)
+        .map(|(category, count)| (category.clone(), count))
+        .unzip::<T, Integer, Vec<T>, Vec<Integer>>();
+
+    Ok((ndarray::Array::from(categories).into_dyn(), ndarray::Array::from(counts).into_dyn()))
+}
+
+/// The smallest noisy count released by the stability histogram.
+pub fn get_stability_threshold(epsilon: f64, delta: f64, sensitivity: f64) -> Integer {
+    1 + (sensitivity * (sensitivity / delta).ln() / epsilon).ceil() as Integer
+}
+
+
+#[cfg(test)]
+mod test_stability_histogram {
+    use ndarray::Array;
+
+    use crate::components::stability_histogram::{get_stability_threshold, stability_histogram};
+
+    #[test]
+    fn test_threshold() {
+        let threshold = get_stability_threshold(1., 1e-6, 1.);
+        let data = Array::from_shape_fn(vec![1_000], |idx| match idx[0] {
+            0 => "rare".to_string(),
+            i if i % 2 == 0 => "even".to_string(),
+            _ => "odd".to_string()
+        });
+
+        let (categories, counts) = stability_histogram(&data, 1., 1e-6, 1.).unwrap();
+
+        // frequent keys are released in sorted order, and the key held by one record is dropped
+        assert_eq!(categories.iter().cloned().collect::<Vec<String>>(), vec!["even".to_string(), "odd".to_string()]);
+        assert!(counts.iter().all(|count| *count >= threshold));
+    }
+}
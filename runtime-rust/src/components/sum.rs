@@ -4,17 +4,26 @@ use crate::NodeArguments;
 use whitenoise_validator::base::{Array, ReleaseNode};
 use whitenoise_validator::utilities::{take_argument};
 use crate::components::Evaluable;
-use whitenoise_validator::proto;
+use whitenoise_validator::{proto, Float};
 use ndarray::{ArrayD};
 use std::ops::Add;
-use crate::utilities::get_num_columns;
-use num::Zero;
+use crate::utilities::{check_float_overflow, get_num_columns};
+use num::{CheckedAdd, Zero};
 
 impl Evaluable for proto::Sum {
-    fn evaluate(&self, _privacy_definition: &Option<proto::PrivacyDefinition>, mut arguments: NodeArguments) -> Result<ReleaseNode> {
-        match take_argument(&mut arguments, "data")?.array()? {
-            Array::Float(data) => Ok(sum(&data)?.into()),
-            Array::Int(data) => Ok(sum(&data)?.into()),
+    fn evaluate(&self, privacy_definition: &Option<proto::PrivacyDefinition>, mut arguments: NodeArguments) -> Result<ReleaseNode> {
+        let protect_overflow = privacy_definition.as_ref()
+            .map(|v| v.protect_overflow).unwrap_or(false);
+        let protect_floating_point = privacy_definition.as_ref()
+            .map(|v| v.protect_floating_point).unwrap_or(false);
+
+        match (take_argument(&mut arguments, "data")?.array()?, protect_overflow) {
+            (Array::Float(data), protect_overflow) => {
+                let sums = if protect_floating_point { pairwise_sum(&data)? } else { sum(&data)? };
+                Ok(if protect_overflow { check_float_overflow(sums)? } else { sums }.into())
+            },
+            (Array::Int(data), false) => Ok(sum(&data)?.into()),
+            (Array::Int(data), true) => Ok(checked_sum(&data)?.into()),
             _ => return Err("data must be either f64 or i64".into())
         }.map(ReleaseNode::new)
     }
@@ -54,3 +63,117 @@ pub fn sum<T: Add<T, Output=T> + Zero + Copy>(data: &ArrayD<T>) -> Result<ArrayD
         Err(_) => Err("unable to package Sum result into an array".into())
     }
 }
+
+/// Calculates sum for each column of the data, failing instead of wrapping around on overflow.
+///
+/// # Arguments
+/// * `data` - Data for which you would like the sum of each column.
+///
+/// # Return
+/// Sum of each column of the data.
+///
+/// # Example
+/// ```
+/// use ndarray::prelude::*;
+/// use whitenoise_runtime::components::sum::checked_sum;
+/// let data = arr2(&[ [1, 10], [2, 20], [3, 30] ]).into_dyn();
+/// let sums = checked_sum(&data).unwrap();
+/// assert!(sums == arr2(&[[6, 60]]).into_dyn());
+///
+/// let data = arr1(&[i64::MAX, 1]).into_dyn();
+/// assert!(checked_sum(&data).is_err());
+/// ```
+pub fn checked_sum<T: CheckedAdd + Zero + Copy>(data: &ArrayD<T>) -> Result<ArrayD<T>> {
+
+    // iterate over the generalized columns
+    let sums = data.gencolumns().into_iter()
+        .map(|column| column.iter().try_fold(T::zero(), |sum, i| sum.checked_add(i)))
+        .collect::<Option<Vec<T>>>()
+        .ok_or_else(|| Error::from("sum overflowed the range of the atomic type"))?;
+
+    let array = match data.ndim() {
+        1 => ndarray::Array::from_shape_vec(vec![], sums),
+        2 => ndarray::Array::from_shape_vec(vec![1, get_num_columns(data)? as usize], sums),
+        _ => return Err("invalid data shape for Sum".into())
+    };
+
+    match array {
+        Ok(array) => Ok(array),
+        Err(_) => Err("unable to package Sum result into an array".into())
+    }
+}
+
+
+/// Calculates sum for each column of the data, adding values pairwise.
+///
+/// The rounding error of pairwise summation is at most `gamma * sum(|x_i|)`,
+/// where `gamma` grows with the logarithm of the number of records,
+/// regardless of the order of the records.
+/// The validator widens the sensitivity of `Sum` and `Mean` by this error when protecting floating-point.
+///
+/// # Arguments
+/// * `data` - Data for which you would like the sum of each column.
+///
+/// # Return
+/// Sum of each column of the data.
+///
+/// # Example
+/// ```
+/// use ndarray::prelude::*;
+/// use whitenoise_runtime::components::sum::pairwise_sum;
+/// let data = arr2(&[ [1.,10.], [2., 20.], [3., 30.] ]).into_dyn();
+/// let sums = pairwise_sum(&data).unwrap();
+/// assert!(sums == arr2(&[[6., 60.]]).into_dyn());
+/// ```
+pub fn pairwise_sum(data: &ArrayD<Float>) -> Result<ArrayD<Float>> {
+
+    // iterate over the generalized columns
+    let sums = data.gencolumns().into_iter()
+        .map(|column| pairwise_sum_slice(&column.to_vec())).collect::<Vec<Float>>();
+
+    let array = match data.ndim() {
+        1 => ndarray::Array::from_shape_vec(vec![], sums),
+        2 => ndarray::Array::from_shape_vec(vec![1, get_num_columns(data)? as usize], sums),
+        _ => return Err("invalid data shape for Sum".into())
+    };
+
+    match array {
+        Ok(array) => Ok(array),
+        Err(_) => Err("unable to package Sum result into an array".into())
+    }
+}
+
+/// Sum the two halves of the slice separately, so that each value passes through at most ceil(log2(n)) additions.
+fn pairwise_sum_slice(values: &[Float]) -> Float {
+    match values.len() {
+        0 => 0.,
+        1 => values[0],
+        length => {
+            let (left, right) = values.split_at(length / 2);
+            pairwise_sum_slice(left) + pairwise_sum_slice(right)
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod test_sum {
+    use ndarray::arr1;
+
+    use crate::components::sum::{pairwise_sum, sum};
+
+    #[test]
+    fn test_pairwise_sum() {
+        let data = arr1(&[0.1; 1_000]).into_dyn();
+        let exact = 100.;
+        let pairwise_error = (pairwise_sum(&data).unwrap().first().unwrap() - exact).abs();
+        let naive_error = (sum(&data).unwrap().first().unwrap() - exact).abs();
+        assert!(pairwise_error <= naive_error);
+
+        // the error bound used by the validator holds
+        let gamma = whitenoise_validator::utilities::get_pairwise_summation_gamma(1_000, 0).unwrap();
+        assert!(pairwise_error <= gamma * exact);
+
+        assert_eq!(pairwise_sum(&arr1::<f64>(&[]).into_dyn()).unwrap().first(), Some(&0.));
+    }
+}
@@ -5,17 +5,21 @@ use whitenoise_validator::base::ReleaseNode;
 use whitenoise_validator::utilities::take_argument;
 use crate::components::Evaluable;
 use ndarray::{ArrayD, Array};
-use crate::utilities::get_num_columns;
+use crate::utilities::{check_float_overflow, get_num_columns};
 use whitenoise_validator::{proto, Float};
 use crate::components::mean::mean;
 
 impl Evaluable for proto::Variance {
-    fn evaluate(&self, _privacy_definition: &Option<proto::PrivacyDefinition>, mut arguments: NodeArguments) -> Result<ReleaseNode> {
+    fn evaluate(&self, privacy_definition: &Option<proto::PrivacyDefinition>, mut arguments: NodeArguments) -> Result<ReleaseNode> {
+        let protect_overflow = privacy_definition.as_ref()
+            .map(|v| v.protect_overflow).unwrap_or(false);
         let delta_degrees_of_freedom = if self.finite_sample_correction { 1 } else { 0 } as usize;
-        Ok(ReleaseNode::new(variance(
+        let variances = variance(
             &take_argument(&mut arguments, "data")?.array()?.float()?,
             delta_degrees_of_freedom
-        )?.into()))
+        )?;
+
+        Ok(ReleaseNode::new(if protect_overflow { check_float_overflow(variances)? } else { variances }.into()))
     }
 }
 
@@ -0,0 +1,230 @@
+//! A file-backed ledger of the privacy usage of every release on a dataset.
+//!
+//! Each release is appended to the ledger as a length-delimited `LedgerEntry` protobuf.
+//! Entries for all datasets may share one file, and are distinguished by their `dataset_id`.
+//! The ledger is append-only. Concurrent releases are serialized by an exclusive advisory lock on the ledger file,
+//! which is held from the budget check until the release is recorded.
+
+use std::collections::HashMap;
+use std::fs::{File, OpenOptions};
+use std::io::{Read, Write};
+use std::path::Path;
+
+use fs2::FileExt;
+use prost::Message;
+
+use whitenoise_validator::errors::*;
+use whitenoise_validator::proto;
+use whitenoise_validator::base::Release;
+use whitenoise_validator::utilities::propagate_properties;
+use whitenoise_validator::utilities::privacy::{
+    compose_privacy_usages, compute_graph_privacy_usage, get_delta, get_epsilon, to_approximate};
+
+/// Compute the privacy usage of an analysis, before it is released.
+///
+/// The usage of the analysis bounds the usage of the release, so it is the usage recorded in the ledger.
+pub fn compute_release_usage(
+    privacy_definition: &proto::PrivacyDefinition,
+    computation_graph: &HashMap<u32, proto::Component>,
+    release: &Release,
+) -> Result<proto::PrivacyUsage> {
+    let (mut computation_graph, mut release) = (computation_graph.clone(), release.clone());
+    let properties = propagate_properties(
+        &Some(privacy_definition.clone()),
+        &mut computation_graph,
+        &mut release, None, false)?.0;
+
+    compute_graph_privacy_usage(&computation_graph, privacy_definition, &properties, &release)
+}
+
+/// An exclusive advisory lock on the ledger file, released when dropped.
+pub struct LedgerLock(File);
+
+/// Block until an exclusive lock on the ledger file is acquired.
+///
+/// Other processes and threads that lock the same ledger wait until the lock is dropped,
+/// so a budget check and the record of the release may not be interleaved with those of another release.
+pub fn lock_ledger(ledger: &proto::Ledger) -> Result<LedgerLock> {
+    let file = OpenOptions::new().create(true).append(true).open(&ledger.path)
+        .map_err(|err| Error::from(format!("ledger: unable to open {}: {}", ledger.path, err)))?;
+    file.lock_exclusive()
+        .map_err(|err| Error::from(format!("ledger: unable to lock {}: {}", ledger.path, err)))?;
+    Ok(LedgerLock(file))
+}
+
+/// Retrieve the privacy usages of all prior releases on the dataset.
+pub fn get_entries(ledger: &proto::Ledger) -> Result<Vec<proto::PrivacyUsage>> {
+    if !Path::new(&ledger.path).exists() {
+        return Ok(Vec::new())
+    }
+
+    let mut buffer = Vec::new();
+    OpenOptions::new().read(true).open(&ledger.path)
+        .and_then(|mut file| file.read_to_end(&mut buffer))
+        .map_err(|err| Error::from(format!("ledger: unable to read {}: {}", ledger.path, err)))?;
+
+    let mut buffer = buffer.as_slice();
+    let mut usages = Vec::new();
+    while !buffer.is_empty() {
+        let entry = proto::LedgerEntry::decode_length_delimited(&mut buffer)
+            .map_err(|_| Error::from("ledger: unable to parse entry"))?;
+        if entry.dataset_id == ledger.dataset_id {
+            usages.push(entry.privacy_usage.ok_or("ledger: privacy_usage must be defined on each entry")?);
+        }
+    }
+    Ok(usages)
+}
+
+/// Compose the privacy usages of all prior releases on the dataset, in terms of (epsilon, delta).
+pub fn get_spent_budget(
+    ledger: &proto::Ledger,
+    privacy_definition: &proto::PrivacyDefinition,
+) -> Result<proto::PrivacyUsage> {
+    compose_approximate(privacy_definition, &get_entries(ledger)?)
+}
+
+/// Compute the (epsilon, delta) that may still be spent on the dataset.
+pub fn get_remaining_budget(
+    ledger: &proto::Ledger,
+    privacy_definition: &proto::PrivacyDefinition,
+) -> Result<proto::PrivacyUsage> {
+    let (budget_epsilon, budget_delta) = get_budget(ledger)?;
+    let spent = get_spent_budget(ledger, privacy_definition)?;
+
+    Ok(proto::PrivacyUsage {
+        distance: Some(proto::privacy_usage::Distance::Approximate(proto::privacy_usage::DistanceApproximate {
+            epsilon: (budget_epsilon - get_epsilon(&spent)?).max(0.),
+            delta: (budget_delta - get_delta(&spent)?).max(0.),
+        }))
+    })
+}
+
+/// Return an error if releasing the privacy usage would exceed the budget of the dataset.
+///
+/// The lock must be held until the release is recorded.
+pub fn check_budget(
+    _lock: &LedgerLock,
+    ledger: &proto::Ledger,
+    privacy_definition: &proto::PrivacyDefinition,
+    privacy_usage: &proto::PrivacyUsage,
+) -> Result<()> {
+    let (budget_epsilon, budget_delta) = get_budget(ledger)?;
+
+    let mut usages = get_entries(ledger)?;
+    usages.push(privacy_usage.clone());
+    let total = compose_approximate(privacy_definition, &usages)?;
+    let (epsilon, delta) = (get_epsilon(&total)?, get_delta(&total)?);
+
+    if epsilon > budget_epsilon || delta > budget_delta {
+        return Err(format!(
+            "ledger: the release would bring the total privacy usage on dataset {} to ({}, {}), which exceeds the budget of ({}, {})",
+            ledger.dataset_id, epsilon, delta, budget_epsilon, budget_delta).into())
+    }
+    Ok(())
+}
+
+/// Append the privacy usage of a release to the ledger, under the lock its budget was checked with.
+pub fn record_release(lock: &LedgerLock, ledger: &proto::Ledger, privacy_usage: &proto::PrivacyUsage) -> Result<()> {
+    let entry = proto::LedgerEntry {
+        dataset_id: ledger.dataset_id.clone(),
+        privacy_usage: Some(privacy_usage.clone()),
+    };
+
+    let mut buffer = Vec::new();
+    entry.encode_length_delimited(&mut buffer)
+        .map_err(|_| Error::from("ledger: unable to serialize entry"))?;
+
+    // the locked file was opened in append mode, so the entry is written after every prior entry
+    let mut file = &lock.0;
+    file.write_all(&buffer).and_then(|_| file.sync_all())
+        .map_err(|err| Error::from(format!("ledger: unable to write {}: {}", ledger.path, err)))
+}
+
+fn get_budget(ledger: &proto::Ledger) -> Result<(f64, f64)> {
+    if ledger.dataset_id.is_empty() {
+        return Err("ledger: dataset_id must be defined".into())
+    }
+    match ledger.budget.as_ref().and_then(|budget| budget.distance.as_ref()) {
+        Some(proto::privacy_usage::Distance::Approximate(budget)) => Ok((budget.epsilon, budget.delta)),
+        _ => Err("ledger: budget must be an approximate privacy usage".into())
+    }
+}
+
+fn compose_approximate(
+    privacy_definition: &proto::PrivacyDefinition,
+    usages: &[proto::PrivacyUsage],
+) -> Result<proto::PrivacyUsage> {
+    if usages.is_empty() {
+        return Ok(proto::PrivacyUsage {
+            distance: Some(proto::privacy_usage::Distance::Approximate(proto::privacy_usage::DistanceApproximate {
+                epsilon: 0., delta: 0.,
+            }))
+        })
+    }
+    to_approximate(
+        &compose_privacy_usages(privacy_definition, usages)?,
+        privacy_definition.conversion_delta)
+}
+
+
+#[cfg(test)]
+mod test_ledger {
+    use whitenoise_validator::proto;
+    use crate::ledger::{check_budget, get_remaining_budget, lock_ledger, record_release};
+    use whitenoise_validator::utilities::privacy::get_epsilon;
+    use whitenoise_validator::base::test_usage::usage;
+
+    #[test]
+    fn test_budget() {
+        let path = std::env::temp_dir().join(format!("whitenoise_ledger_{}", std::process::id()));
+        let ledger = |dataset_id: &str| proto::Ledger {
+            path: path.to_str().unwrap().to_string(),
+            dataset_id: dataset_id.to_string(),
+            budget: Some(usage(1.)),
+        };
+        let privacy_definition = proto::PrivacyDefinition { group_size: 1, ..Default::default() };
+
+        let lock = lock_ledger(&ledger("a")).unwrap();
+        check_budget(&lock, &ledger("a"), &privacy_definition, &usage(0.6)).unwrap();
+        record_release(&lock, &ledger("a"), &usage(0.6)).unwrap();
+
+        // releases on other datasets are not affected
+        check_budget(&lock, &ledger("b"), &privacy_definition, &usage(0.6)).unwrap();
+
+        assert!(check_budget(&lock, &ledger("a"), &privacy_definition, &usage(0.6)).is_err());
+        let remaining = get_remaining_budget(&ledger("a"), &privacy_definition).unwrap();
+        assert!((get_epsilon(&remaining).unwrap() - 0.4).abs() < 1e-12);
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_concurrent_releases() {
+        let path = std::env::temp_dir().join(format!("whitenoise_ledger_concurrent_{}", std::process::id()));
+        let ledger = proto::Ledger {
+            path: path.to_str().unwrap().to_string(),
+            dataset_id: "a".to_string(),
+            budget: Some(usage(1.)),
+        };
+        let privacy_definition = proto::PrivacyDefinition { group_size: 1, ..Default::default() };
+
+        // both releases fit the budget on their own, but not together
+        let handles = (0..2).map(|_| {
+            let (ledger, privacy_definition) = (ledger.clone(), privacy_definition.clone());
+            std::thread::spawn(move || {
+                let lock = lock_ledger(&ledger).unwrap();
+                check_budget(&lock, &ledger, &privacy_definition, &usage(0.6))?;
+                std::thread::sleep(std::time::Duration::from_millis(50));
+                record_release(&lock, &ledger, &usage(0.6))
+            })
+        }).collect::<Vec<_>>();
+
+        let successes = handles.into_iter()
+            .map(|handle| handle.join().unwrap())
+            .filter(Result::is_ok)
+            .count();
+        assert_eq!(successes, 1);
+
+        std::fs::remove_file(path).unwrap();
+    }
+}
@@ -17,12 +17,15 @@ use whitenoise_validator::errors::*;
 pub mod utilities;
 pub mod components;
 pub mod base;
+pub mod ledger;
 
 use std::collections::{HashMap, HashSet};
 use std::vec::Vec;
 
 use whitenoise_validator::base::{Value, ReleaseNode, Release, IndexKey, ComponentExpansion, ValueProperties};
 use whitenoise_validator::utilities::{get_sinks, get_input_properties, get_dependents};
+use whitenoise_validator::utilities::privacy_filter::{get_released_privacy_usages, privacy_filter_continues};
+use whitenoise_validator::components::Mechanism;
 
 use crate::components::Evaluable;
 
@@ -50,10 +53,30 @@ pub type NodeArguments = IndexMap<IndexKey, Value>;
 /// # Return
 /// a collection of computed values for components in the graph
 pub fn release(
+    privacy_definition: Option<proto::PrivacyDefinition>,
+    computation_graph: HashMap<u32, proto::Component>,
+    release: Release,
+    filter_level: proto::FilterLevel,
+) -> Result<(Release, Vec<Error>)> {
+    release_with_ledger(privacy_definition, computation_graph, release, filter_level, None)
+}
+
+/// Execute the computation, as in `release`, while tracking the privacy usage on a budget ledger
+///
+/// # Arguments
+/// * `analysis` - a computational graph and definition of privacy, in prost protobuf format
+/// * `release` - a collection of precomputed values for components in the graph
+/// * `filter_level` - configure the amount of information included in the return
+/// * `ledger` - if set, refuse the release if it would exceed the budget of the ledger, and record it otherwise
+///
+/// # Return
+/// a collection of computed values for components in the graph
+pub fn release_with_ledger(
     privacy_definition: Option<proto::PrivacyDefinition>,
     mut computation_graph: HashMap<u32, proto::Component>,
     mut release: Release,
-    filter_level: proto::FilterLevel
+    filter_level: proto::FilterLevel,
+    ledger: Option<&proto::Ledger>
 ) -> Result<(Release, Vec<Error>)> {
 
     if let Some(privacy_definition) = &privacy_definition {
@@ -62,6 +85,21 @@ pub fn release(
         }
     }
 
+    // check the budget before any data is touched,
+    //     and hold the lock on the ledger until the release is recorded
+    let ledger_usage = match ledger {
+        Some(ledger) => {
+            let privacy_definition = privacy_definition.as_ref()
+                .ok_or_else(|| Error::from("privacy_definition must be defined to release against a ledger"))?;
+            let privacy_usage = ledger::compute_release_usage(
+                privacy_definition, &computation_graph, &release)?;
+            let lock = ledger::lock_ledger(ledger)?;
+            ledger::check_budget(&lock, ledger, privacy_definition, &privacy_usage)?;
+            Some((lock, ledger, privacy_usage))
+        }
+        None => None
+    };
+
     // core state for the graph execution algorithm
     let mut traversal: Vec<u32> = get_sinks(&computation_graph).into_iter().collect();
 
@@ -81,6 +119,16 @@ pub fn release(
     // track node parents. Each key is a node id, and the value is the set of node ids that use it
     let mut parents = get_dependents(&computation_graph);
 
+    // usages of the mechanisms released so far, extended as each mechanism is released
+    let privacy_filter = privacy_definition.as_ref()
+        .and_then(|privacy_definition| privacy_definition.privacy_filter.as_ref()
+            .map(|budget| (privacy_definition, budget)));
+    let mut released_usages = match privacy_filter {
+        Some((privacy_definition, _)) => get_released_privacy_usages(
+            &computation_graph, privacy_definition, &properties, &release)?,
+        None => Vec::new()
+    };
+
     // evaluate components until the traversal is empty
     while !traversal.is_empty() {
 
@@ -176,6 +224,23 @@ pub fn release(
         // the expansion may have overwritten the current component
         let component = computation_graph.get(&component_id).unwrap();
 
+        // halt once the privacy filter no longer permits releasing mechanisms
+        if let Some((privacy_definition, budget)) = privacy_filter {
+            if let Some(usages) = component.get_privacy_usage(
+                privacy_definition, None, &get_input_properties(component, &properties)?)? {
+
+                let num_released = released_usages.len();
+                released_usages.extend(usages);
+                let continues = privacy_filter_continues(privacy_definition, budget, &released_usages)?;
+                released_usages.truncate(num_released);
+
+                if !continues {
+                    warnings.push("privacy filter: releasing the next mechanism would exceed the budget, so the analysis has been halted".into());
+                    break;
+                }
+            }
+        }
+
         // println!("node id:    {:?}", component_id);
         // println!("component:  {:?}", component.variant);
         // println!("arguments:  {:?}", node_arguments);
@@ -232,10 +297,25 @@ pub fn release(
             .map(ValueProperties::is_public)
             .unwrap_or(false);
 
+        // record the actual usages of the release
+        if let Some((privacy_definition, _)) = privacy_filter {
+            if evaluation.privacy_usages.is_some() {
+                released_usages.extend(component.get_privacy_usage(
+                    privacy_definition,
+                    evaluation.privacy_usages.as_ref(),
+                    &get_input_properties(component, &properties)?)?
+                    .unwrap_or_else(Vec::new));
+            }
+        }
+
         // store the evaluated `Value` enum in the release
         release.insert(component_id, evaluation);
     }
 
+    if let Some((lock, ledger, privacy_usage)) = ledger_usage {
+        ledger::record_release(&lock, ledger, &privacy_usage)?;
+    }
+
     // remove all omitted nodes (temporarily added to the graph while executing)
     release.retain(|node_id, _| !computation_graph.get(node_id)
         .map(|v| v.omit)
@@ -1,9 +1,10 @@
 use whitenoise_validator::errors::*;
 
 use crate::utilities;
-use whitenoise_validator::Float;
+use whitenoise_validator::{Float, Integer};
 use crate::utilities::{noise};
-use whitenoise_validator::components::gaussian_mechanism::get_analytic_gaussian_sigma;
+use noisy_float::types::{n64, N64};
+use whitenoise_validator::components::gaussian_mechanism::{get_analytic_gaussian_sigma, get_concentrated_gaussian_sigma};
 
 /// Returns noise drawn according to the Laplace mechanism
 ///
@@ -157,6 +158,72 @@ pub fn gaussian_mechanism(
     noise::sample_gaussian(0., scale, enforce_constant_time)
 }
 
+/// Returns noise drawn according to the Gaussian mechanism, calibrated to zero-concentrated differential privacy.
+///
+/// Noise is drawn from a Gaussian distribution with scale sensitivity/sqrt(2*rho) and centered about 0.
+///
+/// For more information, see
+/// [Bun & Steinke (2016)](https://arxiv.org/pdf/1605.02065.pdf), Proposition 1.6.
+///
+/// # Arguments
+///
+/// * `rho` - Privacy loss parameter for zero-concentrated differential privacy.
+/// * `sensitivity` - Upper bound on the L2 sensitivity of the function you want to privatize.
+/// * `enforce_constant_time` - Whether or not to enforce the algorithm to run in constant time
+///
+/// # Return
+/// A draw from Gaussian distribution with scale defined as above.
+///
+/// # Examples
+/// ```
+/// use whitenoise_runtime::utilities::mechanisms::concentrated_gaussian_mechanism;
+/// let n = concentrated_gaussian_mechanism(0.1, 2.0, false);
+/// ```
+pub fn concentrated_gaussian_mechanism(
+    rho: f64, sensitivity: f64,
+    enforce_constant_time: bool
+) -> Result<f64> {
+    if rho <= 0. || sensitivity <= 0. {
+        return Err(format!("rho ({}) and sensitivity ({}) must all be positive", rho, sensitivity).into());
+    }
+
+    let scale = get_concentrated_gaussian_sigma(rho, sensitivity);
+    // this uses mpfr noise if available
+    noise::sample_gaussian(0., scale, enforce_constant_time)
+}
+
+/// Returns integer noise drawn according to the discrete Gaussian mechanism.
+///
+/// Noise is drawn from the discrete Gaussian with sigma = sensitivity / sqrt(2 rho), which satisfies rho-zCDP.
+/// The sampler is exact, and is not susceptible to floating-point attacks.
+/// See [Canonne, Kamath, Steinke (2020)](https://arxiv.org/abs/2004.00010) for more information.
+///
+/// # Arguments
+/// * `rho` - Privacy loss parameter under zero-concentrated differential privacy.
+/// * `sensitivity` - Upper bound on the L2 sensitivity of the function you want to privatize.
+/// * `enforce_constant_time` - Whether or not to enforce the algorithm to run in constant time
+///
+/// # Return
+/// A draw from the discrete Gaussian distribution centered at 0.
+///
+/// # Examples
+/// ```
+/// use whitenoise_runtime::utilities::mechanisms::discrete_gaussian_mechanism;
+/// let n = discrete_gaussian_mechanism(0.1, 1.0, false);
+/// # n.unwrap();
+/// ```
+pub fn discrete_gaussian_mechanism(
+    rho: f64, sensitivity: f64,
+    enforce_constant_time: bool
+) -> Result<Integer> {
+    if rho <= 0. || sensitivity <= 0. {
+        return Err(format!("rho ({}) and sensitivity ({}) must all be positive", rho, sensitivity).into());
+    }
+
+    let scale = get_concentrated_gaussian_sigma(rho, sensitivity);
+    noise::sample_discrete_gaussian(0, scale, enforce_constant_time)
+}
+
 /// Returns noise drawn according to the Geometric mechanism.
 ///
 /// Uses the Geometric mechanism as originally proposed in
@@ -263,4 +330,537 @@ pub fn exponential_mechanism<T>(
 
     // sample element relative to probability
     utilities::sample_from_set(candidate_set, &weight_vec, enforce_constant_time)
-}
\ No newline at end of file
+}
+
+/// Returns a quantile of the data, sampled from the continuous range between `lower` and `upper`.
+///
+/// The sorted data split the range into intervals. The utility of the `i`-th interval is `-|i - alpha * n|`,
+/// where `i` is the number of records below the interval, and each interval is chosen with probability
+/// proportional to its length times `exp(epsilon * utility / (2 * sensitivity))`.
+/// The release is then sampled uniformly from within the chosen interval.
+/// For more information, see
+/// A. Smith, Privacy-preserving statistical estimation with optimal convergence rates, STOC 2011.
+///
+/// NOTE: This implementation is likely non-private because of the difference between theory on
+///       the real numbers and floating-point numbers.
+///
+/// # Arguments
+/// * `epsilon` - Multiplicative privacy loss parameter.
+/// * `sensitivity` - Sensitivity of the utility of each interval.
+/// * `alpha` - Desired quantile, within `[0, 1]`.
+/// * `data` - Records of one column of the data.
+/// * `lower` - Lower bound of the data.
+/// * `upper` - Upper bound of the data.
+/// * `enforce_constant_time` - Whether or not to enforce the algorithm to run in constant time
+///
+/// # Return
+/// Differentially private estimate of the quantile.
+///
+/// # Example
+/// ```
+/// use whitenoise_runtime::utilities::mechanisms::continuous_exponential_mechanism;
+/// let data = vec![1., 2., 3., 4., 5.];
+/// let median = continuous_exponential_mechanism(1., 0.5, 0.5, &data, 0., 10., false).unwrap();
+/// assert!(0. <= median && median <= 10.);
+/// ```
+pub fn continuous_exponential_mechanism(
+    epsilon: f64,
+    sensitivity: f64,
+    alpha: f64,
+    data: &[Float],
+    lower: Float,
+    upper: Float,
+    enforce_constant_time: bool
+) -> Result<Float> {
+    if epsilon <= 0. || sensitivity <= 0. {
+        return Err(format!("epsilon ({}) and sensitivity ({}) must be positive", epsilon, sensitivity).into());
+    }
+    if alpha < 0. || alpha > 1. {
+        return Err("alpha must be within [0, 1]".into());
+    }
+    if !lower.is_finite() || !upper.is_finite() || lower > upper {
+        return Err("lower and upper must be finite, and lower may not be greater than upper".into());
+    }
+
+    let edges = get_interval_edges(data, lower, upper, enforce_constant_time);
+
+    // the utilities are shifted so that the largest is zero, to avoid underflow
+    let num_records = data.len() as f64;
+    let utilities = (0..edges.len() - 1)
+        .map(|i| -(i as f64 - alpha * num_records).abs())
+        .collect::<Vec<f64>>();
+    let max_utility = utilities.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+
+    let weights = edges.windows(2).zip(utilities.iter())
+        .map(|(interval, utility)| (interval[1] - interval[0]) * (epsilon * (utility - max_utility) / (2. * sensitivity)).exp())
+        .collect::<Vec<Float>>();
+
+    // every interval is empty when the bounds are equal, or when the records are tied at the quantile and
+    //     the weights of the remaining intervals underflow. The mass is then concentrated at the quantile
+    if weights.iter().sum::<Float>() <= 0. {
+        let best = utilities.iter().position(|utility| *utility >= max_utility).unwrap_or(0);
+        return Ok(edges[best])
+    }
+
+    let indices = (0..weights.len()).collect::<Vec<usize>>();
+    let index = utilities::sample_from_set(&indices, &weights, enforce_constant_time)?;
+    noise::sample_uniform(edges[index], edges[index + 1], enforce_constant_time)
+}
+
+/// Returns several quantiles of the data, sampled jointly from the continuous range between `lower` and `upper`.
+///
+/// The sorted data split the range into intervals, and each quantile is placed in one interval.
+/// The utility of a placement is `-sum_j |g_j - (alpha_j - alpha_{j-1}) * n|`, where `g_j` is the number of records
+/// between quantiles `j - 1` and `j`, and the first and last gaps extend to the bounds.
+/// Each placement is chosen with probability proportional to the volume of the sorted vectors it contains,
+/// times `exp(epsilon * utility / (2 * sensitivity))`, by dynamic programming over the quantiles and intervals.
+/// The quantiles are then sampled uniformly from within their intervals, and sorted.
+/// For more information, see
+/// [Gillenwater, Joseph & Kulesza (2021)](https://arxiv.org/abs/2102.08244).
+///
+/// NOTE: This implementation is likely non-private because of the difference between theory on
+///       the real numbers and floating-point numbers.
+///
+/// # Arguments
+/// * `epsilon` - Multiplicative privacy loss parameter.
+/// * `sensitivity` - Sensitivity of the utility of each placement.
+/// * `alphas` - Desired quantiles, within `[0, 1]`, in non-decreasing order.
+/// * `data` - Records of one column of the data.
+/// * `lower` - Lower bound of the data.
+/// * `upper` - Upper bound of the data.
+/// * `enforce_constant_time` - Whether or not to enforce the algorithm to run in constant time
+///
+/// # Return
+/// Differentially private estimates of the quantiles, in non-decreasing order.
+///
+/// # Example
+/// ```
+/// use whitenoise_runtime::utilities::mechanisms::joint_exponential_mechanism;
+/// let data = vec![1., 2., 3., 4., 5., 6., 7., 8., 9.];
+/// let quartiles = joint_exponential_mechanism(1., 2., &[0.25, 0.5, 0.75], &data, 0., 10., false).unwrap();
+/// assert!(quartiles.windows(2).all(|pair| pair[0] <= pair[1]));
+/// ```
+pub fn joint_exponential_mechanism(
+    epsilon: f64,
+    sensitivity: f64,
+    alphas: &[f64],
+    data: &[Float],
+    lower: Float,
+    upper: Float,
+    enforce_constant_time: bool
+) -> Result<Vec<Float>> {
+    if epsilon <= 0. || sensitivity <= 0. {
+        return Err(format!("epsilon ({}) and sensitivity ({}) must be positive", epsilon, sensitivity).into());
+    }
+    if alphas.is_empty() || alphas.iter().any(|alpha| *alpha < 0. || *alpha > 1.)
+        || alphas.windows(2).any(|pair| pair[0] > pair[1]) {
+        return Err("alphas must be non-empty, within [0, 1], and in non-decreasing order".into());
+    }
+    if !lower.is_finite() || !upper.is_finite() || lower > upper {
+        return Err("lower and upper must be finite, and lower may not be greater than upper".into());
+    }
+
+    let edges = get_interval_edges(data, lower, upper, enforce_constant_time);
+    let log_lengths = edges.windows(2)
+        .map(|interval| (interval[1] - interval[0]).ln())
+        .collect::<Vec<f64>>();
+
+    // every interval is empty when the bounds are equal
+    if log_lengths.iter().all(|v| *v == f64::NEG_INFINITY) {
+        return Ok(vec![lower; alphas.len()])
+    }
+
+    let num_records = data.len();
+    let num_intervals = num_records + 1;
+    let num_quantiles = alphas.len();
+    let scale = epsilon / (2. * sensitivity);
+
+    // expected number of records in the gap before each quantile, and after the last
+    let mut targets = alphas.iter().scan(0., |previous, alpha| {
+        let target = (alpha - *previous) * num_records as f64;
+        *previous = *alpha;
+        Some(target)
+    }).collect::<Vec<f64>>();
+    targets.push((1. - alphas[num_quantiles - 1]) * num_records as f64);
+    let gap_score = |j: usize, gap: usize| -scale * (gap as f64 - targets[j]).abs();
+
+    // cumulative scores of quantiles that share an interval with the previous quantile, and log factorials
+    let mut tied_scores = vec![0.];
+    (0..num_quantiles).for_each(|j| tied_scores.push(tied_scores[j] + gap_score(j, 0)));
+    let mut log_factorials = vec![0.];
+    (1..=num_quantiles).for_each(|r| log_factorials.push(log_factorials[r - 1] + (r as f64).ln()));
+
+    // log weight of placing quantile j in interval k, and the quantiles before it in earlier intervals
+    let mut entries: Vec<Vec<f64>> = Vec::with_capacity(num_quantiles);
+    // log weight of placing quantiles up to j, where quantile j is the last quantile in interval k
+    let mut lasts: Vec<Vec<f64>> = Vec::with_capacity(num_quantiles);
+
+    // the weight of a run of r quantiles in interval k includes the volume of sorted vectors, length^r / r!
+    let get_run_score = |entries: &[Vec<f64>], j: usize, k: usize, r: usize|
+        entries[j + 1 - r][k] + r as f64 * log_lengths[k] - log_factorials[r]
+            + tied_scores[j + 1] - tied_scores[j + 2 - r];
+
+    for j in 0..num_quantiles {
+        entries.push(match j {
+            0 => (0..num_intervals).map(|k| gap_score(0, k)).collect(),
+            _ => log_gap_convolution(&lasts[j - 1], scale, targets[j])
+        });
+        lasts.push((0..num_intervals)
+            .map(|k| log_sum_exp((1..=j + 1).map(|r| get_run_score(&entries, j, k, r))))
+            .collect());
+    }
+
+    // sample the placement, from the last quantile to the first
+    let mut placements = vec![0; num_quantiles];
+    let mut k = sample_log_weights(&(0..num_intervals)
+        .map(|k| lasts[num_quantiles - 1][k] + gap_score(num_quantiles, num_records - k))
+        .collect::<Vec<f64>>(), enforce_constant_time)?;
+    let mut j = num_quantiles - 1;
+    loop {
+        let run = 1 + sample_log_weights(&(1..=j + 1)
+            .map(|r| get_run_score(&entries, j, k, r))
+            .collect::<Vec<f64>>(), enforce_constant_time)?;
+        let first = j + 1 - run;
+        placements[first..=j].iter_mut().for_each(|placement| *placement = k);
+
+        if first == 0 { break }
+        j = first - 1;
+        k = sample_log_weights(&(0..k)
+            .map(|previous| lasts[j][previous] + gap_score(first, k - previous))
+            .collect::<Vec<f64>>(), enforce_constant_time)?;
+    }
+
+    let mut quantiles = placements.into_iter()
+        .map(|k| noise::sample_uniform(edges[k], edges[k + 1], enforce_constant_time))
+        .collect::<Result<Vec<Float>>>()?;
+    // quantiles that share an interval are sorted, and intervals are already in order
+    quantiles.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    Ok(quantiles)
+}
+
+/// Clamp and sort the records, and bracket them with the bounds.
+///
+/// Consecutive edges delimit the intervals between records, where the `k`-th interval has `k` records below it.
+fn get_interval_edges(data: &[Float], lower: Float, upper: Float, enforce_constant_time: bool) -> Vec<Float> {
+    let mut sorted = data.iter()
+        .map(|v| n64(v.max(lower).min(upper)))
+        .collect::<Vec<N64>>();
+    if enforce_constant_time {
+        utilities::sort_constant_time(&mut sorted)
+    } else {
+        sorted.sort()
+    }
+
+    let mut edges = vec![lower];
+    edges.extend(sorted.into_iter().map(|v| v.raw()));
+    edges.push(upper);
+    edges
+}
+
+/// Compute `log(exp(a) + exp(b) + ...)` without overflow.
+fn log_sum_exp(values: impl Iterator<Item=f64>) -> f64 {
+    let values = values.collect::<Vec<f64>>();
+    let max = values.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+    if max == f64::NEG_INFINITY { return max }
+    max + values.iter().map(|v| (v - max).exp()).sum::<f64>().ln()
+}
+
+/// Sample an index with probability proportional to the exponential of its log weight.
+fn sample_log_weights(log_weights: &[f64], enforce_constant_time: bool) -> Result<usize> {
+    let max = log_weights.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+    if max == f64::NEG_INFINITY {
+        return Err("all weights are zero".into())
+    }
+    let weights = log_weights.iter().map(|v| (v - max).exp()).collect::<Vec<f64>>();
+    let indices = (0..weights.len()).collect::<Vec<usize>>();
+    utilities::sample_from_set(&indices, &weights, enforce_constant_time)
+}
+
+/// For each `k`, compute `log(sum_{d=1}^k exp(values[k - d] - scale * |d - target|))` in linear time.
+///
+/// Gaps at least as large as the target are accumulated by a recurrence,
+/// and smaller gaps are summed over a sliding window of log weights.
+fn log_gap_convolution(values: &[f64], scale: f64, target: f64) -> Vec<f64> {
+    let num_values = values.len();
+    // smallest gap that is at least as large as the target
+    let threshold = (target.ceil() as usize).max(1);
+
+    // gaps of at least the threshold: each step lengthens every gap by one
+    let mut above = vec![f64::NEG_INFINITY; num_values];
+    (threshold..num_values).for_each(|k| above[k] = log_sum_exp(vec![
+        above[k - 1] - scale,
+        values[k - threshold] - scale * (threshold as f64 - target)
+    ].into_iter()));
+
+    // gaps smaller than the threshold: sum over a window of length threshold - 1, in blocks of that length
+    let width = threshold - 1;
+    let mut below = vec![f64::NEG_INFINITY; num_values];
+    if width > 0 {
+        let shifted = values.iter().enumerate()
+            .map(|(k, v)| v - scale * k as f64)
+            .collect::<Vec<f64>>();
+        let mut prefix = shifted.clone();
+        let mut suffix = shifted.clone();
+        (1..num_values).filter(|k| k % width != 0)
+            .for_each(|k| prefix[k] = log_sum_exp(vec![prefix[k - 1], shifted[k]].into_iter()));
+        (0..num_values.saturating_sub(1)).rev().filter(|k| (k + 1) % width != 0)
+            .for_each(|k| suffix[k] = log_sum_exp(vec![suffix[k + 1], shifted[k]].into_iter()));
+
+        (1..num_values).for_each(|k| {
+            let (first, last) = (k.saturating_sub(width), k - 1);
+            let window = if first / width == last / width { prefix[last] } else {
+                log_sum_exp(vec![suffix[first], prefix[last]].into_iter())
+            };
+            below[k] = window + scale * (k as f64 - target);
+        });
+    }
+
+    above.into_iter().zip(below.into_iter())
+        .map(|(above, below)| log_sum_exp(vec![above, below].into_iter()))
+        .collect()
+}
+/// Returns an element from a finite set with the largest utility, after perturbing each utility with noise
+///
+/// Each utility is perturbed with Laplace or Gumbel noise of scale 2 * sensitivity / epsilon.
+/// Report Noisy Max with Gumbel noise is equivalent to the Exponential mechanism.
+/// For more information, see
+/// C. Dwork, A. Roth The Algorithmic Foundations of Differential Privacy, Chapter 3.3 Report Noisy Max p.35-36. August 2014.
+///
+/// # Arguments
+/// * `epsilon` - Multiplicative privacy loss parameter.
+/// * `sensitivity` - L1 sensitivity of the utility function.
+/// * `candidate_set` - Data from which user wants an element returned.
+/// * `utilities` - Utility score for each element in `candidate_set`.
+/// * `distribution` - Distribution of the noise added to each utility. One of [`Laplace`, `Gumbel`].
+/// * `enforce_constant_time` - Whether or not to enforce the algorithm to run in constant time
+///
+/// # Returns
+/// Element from the candidate set with the largest noisy utility.
+///
+/// # Example
+/// ```
+/// use whitenoise_runtime::utilities::mechanisms::report_noisy_max_mechanism;
+/// let xs: Vec<f64> = vec![1., 2., 3., 4., 5.];
+/// let utilities: Vec<f64> = xs.clone();
+/// let ans = report_noisy_max_mechanism(1.0, 1.0, &xs, utilities, "laplace", false);
+/// # ans.unwrap();
+/// ```
+pub fn report_noisy_max_mechanism<T>(
+    epsilon: f64,
+    sensitivity: f64,
+    candidate_set: &[T],
+    utilities: Vec<f64>,
+    distribution: &str,
+    enforce_constant_time: bool
+) -> Result<T> where T: Clone {
+    if epsilon <= 0. || sensitivity < 0. {
+        return Err(format!("epsilon ({}) must be positive and sensitivity ({}) must be non-negative", epsilon, sensitivity).into());
+    }
+    let lowercase_distribution = distribution.to_lowercase();
+    if !["laplace", "gumbel"].contains(&lowercase_distribution.as_str()) {
+        return Err(format!("distribution must be one of [Laplace, Gumbel], got {}", distribution).into())
+    }
+    let scale = 2. * sensitivity / epsilon;
+
+    let mut best: Option<(usize, f64)> = None;
+    for (index, utility) in utilities.into_iter().enumerate() {
+        let noisy_utility = utility + match lowercase_distribution.as_str() {
+            "laplace" => noise::sample_laplace(0., scale, enforce_constant_time)?,
+            _ => noise::sample_gumbel(0., scale)
+        };
+        if best.map(|(_, best_utility)| noisy_utility > best_utility).unwrap_or(true) {
+            best = Some((index, noisy_utility))
+        }
+    }
+
+    let (index, _) = best.ok_or_else(|| Error::from("candidate set must be non-empty"))?;
+    Ok(candidate_set[index].clone())
+}
+
+/// Returns an element from a finite set via the Permute-and-Flip mechanism
+///
+/// Candidates are visited in a random order, and each is accepted with probability
+/// exp(epsilon * (utility - max_utility) / (2 * sensitivity)).
+/// The expected error is never worse than that of the Exponential mechanism.
+/// For more information, see [McKenna, Sheldon (2020)](https://arxiv.org/abs/2010.12603).
+///
+/// # Arguments
+/// * `epsilon` - Multiplicative privacy loss parameter.
+/// * `sensitivity` - L1 sensitivity of the utility function.
+/// * `candidate_set` - Data from which user wants an element returned.
+/// * `utilities` - Utility score for each element in `candidate_set`.
+/// * `enforce_constant_time` - Whether or not to enforce the algorithm to run in constant time
+///
+/// # Returns
+/// Element from the candidate set selected via the Permute-and-Flip mechanism.
+///
+/// # Example
+/// ```
+/// use whitenoise_runtime::utilities::mechanisms::permute_and_flip_mechanism;
+/// let xs: Vec<f64> = vec![1., 2., 3., 4., 5.];
+/// let utilities: Vec<f64> = xs.clone();
+/// let ans = permute_and_flip_mechanism(1.0, 1.0, &xs, utilities, false);
+/// # ans.unwrap();
+/// ```
+pub fn permute_and_flip_mechanism<T>(
+    epsilon: f64,
+    sensitivity: f64,
+    candidate_set: &[T],
+    utilities: Vec<f64>,
+    enforce_constant_time: bool
+) -> Result<T> where T: Clone {
+    if epsilon <= 0. || sensitivity < 0. {
+        return Err(format!("epsilon ({}) must be positive and sensitivity ({}) must be non-negative", epsilon, sensitivity).into());
+    }
+    let max_utility = utilities.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+
+    // the candidate with the largest utility is always accepted, so the loop terminates
+    let order = noise::shuffle((0..utilities.len()).collect(), enforce_constant_time)?;
+    for index in order {
+        let probability = if utilities[index] == max_utility { 1. } else {
+            (epsilon * (utilities[index] - max_utility) / (2. * sensitivity)).exp()
+        };
+        if noise::sample_bit_prob(probability, enforce_constant_time)? {
+            return Ok(candidate_set[index].clone())
+        }
+    }
+    Err("candidate set must be non-empty".into())
+}
+
+/// Returns the indices of queries found to be above a threshold, via the Sparse Vector Technique
+///
+/// The threshold is perturbed once with Laplace noise of scale 2 * sensitivity / epsilon,
+/// and each query is perturbed with Laplace noise of scale 4 * cutoff * sensitivity / epsilon.
+/// Evaluation halts once `cutoff` queries have been found above the threshold.
+/// The privacy usage does not depend on the number of queries found below the threshold.
+/// For more information, see Algorithm 1 in
+/// [Lyu, Su, Li (2017)](https://arxiv.org/abs/1603.01699).
+///
+/// # Arguments
+/// * `epsilon` - Multiplicative privacy loss parameter.
+/// * `sensitivity` - Upper bound on the sensitivity of each query.
+/// * `queries` - Non-private answers to each query.
+/// * `threshold` - Public threshold that each query is compared against.
+/// * `cutoff` - Maximum number of above-threshold queries to report.
+/// * `enforce_constant_time` - Whether or not to enforce the algorithm to run in constant time
+///
+/// # Return
+/// Indices of the queries found to be above the threshold, in order.
+///
+/// # Example
+/// ```
+/// use whitenoise_runtime::utilities::mechanisms::sparse_vector_mechanism;
+/// let queries: Vec<f64> = vec![1., 200., 3., 400.];
+/// let indices = sparse_vector_mechanism(1.0, 1.0, &queries, 100., 2, false).unwrap();
+/// assert!(indices.len() <= 2);
+/// ```
+pub fn sparse_vector_mechanism(
+    epsilon: f64,
+    sensitivity: f64,
+    queries: &[f64],
+    threshold: f64,
+    cutoff: u32,
+    enforce_constant_time: bool,
+) -> Result<Vec<usize>> {
+    if epsilon <= 0. || sensitivity < 0. {
+        return Err(format!("epsilon ({}) must be positive and sensitivity ({}) must be non-negative", epsilon, sensitivity).into());
+    }
+    if cutoff == 0 {
+        return Err("cutoff must be positive".into());
+    }
+
+    // half of the budget is spent on the threshold, and half is spread among the above-threshold queries
+    let noisy_threshold = threshold + noise::sample_laplace(0., 2. * sensitivity / epsilon, enforce_constant_time)?;
+    let query_scale = 4. * cutoff as f64 * sensitivity / epsilon;
+
+    let mut indices = Vec::new();
+    for (index, query) in queries.iter().enumerate() {
+        if query + noise::sample_laplace(0., query_scale, enforce_constant_time)? >= noisy_threshold {
+            indices.push(index);
+            if indices.len() == cutoff as usize {
+                break
+            }
+        }
+    }
+    Ok(indices)
+}
+
+
+#[cfg(test)]
+mod test_mechanisms {
+    use crate::utilities::mechanisms::{continuous_exponential_mechanism, joint_exponential_mechanism, permute_and_flip_mechanism, report_noisy_max_mechanism, sparse_vector_mechanism};
+
+    // the utility of the third candidate exceeds the others by 100 sensitivities,
+    //     so any other candidate is selected with probability on the order of e^-50
+    fn dominant_utilities() -> (Vec<usize>, Vec<f64>) {
+        ((0..5).collect(), vec![0., 0., 100., 0., 0.])
+    }
+
+    #[test]
+    fn test_report_noisy_max() {
+        let (candidates, utilities) = dominant_utilities();
+        ["Laplace", "Gumbel"].iter().for_each(|distribution| (0..100).for_each(|_| assert_eq!(
+            report_noisy_max_mechanism(1., 1., &candidates, utilities.clone(), distribution, false).unwrap(), 2)));
+
+        assert!(report_noisy_max_mechanism(1., 1., &candidates, utilities, "Gaussian", false).is_err());
+        assert!(report_noisy_max_mechanism::<usize>(1., 1., &[], vec![], "Laplace", false).is_err());
+    }
+
+    #[test]
+    fn test_permute_and_flip() {
+        let (candidates, utilities) = dominant_utilities();
+        (0..100).for_each(|_| assert_eq!(
+            permute_and_flip_mechanism(1., 1., &candidates, utilities.clone(), false).unwrap(), 2));
+
+        assert!(permute_and_flip_mechanism::<usize>(1., 1., &[], vec![], false).is_err());
+    }
+
+    #[test]
+    fn test_continuous_exponential() {
+        let data = (0..=100).rev().map(|v| v as f64).collect::<Vec<f64>>();
+
+        // intervals away from the median have utility at least one less, so are sampled with probability on the order of e^-50
+        (0..100).for_each(|_| {
+            let median = continuous_exponential_mechanism(100., 1., 0.5, &data, 0., 100., false).unwrap();
+            assert!(49. <= median && median <= 51.);
+        });
+
+        // the release is always within the bounds, regardless of the accuracy
+        (0..100).for_each(|_| {
+            let median = continuous_exponential_mechanism(0.01, 1., 0.5, &data, 0., 100., false).unwrap();
+            assert!(0. <= median && median <= 100.);
+        });
+    }
+
+    #[test]
+    fn test_joint_exponential() {
+        let data = (0..=100).rev().map(|v| v as f64).collect::<Vec<f64>>();
+        let alphas = [0.1, 0.5, 0.5, 0.9];
+
+        // the release is sorted and within the bounds, even when the quantiles are inaccurate
+        (0..100).for_each(|_| {
+            let quantiles = joint_exponential_mechanism(0.01, 1., &alphas, &data, 0., 100., false).unwrap();
+            assert!(quantiles.windows(2).all(|pair| pair[0] <= pair[1]));
+            assert!(quantiles.iter().all(|quantile| 0. <= *quantile && *quantile <= 100.));
+        });
+
+        (0..20).for_each(|_| {
+            let quantiles = joint_exponential_mechanism(100., 1., &alphas, &data, 0., 100., false).unwrap();
+            quantiles.iter().zip(&[10., 50., 50., 90.])
+                .for_each(|(quantile, expected)| assert!((quantile - expected).abs() <= 2.));
+        });
+    }
+
+    #[test]
+    fn test_sparse_vector_cutoff() {
+        // every query is far above the threshold, so the first `cutoff` queries are reported
+        let queries = vec![1e6; 10];
+        let indices = sparse_vector_mechanism(1., 1., &queries, 0., 3, false).unwrap();
+        assert_eq!(indices, vec![0, 1, 2]);
+
+        // queries after the cutoff are never reported, even when the cutoff is not reached before them
+        let queries = vec![-1e6, 1e6, -1e6, 1e6, 1e6];
+        let indices = sparse_vector_mechanism(1., 1., &queries, 0., 2, false).unwrap();
+        assert_eq!(indices, vec![1, 3]);
+    }
+}
@@ -1,13 +1,18 @@
 use std::cmp::Ordering;
 
 use whitenoise_validator::errors::*;
+use whitenoise_validator::Float;
 use ieee754::Ieee754;
 use ndarray::{ArrayD, Axis, Zip};
 use ndarray::prelude::IxDyn;
 use openssl::rand::rand_bytes;
 
+use whitenoise_validator::base::IndexKey;
+use whitenoise_validator::proto;
 use whitenoise_validator::utilities::array::{slow_select, slow_stack};
 
+use crate::NodeArguments;
+
 pub mod mechanisms;
 pub mod noise;
 
@@ -26,6 +31,284 @@ pub fn get_num_columns<T>(data: &ArrayD<T>) -> Result<i64> {
     }
 }
 
+/// Retrieve the public number of records that buffers are sized from when protecting memory utilization.
+///
+/// The validator expands components that allocate with a `number_records` argument,
+/// taken from the static properties of the data.
+///
+/// # Arguments
+/// * `privacy_definition` - Definition of privacy, containing whether memory utilization is protected.
+/// * `arguments` - Arguments to the component, from which `number_records` is removed.
+///
+/// # Return
+/// The public number of records, or None if memory utilization is not protected.
+pub fn take_number_records(
+    privacy_definition: &Option<proto::PrivacyDefinition>, arguments: &mut NodeArguments
+) -> Result<Option<usize>> {
+    let number_records = arguments.remove::<IndexKey>(&"number_records".into());
+    if !privacy_definition.as_ref().map(|v| v.protect_memory_utilization).unwrap_or(false) {
+        return Ok(None)
+    }
+
+    let number_records = number_records
+        .ok_or_else(|| Error::from("number_records: must be known to protect memory utilization"))?
+        .array()?.first_int()?;
+    if number_records < 0 {
+        return Err("number_records: must be non-negative".into())
+    }
+    Ok(Some(number_records as usize))
+}
+
+/// Select rows of the data, into a buffer with capacity for the public number of records.
+///
+/// The memory allocated depends only on the public number of records and the shape of a row,
+/// and not on how many rows are selected.
+///
+/// # Arguments
+/// * `data` - Data to select rows from. Must have at least one dimension.
+/// * `indices` - Indices of the rows to select.
+/// * `number_records` - Public number of records in the data.
+///
+/// # Return
+/// The selected rows.
+///
+/// # Example
+/// ```
+/// use ndarray::arr2;
+/// use whitenoise_runtime::utilities::select_rows_with_full_capacity;
+/// let data = arr2(&[ [1, 2], [3, 4], [5, 6] ]).into_dyn();
+/// let selected = select_rows_with_full_capacity(&data, &[0, 2], 3).unwrap();
+/// assert_eq!(selected, arr2(&[ [1, 2], [5, 6] ]).into_dyn());
+/// ```
+pub fn select_rows_with_full_capacity<T: Clone>(
+    data: &ArrayD<T>, indices: &[usize], number_records: usize
+) -> Result<ArrayD<T>> {
+    if data.ndim() == 0 {
+        return Err("data must have at least one dimension".into())
+    }
+    if data.len_of(Axis(0)) > number_records {
+        return Err("data: must not have more records than the public number of records".into())
+    }
+    let row_length = data.shape()[1..].iter().product::<usize>();
+
+    let mut buffer = Vec::with_capacity(number_records * row_length);
+    indices.iter().for_each(|index| buffer.extend(data.index_axis(Axis(0), *index).iter().cloned()));
+
+    let mut shape = data.shape().to_vec();
+    shape[0] = indices.len();
+    Ok(ArrayD::from_shape_vec(shape, buffer)?)
+}
+
+#[cfg(test)]
+mod test_select_rows_with_full_capacity {
+    use ndarray::arr2;
+
+    use whitenoise_validator::proto;
+
+    use crate::NodeArguments;
+    use crate::utilities::{select_rows_with_full_capacity, take_number_records};
+
+    #[test]
+    fn test_capacity() {
+        let data = arr2(&[ [1, 2], [3, 4], [5, 6] ]).into_dyn();
+        let selected = select_rows_with_full_capacity(&data, &[1], 3).unwrap();
+        assert_eq!(selected, arr2(&[ [3, 4] ]).into_dyn());
+
+        // the buffer has space for every public record
+        assert_eq!(selected.into_raw_vec().capacity(), 6);
+
+        // the data may not hold more records than are public
+        assert!(select_rows_with_full_capacity(&data, &[1], 2).is_err());
+    }
+
+    #[test]
+    fn test_unknown_number_records() {
+        let privacy_definition = Some(proto::PrivacyDefinition {
+            protect_memory_utilization: true,
+            ..Default::default()
+        });
+        assert!(take_number_records(&privacy_definition, &mut NodeArguments::new()).is_err());
+        assert_eq!(take_number_records(&None, &mut NodeArguments::new()).unwrap(), None);
+    }
+}
+
+/// Select the rows of the data where the mask is true, in time that does not depend on the mask.
+///
+/// Every row is copied into the output buffer, at the position after the last kept row.
+/// Rows that are not kept are overwritten by the following row, or truncated at the end.
+/// The buffer has capacity for every row of the data.
+///
+/// # Arguments
+/// * `data` - Data to select rows from. Must have at least one dimension.
+/// * `mask` - Whether or not each row should be kept.
+///
+/// # Return
+/// The selected rows.
+///
+/// # Example
+/// ```
+/// use ndarray::arr2;
+/// use whitenoise_runtime::utilities::select_rows_constant_time;
+/// let data = arr2(&[ [1, 2], [3, 4], [5, 6] ]).into_dyn();
+/// let selected = select_rows_constant_time(&data, &[true, false, true]).unwrap();
+/// assert_eq!(selected, arr2(&[ [1, 2], [5, 6] ]).into_dyn());
+/// ```
+pub fn select_rows_constant_time<T: Clone + Default>(data: &ArrayD<T>, mask: &[bool]) -> Result<ArrayD<T>> {
+    if data.ndim() == 0 {
+        return Err("data must have at least one dimension".into())
+    }
+    let num_rows = data.len_of(Axis(0));
+    if num_rows != mask.len() {
+        return Err("mask must have the same number of rows as data".into())
+    }
+    let row_length = data.len() / num_rows.max(1);
+
+    let mut buffer = vec![T::default(); data.len()];
+    let mut position = 0;
+    data.outer_iter().zip(mask.iter()).for_each(|(row, keep)| {
+        buffer[position * row_length..(position + 1) * row_length].iter_mut()
+            .zip(row.iter()).for_each(|(cell, v)| *cell = v.clone());
+        position += *keep as usize;
+    });
+    buffer.truncate(position * row_length);
+
+    let mut shape = data.shape().to_vec();
+    shape[0] = position;
+    Ok(ArrayD::from_shape_vec(shape, buffer)?)
+}
+
+/// Sort a slice with a bitonic sorting network.
+///
+/// The sequence of comparisons and writes depends only on the length of the slice,
+/// so the time taken does not depend on the values being sorted.
+///
+/// # Arguments
+/// * `data` - Slice to be sorted in place.
+///
+/// # Example
+/// ```
+/// use whitenoise_runtime::utilities::sort_constant_time;
+/// let mut data = vec![3, 1, 4, 1, 5, 9, 2];
+/// sort_constant_time(&mut data);
+/// assert_eq!(data, vec![1, 1, 2, 3, 4, 5, 9]);
+/// ```
+pub fn sort_constant_time<T: Ord + Copy>(data: &mut [T]) {
+    bitonic_sort(data, true)
+}
+
+fn bitonic_sort<T: Ord + Copy>(data: &mut [T], ascending: bool) {
+    if data.len() < 2 { return }
+    let (left, right) = data.split_at_mut(data.len() / 2);
+    bitonic_sort(left, !ascending);
+    bitonic_sort(right, ascending);
+    bitonic_merge(data, ascending);
+}
+
+fn bitonic_merge<T: Ord + Copy>(data: &mut [T], ascending: bool) {
+    if data.len() < 2 { return }
+    // greatest power of two less than the length
+    let distance = data.len().next_power_of_two() / 2;
+    (0..data.len() - distance).for_each(|i| {
+        let (left, right) = (data[i], data[i + distance]);
+        // both cells are always written, whether or not they are swapped
+        let swap = (left > right) == ascending;
+        data[i] = if swap { right } else { left };
+        data[i + distance] = if swap { left } else { right };
+    });
+    let (left, right) = data.split_at_mut(distance);
+    bitonic_merge(left, ascending);
+    bitonic_merge(right, ascending);
+}
+
+#[cfg(test)]
+pub mod test_constant_time {
+    use std::time::{Duration, Instant};
+
+    use ndarray::arr2;
+
+    use crate::utilities::{select_rows_constant_time, sort_constant_time};
+    use crate::utilities::noise::sample_uniform;
+
+    const NUM_TRIALS: usize = 20;
+    const TOLERANCE: f64 = 1.5;
+
+    /// Assert that `evaluate` takes a similar amount of time on each of the `datasets`.
+    ///
+    /// The fastest of many interleaved trials is compared, to be robust to scheduling noise.
+    /// Wall-clock measurements remain sensitive to the load on the machine,
+    /// so the timing tests are ignored by default, and are run with `cargo test -- --ignored`.
+    pub fn assert_data_independent_time<T: Clone>(datasets: &[T], evaluate: impl Fn(T)) {
+        let mut fastest = vec![Duration::from_secs(u64::MAX); datasets.len()];
+        (0..NUM_TRIALS).for_each(|_| datasets.iter().zip(fastest.iter_mut())
+            .for_each(|(data, fastest)| {
+                let data = data.clone();
+                let start = Instant::now();
+                evaluate(data);
+                *fastest = start.elapsed().min(*fastest);
+            }));
+
+        let slowest = fastest.iter().max().unwrap().as_secs_f64();
+        let quickest = fastest.iter().min().unwrap().as_secs_f64();
+        assert!(slowest <= quickest * TOLERANCE,
+                "elapsed time depends on the data: {:?}", fastest);
+    }
+
+    #[test]
+    fn test_select_rows() {
+        let data = arr2(&[ [1, 2], [3, 4], [5, 6] ]).into_dyn();
+        assert_eq!(
+            select_rows_constant_time(&data, &[false, true, true]).unwrap(),
+            arr2(&[ [3, 4], [5, 6] ]).into_dyn());
+        assert_eq!(
+            select_rows_constant_time(&data, &[false, false, false]).unwrap().shape(),
+            &[0, 2]);
+        assert!(select_rows_constant_time(&data, &[true]).is_err());
+    }
+
+    #[test]
+    fn test_sort() {
+        (0..50).for_each(|length| {
+            let data = (0..length)
+                .map(|_| (sample_uniform(0., 10., false).unwrap() as i64))
+                .collect::<Vec<i64>>();
+            let mut expected = data.clone();
+            expected.sort_unstable();
+            let mut sorted = data;
+            sort_constant_time(&mut sorted);
+            assert_eq!(sorted, expected);
+        })
+    }
+
+    #[test]
+    #[ignore]
+    fn test_sort_timing() {
+        let ascending = (0..10_000).collect::<Vec<i64>>();
+        let descending = ascending.iter().rev().copied().collect::<Vec<i64>>();
+        let constant = vec![0; 10_000];
+        assert_data_independent_time(
+            &[ascending, descending, constant],
+            |mut data| sort_constant_time(&mut data));
+    }
+}
+
+/// Check that no value in an aggregate has overflowed the range of floats.
+///
+/// Floats that overflow become infinite, and stay non-finite through subsequent arithmetic,
+/// so checking the aggregate detects an overflow at any intermediate step.
+///
+/// # Arguments
+/// * `data` - The aggregated data.
+///
+/// # Return
+/// The aggregated data, if no value overflowed.
+pub fn check_float_overflow(data: ArrayD<Float>) -> Result<ArrayD<Float>> {
+    if data.iter().all(|v| v.is_finite()) {
+        Ok(data)
+    } else {
+        Err("aggregation overflowed the range of floats".into())
+    }
+}
+
 
 /// Broadcast left and right to match each other, and map an operator over the pairs.
 ///
@@ -2,6 +2,8 @@ use std::{cmp, f64::consts, mem};
 
 use ieee754::Ieee754;
 use noisy_float::types::n64;
+use num::{BigInt, BigRational, Integer as _, One, Signed, ToPrimitive, Zero};
+use num::bigint::Sign;
 use probability::distribution::{Inverse, Laplace};
 #[cfg(not(feature="use-mpfr"))]
 use probability::prelude::Gaussian;
@@ -562,6 +564,231 @@ pub fn sample_gaussian_truncated(
     }
 }
 
+/// Sample from the discrete Gaussian distribution centered at shift, with scale parameter sigma.
+///
+/// The sampler is exact: the only floating-point operation is the exact conversion of sigma^2 to a rational,
+/// and all subsequent arithmetic is carried out on integers and rationals, with randomness drawn from unbiased bits.
+/// For more information, see Algorithm 3 in
+/// [Canonne, Kamath, Steinke (2020)](https://arxiv.org/abs/2004.00010).
+///
+/// # Arguments
+/// * `shift` - The expectation of the discrete Gaussian distribution.
+/// * `scale` - The scale parameter (sigma) of the discrete Gaussian distribution.
+/// * `enforce_constant_time` - Not supported. The running time of the sampler depends on the sampled value.
+///
+/// # Return
+/// A draw from the discrete Gaussian distribution, N_Z(shift, scale^2).
+///
+/// # Example
+/// ```
+/// use whitenoise_runtime::utilities::noise::sample_discrete_gaussian;
+/// let n = sample_discrete_gaussian(0, 2.0, false);
+/// # n.unwrap();
+/// ```
+pub fn sample_discrete_gaussian(shift: Integer, scale: f64, _enforce_constant_time: bool) -> Result<Integer> {
+    if !scale.is_finite() || scale <= 0. {
+        return Err("scale must be positive and finite".into())
+    }
+    let sigma_sq = BigRational::from_float(scale * scale)
+        .ok_or_else(|| Error::from("scale must be finite"))?;
+
+    // any positive integer t is a valid scale for the discrete laplace proposal, floor(sigma) + 1 is efficient
+    let t = BigRational::from_integer(BigInt::from(scale.floor() as Integer + 1));
+
+    loop {
+        let candidate = sample_discrete_laplace(&t)?;
+
+        // accept the candidate with probability exp(-(|candidate| - sigma^2 / t)^2 / (2 sigma^2))
+        let gamma = (BigRational::from_integer(BigInt::from(candidate.abs())) - &sigma_sq / &t).pow(2)
+            / (BigRational::from_integer(BigInt::from(2)) * &sigma_sq);
+
+        if sample_bernoulli_exp(&gamma)? {
+            return Ok(shift + candidate)
+        }
+    }
+}
+
+/// Sample from the discrete Laplace distribution centered at zero, with rational scale parameter.
+///
+/// The probability of sampling x is proportional to exp(-|x| / scale).
+/// The sampler only uses unbiased bits and integer arithmetic.
+/// See Algorithm 2 in [Canonne, Kamath, Steinke (2020)](https://arxiv.org/abs/2004.00010).
+///
+/// # Arguments
+/// * `scale` - The scale parameter of the discrete Laplace distribution. Must be positive.
+///
+/// # Return
+/// A draw from the discrete Laplace distribution.
+///
+/// # Example
+/// ```
+/// use num::BigRational;
+/// use whitenoise_runtime::utilities::noise::sample_discrete_laplace;
+/// let n = sample_discrete_laplace(&BigRational::from_float(2.5).unwrap());
+/// # n.unwrap();
+/// ```
+pub fn sample_discrete_laplace(scale: &BigRational) -> Result<Integer> {
+    if !scale.is_positive() {
+        return Err("scale must be positive".into())
+    }
+    let (numer, denom) = (scale.numer(), scale.denom());
+
+    loop {
+        // the remainder of the magnitude modulo the numerator of the scale
+        let remainder = sample_uniform_bigint(numer)?;
+        if !sample_bernoulli_exp(&BigRational::new(remainder.clone(), numer.clone()))? {
+            continue
+        }
+
+        // the quotient of the magnitude divided by the numerator of the scale is geometric
+        let mut quotient = BigInt::zero();
+        while sample_bernoulli_exp(&BigRational::one())? {
+            quotient += 1;
+        }
+
+        let magnitude = (remainder + numer * quotient).div_floor(denom);
+        let negative = sample_bit()?;
+
+        // reject negative zero, so that zero is not sampled twice as often
+        if negative && magnitude.is_zero() {
+            continue
+        }
+        let magnitude = magnitude.to_i64()
+            .ok_or_else(|| Error::from("discrete laplace sample overflowed"))?;
+        return Ok(if negative { -magnitude } else { magnitude })
+    }
+}
+
+/// Sample a bit with probability exp(-gamma), for a non-negative rational gamma.
+///
+/// The sampler only uses unbiased bits and integer arithmetic.
+/// See Algorithm 1 in [Canonne, Kamath, Steinke (2020)](https://arxiv.org/abs/2004.00010).
+///
+/// # Arguments
+/// * `gamma` - Non-negative rational.
+///
+/// # Return
+/// A bit that is true with probability exp(-gamma).
+///
+/// # Example
+/// ```
+/// use num::BigRational;
+/// use whitenoise_runtime::utilities::noise::sample_bernoulli_exp;
+/// let n = sample_bernoulli_exp(&BigRational::from_float(0.5).unwrap());
+/// # n.unwrap();
+/// ```
+pub fn sample_bernoulli_exp(gamma: &BigRational) -> Result<bool> {
+    if gamma.is_negative() {
+        return Err("gamma must be non-negative".into())
+    }
+
+    // exp(-gamma) = exp(-1)^floor(gamma) * exp(-(gamma - floor(gamma)))
+    let mut gamma = gamma.clone();
+    while gamma > BigRational::one() {
+        if !sample_bernoulli_exp1(&BigRational::one())? {
+            return Ok(false)
+        }
+        gamma -= BigRational::one();
+    }
+    sample_bernoulli_exp1(&gamma)
+}
+
+/// Sample a bit with probability exp(-gamma), for a rational gamma in [0, 1].
+fn sample_bernoulli_exp1(gamma: &BigRational) -> Result<bool> {
+    let mut k = BigInt::one();
+    loop {
+        if !sample_bernoulli_rational(&(gamma / BigRational::from_integer(k.clone())))? {
+            // the number of successes before the first failure is odd with probability exp(-gamma)
+            return Ok(k.is_odd())
+        }
+        k += 1;
+    }
+}
+
+/// Sample a bit with probability prob, for a rational prob in [0, 1].
+///
+/// The bit is exact, as it is computed by comparing an unbiased uniform integer against the numerator.
+///
+/// # Arguments
+/// * `prob` - Rational probability that the bit is true.
+///
+/// # Return
+/// A bit that is true with probability prob.
+pub fn sample_bernoulli_rational(prob: &BigRational) -> Result<bool> {
+    if prob.is_negative() || prob > &BigRational::one() {
+        return Err("probability is not within [0, 1]".into())
+    }
+    Ok(&sample_uniform_bigint(prob.denom())? < prob.numer())
+}
+
+/// Sample a uniform integer from {0, 1, ..., upper - 1}, by rejection sampling on unbiased bits.
+fn sample_uniform_bigint(upper: &BigInt) -> Result<BigInt> {
+    if !upper.is_positive() {
+        return Err("upper must be positive".into())
+    }
+    let num_bits = (upper - BigInt::one()).bits();
+    let num_bytes = num_bits.div_ceil(8) as usize;
+    let mask = 0xFF_u8.checked_shr((8 * num_bytes as u64 - num_bits) as u32).unwrap_or(0);
+
+    let mut buffer = vec![0u8; num_bytes];
+    loop {
+        utilities::fill_bytes(&mut buffer)?;
+        // discard the excess high bits, so that each draw succeeds with probability at least 1/2
+        if let Some(high) = buffer.last_mut() { *high &= mask }
+        let sample = BigInt::from_bytes_le(Sign::Plus, &buffer);
+        if &sample < upper {
+            return Ok(sample)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_sample_discrete_gaussian {
+    use num::BigRational;
+
+    use crate::utilities::noise::{sample_bernoulli_exp, sample_discrete_gaussian, sample_discrete_laplace};
+
+    #[test]
+    fn test_moments() {
+        let samples = (0..2_000)
+            .map(|_| sample_discrete_gaussian(0, 3., false).unwrap() as f64)
+            .collect::<Vec<f64>>();
+
+        let mean = samples.iter().sum::<f64>() / samples.len() as f64;
+        let variance = samples.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / samples.len() as f64;
+
+        assert!(mean.abs() < 0.5);
+        assert!((variance - 9.).abs() < 1.5);
+    }
+
+    #[test]
+    fn test_bernoulli_exp() {
+        let gamma = BigRational::new(3.into(), 2.into());
+        let n_samples = 5_000;
+        let n_true = (0..n_samples)
+            .filter(|_| sample_bernoulli_exp(&gamma).unwrap())
+            .count();
+
+        assert!((n_true as f64 / n_samples as f64 - (-1.5f64).exp()).abs() < 0.03);
+    }
+
+    #[test]
+    fn test_discrete_laplace() {
+        let scale = BigRational::new(5.into(), 2.into());
+        let samples = (0..2_000)
+            .map(|_| sample_discrete_laplace(&scale).unwrap() as f64)
+            .collect::<Vec<f64>>();
+
+        let mean = samples.iter().sum::<f64>() / samples.len() as f64;
+        let variance = samples.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / samples.len() as f64;
+
+        // the variance of the discrete laplace is 2 alpha / (1 - alpha)^2, where alpha = exp(-1 / scale)
+        let alpha = (-0.4f64).exp();
+        assert!(mean.abs() < 0.5);
+        assert!((variance - 2. * alpha / (1. - alpha).powi(2)).abs() < 2.);
+    }
+}
+
 /// Sample from the censored geometric distribution with parameter "prob" and maximum
 /// number of trials "max_trials".
 ///
@@ -615,10 +842,13 @@ pub fn sample_geometric_censored(prob: f64, max_trials: i64, enforce_constant_ti
 
 /// Sample noise according to geometric mechanism
 ///
-/// This function uses coin flips to sample from the geometric distribution,
+/// When constant time is not enforced, the noise is drawn from the exact discrete Laplace sampler,
+/// which only uses unbiased bits and integer arithmetic. The float scale is converted to a rational exactly,
+/// so no probabilities are approximated. The magnitude of the noise is then censored at (max - min).
+///
+/// When constant time is enforced, this function uses coin flips to sample from the geometric distribution,
 /// rather than using the inverse probability transform. This is done
 /// to avoid finite precision attacks.
-///
 /// For this algorithm, the number of steps it takes to sample from the geometric
 /// is bounded above by (max - min).
 ///
@@ -641,9 +871,17 @@ pub fn sample_simple_geometric_mechanism(
     scale: f64, min: i64, max: i64, enforce_constant_time: bool
 ) -> Result<i64> {
 
-    let alpha: f64 = consts::E.powf(-1. / scale);
     let max_trials: i64 = max - min;
 
+    if !enforce_constant_time {
+        let scale = BigRational::from_float(scale)
+            .ok_or_else(|| Error::from("scale must be finite"))?;
+        let noise = sample_discrete_laplace(&scale)?;
+        return Ok(noise.signum() * cmp::min(noise.abs(), max_trials))
+    }
+
+    let alpha: f64 = consts::E.powf(-1. / scale);
+
     // return 0 noise with probability (1-alpha) / (1+alpha), otherwise sample from geometric
     let unif: f64 = sample_uniform(0., 1., enforce_constant_time)?;
     Ok(if unif < (1. - alpha) / (1. + alpha) {
@@ -749,7 +987,22 @@ pub fn sample_gumbel(loc: f64, scale: f64) -> f64 {
 
 /// Shuffle a vector
 ///
+/// When enforcing constant time, the random keys are sorted with a sorting network,
+/// so that the time taken does not depend on the permutation.
 pub fn shuffle<T>(vector: Vec<T>, enforce_constant_time: bool) -> Result<Vec<T>> {
+    if enforce_constant_time {
+        let mut keys = (0..vector.len())
+            .map(|index| Ok((n64(sample_uniform(0., 1., enforce_constant_time)?), index)))
+            .collect::<Result<Vec<_>>>()?;
+        utilities::sort_constant_time(&mut keys);
+
+        let mut vector = vector.into_iter().map(Some).collect::<Vec<Option<T>>>();
+        return keys.into_iter()
+            .map(|(_, index)| vector[index].take()
+                .ok_or_else(|| Error::from("shuffled indices must be unique")))
+            .collect()
+    }
+
     let mut vector = vector
         .into_iter()
         .map(|v| Ok((v, n64(sample_uniform(0., 1., enforce_constant_time)?))))
@@ -757,3 +1010,29 @@ pub fn shuffle<T>(vector: Vec<T>, enforce_constant_time: bool) -> Result<Vec<T>>
     vector.sort_unstable_by_key(|v| v.1);
     Ok(vector.into_iter().map(|(v, _)| v).collect())
 }
+
+#[cfg(test)]
+mod test_shuffle {
+    use crate::utilities::noise::shuffle;
+    use crate::utilities::test_constant_time::assert_data_independent_time;
+
+    #[test]
+    fn test_permutation() {
+        let mut shuffled = shuffle((0..100).collect::<Vec<i64>>(), true).unwrap();
+        shuffled.sort_unstable();
+        assert_eq!(shuffled, (0..100).collect::<Vec<i64>>());
+    }
+
+    #[test]
+    #[ignore]
+    fn test_timing() {
+        let datasets = vec![
+            vec![0; 5_000],
+            (0..5_000).collect::<Vec<i64>>(),
+            (0..5_000).rev().collect::<Vec<i64>>()];
+
+        assert_data_independent_time(&datasets, |data| {
+            shuffle(data, true).unwrap();
+        });
+    }
+}
@@ -862,6 +862,30 @@ impl ArrayProperties {
             Err("sampled data may not be manipulated in this way".into())
         } else { Ok(())}
     }
+    /// Ensure that the number of records is known, so that buffers may be sized without inspecting the data.
+    pub fn assert_num_records_known(&self) -> Result<()> {
+        self.num_records()
+            .map_err(|_| Error::from("number of records must be known to protect memory utilization"))?;
+        Ok(())
+    }
+    /// Ensure that accumulating every record of each column cannot overflow an Integer.
+    ///
+    /// Every partial sum lies between num_records * lower and num_records * upper.
+    pub fn assert_no_int_overflow(&self) -> Result<()> {
+        let num_records = self.num_records()
+            .map_err(|_| Error::from("number of records must be known to protect against overflow"))?;
+        let overflows = self.lower_int()?.into_iter().zip(self.upper_int()?)
+            .any(|(lower, upper)| lower.checked_mul(num_records).is_none() || upper.checked_mul(num_records).is_none());
+        if overflows { Err("aggregation may overflow the range of integers".into()) } else { Ok(()) }
+    }
+    /// Ensure that accumulating `magnitude(lower, upper)` over every record of each column cannot overflow a Float.
+    pub fn assert_no_float_overflow(&self, magnitude: impl Fn(Float, Float) -> Float) -> Result<()> {
+        let num_records = self.num_records()
+            .map_err(|_| Error::from("number of records must be known to protect against overflow"))?;
+        let overflows = self.lower_float()?.into_iter().zip(self.upper_float()?)
+            .any(|(lower, upper)| !(magnitude(lower, upper) * num_records as Float).is_finite());
+        if overflows { Err("aggregation may overflow the range of floats".into()) } else { Ok(()) }
+    }
 }
 
 /// Fundamental data types for ArrayNDs and Vector2DJagged Values.
@@ -1146,52 +1170,127 @@ pub type NodeProperties = IndexMap<base::IndexKey, ValueProperties>;
 
 
 impl proto::PrivacyUsage {
-    pub(crate) fn actual_to_effective(&self, s: f64, mut c_stability: u32, group_size: u32) -> Result<Self> {
+    /// Convert the privacy usage of a release into the usage a mechanism may spend on each record of its input.
+    ///
+    /// Accounts for group privacy over `c_stability` and the group size,
+    /// and privacy amplification when the input was sampled from the dataset with probability `s`.
+    pub(crate) fn actual_to_effective(&self, s: f64, mut c_stability: u32, privacy_definition: &proto::PrivacyDefinition) -> Result<Self> {
+        let group_size = privacy_definition.group_size;
         if group_size == 0 {
             return Err(Error::from("group size must be greater than zero"))
         }
-        use proto::privacy_usage::{DistanceApproximate, Distance::Approximate};
+        use proto::privacy_usage::{DistanceApproximate, DistanceConcentrated, DistanceGaussian, Distance::{Approximate, Concentrated, Gaussian, PrivacyLoss, Renyi}};
 
         c_stability *= group_size;
         Ok(proto::PrivacyUsage {
             distance: Some(match self.distance.as_ref().ok_or_else(|| "distance must be defined")? {
-                Approximate(DistanceApproximate { epsilon, delta }) =>
+                // group privacy for zCDP scales rho by the square of the group size
+                Concentrated(DistanceConcentrated { rho }) => if s == 1. {
+                    Concentrated(DistanceConcentrated { rho: rho / (c_stability as f64).powi(2) })
+                } else {
+                    return Err(Error::from("privacy amplification by subsampling is not supported for concentrated privacy usages"))
+                },
+                // group privacy for GDP scales mu by the group size
+                Gaussian(DistanceGaussian { mu }) => if s == 1. {
+                    Gaussian(DistanceGaussian { mu: mu / c_stability as f64 })
+                } else {
+                    return Err(Error::from("privacy amplification by subsampling is not supported for gaussian privacy usages"))
+                },
+                Renyi(_) | PrivacyLoss(_) => return Err(Error::from("privacy usages reported by the accountant may not be rescaled")),
+                Approximate(DistanceApproximate { epsilon, delta }) => {
+                    if s != 1. && *epsilon > 100. {
+                        return Err(Error::from("large epsilon (>100) with privacy amplification by subsampling is numerically unstable"))
+                    }
+                    let effective_epsilon = deamplify_epsilon(*epsilon, s, privacy_definition.neighboring)? / c_stability as f64;
                     Approximate(DistanceApproximate {
-                    epsilon: match s {
-                        s if s == 1. => epsilon / c_stability as f64,
-                        _ if *epsilon > 100. =>
-                            return Err(Error::from("large epsilon (>100) with privacy amplification by subsampling is numerically unstable")),
-                        s => (((epsilon.exp() - 1.) / s) + 1.).ln() / c_stability as f64
-                    },
-                    delta: delta / s / ((c_stability as f64 * epsilon).exp() - 1.) / (epsilon.exp() - 1.),
-                })
+                        epsilon: effective_epsilon,
+                        delta: delta / s / group_delta_factor(effective_epsilon, c_stability),
+                    })
+                }
             })
         })
     }
 
-    pub(crate) fn effective_to_actual(&self, s: f64, mut c_stability: u32, group_size: u32) -> Result<Self> {
+    /// Convert the usage a mechanism spent on each record of its input into the privacy usage of the release.
+    pub(crate) fn effective_to_actual(&self, s: f64, mut c_stability: u32, privacy_definition: &proto::PrivacyDefinition) -> Result<Self> {
+        let group_size = privacy_definition.group_size;
         if group_size == 0 {
             return Err(Error::from("group size must be greater than zero"))
         }
-        use proto::privacy_usage::{DistanceApproximate, Distance::Approximate};
+        use proto::privacy_usage::{DistanceApproximate, DistanceConcentrated, DistanceGaussian, Distance::{Approximate, Concentrated, Gaussian, PrivacyLoss, Renyi}};
 
         c_stability *= group_size;
         Ok(proto::PrivacyUsage {
             distance: Some(match self.distance.as_ref().ok_or_else(|| "distance must be defined")? {
-                Approximate(DistanceApproximate { epsilon, delta }) => Approximate(DistanceApproximate {
-                    epsilon: match s {
-                        s if s == 1. => epsilon * c_stability as f64,
-                        _ if epsilon * c_stability as f64 > 100. =>
-                            return Err(Error::from("large epsilon * c_stability (>100) with privacy amplification by subsampling is numerically unstable")),
-                        s => (((epsilon * c_stability as f64).exp() - 1.) * s + 1.).ln()
-                    },
-                    delta: delta * s * ((c_stability as f64 * epsilon).exp() - 1.) / (epsilon.exp() - 1.),
-                })
+                Concentrated(DistanceConcentrated { rho }) => if s == 1. {
+                    Concentrated(DistanceConcentrated { rho: rho * (c_stability as f64).powi(2) })
+                } else {
+                    return Err(Error::from("privacy amplification by subsampling is not supported for concentrated privacy usages"))
+                },
+                Gaussian(DistanceGaussian { mu }) => if s == 1. {
+                    Gaussian(DistanceGaussian { mu: mu * c_stability as f64 })
+                } else {
+                    return Err(Error::from("privacy amplification by subsampling is not supported for gaussian privacy usages"))
+                },
+                Renyi(_) | PrivacyLoss(_) => return Err(Error::from("privacy usages reported by the accountant may not be rescaled")),
+                Approximate(DistanceApproximate { epsilon, delta }) => {
+                    if s != 1. && epsilon * c_stability as f64 > 100. {
+                        return Err(Error::from("large epsilon * c_stability (>100) with privacy amplification by subsampling is numerically unstable"))
+                    }
+                    Approximate(DistanceApproximate {
+                        epsilon: amplify_epsilon(epsilon * c_stability as f64, s, privacy_definition.neighboring)?,
+                        delta: delta * s * group_delta_factor(*epsilon, c_stability),
+                    })
+                }
             })
         })
     }
 }
 
+/// Epsilon of a mechanism run on a sample, where each record is included with probability `s`.
+///
+/// Under add/remove neighboring, epsilon = ln(1 + s (e^epsilon - 1)).
+/// Under substitute neighboring, the substituted record may be sampled in one dataset but not the other.
+///
+/// Balle, Barthe, Gaboardi. "Privacy Amplification by Subsampling: Tight Analyses via Couplings and Divergences." NeurIPS 2018.
+fn amplify_epsilon(epsilon: f64, s: f64, neighboring: i32) -> Result<f64> {
+    if s == 1. {
+        return Ok(epsilon)
+    }
+    Ok(match proto::privacy_definition::Neighboring::from_i32(neighboring)
+        .ok_or_else(|| Error::from("neighboring: unrecognized neighboring definition"))? {
+        proto::privacy_definition::Neighboring::AddRemove => (s * epsilon.exp_m1()).ln_1p(),
+        proto::privacy_definition::Neighboring::Substitute =>
+            (s * epsilon.exp_m1()).ln_1p() - (s * (-epsilon).exp_m1()).ln_1p()
+    })
+}
+
+/// The epsilon a mechanism may spend on a sample, such that the amplified epsilon is `epsilon`.
+fn deamplify_epsilon(epsilon: f64, s: f64, neighboring: i32) -> Result<f64> {
+    if s == 1. {
+        return Ok(epsilon)
+    }
+    Ok(match proto::privacy_definition::Neighboring::from_i32(neighboring)
+        .ok_or_else(|| Error::from("neighboring: unrecognized neighboring definition"))? {
+        proto::privacy_definition::Neighboring::AddRemove => (epsilon.exp_m1() / s).ln_1p(),
+        // solve (1 + s (x - 1)) / (1 + s (1 / x - 1)) = e^epsilon for x = e^(amplified epsilon)
+        proto::privacy_definition::Neighboring::Substitute => {
+            let (b, c) = ((1. - s) * -epsilon.exp_m1(), -s * epsilon.exp());
+            ((-b + (b.powi(2) - 4. * s * c).sqrt()) / (2. * s)).ln()
+        }
+    })
+}
+
+/// Ratio of the delta of a group of `c_stability` records to the delta of one record.
+///
+/// Vadhan. "The Complexity of Differential Privacy." 2017.
+fn group_delta_factor(epsilon: f64, c_stability: u32) -> f64 {
+    if c_stability == 1 || epsilon == 0. {
+        return c_stability as f64
+    }
+    (epsilon * c_stability as f64).exp_m1() / epsilon.exp_m1()
+}
+
 
 impl Add<proto::PrivacyUsage> for proto::PrivacyUsage {
     type Output = Result<proto::PrivacyUsage>;
@@ -1206,7 +1305,30 @@ impl Add<proto::PrivacyUsage> for proto::PrivacyUsage {
             (Distance::Approximate(lhs), Distance::Approximate(rhs)) => proto::privacy_usage::Distance::Approximate(proto::privacy_usage::DistanceApproximate {
                 epsilon: lhs.epsilon + rhs.epsilon,
                 delta: lhs.delta + rhs.delta,
-            })
+            }),
+            (Distance::Concentrated(lhs), Distance::Concentrated(rhs)) => proto::privacy_usage::Distance::Concentrated(proto::privacy_usage::DistanceConcentrated {
+                rho: lhs.rho + rhs.rho,
+            }),
+            // mu-GDP composes exactly by the root sum of squares
+            (Distance::Gaussian(lhs), Distance::Gaussian(rhs)) => proto::privacy_usage::Distance::Gaussian(proto::privacy_usage::DistanceGaussian {
+                mu: lhs.mu.hypot(rhs.mu),
+            }),
+            (Distance::Renyi(lhs), Distance::Renyi(rhs)) => {
+                if lhs.orders != rhs.orders {
+                    return Err("renyi privacy usages must be defined over the same orders".into())
+                }
+                proto::privacy_usage::Distance::Renyi(proto::privacy_usage::DistanceRenyi {
+                    epsilons: lhs.epsilons.iter().zip(rhs.epsilons.iter())
+                        .map(|(l, r)| l + r).collect(),
+                    orders: lhs.orders,
+                })
+            },
+            // privacy loss distributions compose via convolution
+            (Distance::PrivacyLoss(lhs), Distance::PrivacyLoss(rhs)) => {
+                let composed = crate::utilities::privacy_loss::PrivacyLossDistribution::from(lhs).compose(&rhs.into())?;
+                return Ok(composed.into())
+            },
+            _ => return Err("privacy usages with different distances may not be added".into())
         });
         Ok(self)
     }
@@ -1221,7 +1343,20 @@ impl Mul<f64> for proto::PrivacyUsage {
             proto::privacy_usage::Distance::Approximate(approximate) => proto::privacy_usage::Distance::Approximate(proto::privacy_usage::DistanceApproximate {
                 epsilon: approximate.epsilon * rhs,
                 delta: approximate.delta * rhs,
-            })
+            }),
+            proto::privacy_usage::Distance::Concentrated(concentrated) => proto::privacy_usage::Distance::Concentrated(proto::privacy_usage::DistanceConcentrated {
+                rho: concentrated.rho * rhs,
+            }),
+            // scale the budget, so that mu composes back to the original usage
+            proto::privacy_usage::Distance::Gaussian(gaussian) => proto::privacy_usage::Distance::Gaussian(proto::privacy_usage::DistanceGaussian {
+                mu: gaussian.mu * rhs.sqrt(),
+            }),
+            proto::privacy_usage::Distance::Renyi(renyi) => proto::privacy_usage::Distance::Renyi(proto::privacy_usage::DistanceRenyi {
+                epsilons: renyi.epsilons.iter().map(|epsilon| epsilon * rhs).collect(),
+                orders: renyi.orders,
+            }),
+            proto::privacy_usage::Distance::PrivacyLoss(_) =>
+                return Err("privacy loss distributions may not be rescaled".into())
         });
         Ok(self)
     }
@@ -1235,7 +1370,19 @@ impl Div<f64> for proto::PrivacyUsage {
             proto::privacy_usage::Distance::Approximate(approximate) => proto::privacy_usage::Distance::Approximate(proto::privacy_usage::DistanceApproximate {
                 epsilon: approximate.epsilon / rhs,
                 delta: approximate.delta / rhs,
-            })
+            }),
+            proto::privacy_usage::Distance::Concentrated(concentrated) => proto::privacy_usage::Distance::Concentrated(proto::privacy_usage::DistanceConcentrated {
+                rho: concentrated.rho / rhs,
+            }),
+            proto::privacy_usage::Distance::Gaussian(gaussian) => proto::privacy_usage::Distance::Gaussian(proto::privacy_usage::DistanceGaussian {
+                mu: gaussian.mu / rhs.sqrt(),
+            }),
+            proto::privacy_usage::Distance::Renyi(renyi) => proto::privacy_usage::Distance::Renyi(proto::privacy_usage::DistanceRenyi {
+                epsilons: renyi.epsilons.iter().map(|epsilon| epsilon / rhs).collect(),
+                orders: renyi.orders,
+            }),
+            proto::privacy_usage::Distance::PrivacyLoss(_) =>
+                return Err("privacy loss distributions may not be rescaled".into())
         });
         Ok(self)
     }
@@ -1329,4 +1476,23 @@ pub mod test_data {
             [true, true, true],
         ]).into()
     }
-}
\ No newline at end of file
+}
+
+/// Privacy usages shared by the tests of the validator and the runtime.
+#[cfg(any(test, feature = "test-utilities"))]
+pub mod test_usage {
+
+    use crate::proto;
+
+    pub fn usage(epsilon: f64) -> proto::PrivacyUsage {
+        approximate_usage(epsilon, 0.)
+    }
+
+    pub fn approximate_usage(epsilon: f64, delta: f64) -> proto::PrivacyUsage {
+        proto::PrivacyUsage {
+            distance: Some(proto::privacy_usage::Distance::Approximate(proto::privacy_usage::DistanceApproximate {
+                epsilon, delta,
+            }))
+        }
+    }
+}
@@ -49,7 +49,11 @@ impl Analysis {
                 protect_overflow: false,
                 protect_elapsed_time: false,
                 protect_memory_utilization: false,
-                protect_floating_point: false
+                protect_floating_point: false,
+                composition: proto::privacy_definition::Composition::Linear as i32,
+                composition_delta: 0.,
+                privacy_filter: None,
+                conversion_delta: 0.
             },
             components: HashMap::new(),
             component_count: 0,
@@ -0,0 +1,35 @@
+use crate::errors::*;
+
+use crate::{proto, base};
+use crate::components::Expandable;
+
+use crate::base::{Value, IndexKey};
+use indexmap::map::IndexMap;
+
+
+impl Expandable for proto::AboveThreshold {
+    fn expand_component(
+        &self,
+        _privacy_definition: &Option<proto::PrivacyDefinition>,
+        component: &proto::Component,
+        _public_arguments: &IndexMap<IndexKey, &Value>,
+        _properties: &base::NodeProperties,
+        component_id: u32,
+        mut _maximum_id: u32,
+    ) -> Result<base::ComponentExpansion> {
+        let mut expansion = base::ComponentExpansion::default();
+
+        expansion.computation_graph.insert(component_id, proto::Component {
+            arguments: component.arguments.clone(),
+            variant: Some(proto::component::Variant::SparseVector(proto::SparseVector {
+                cutoff: 1,
+                privacy_usage: self.privacy_usage.clone()
+            })),
+            omit: component.omit,
+            submission: component.submission,
+        });
+        expansion.traversal.push(component_id);
+
+        Ok(expansion)
+    }
+}
@@ -0,0 +1,242 @@
+use indexmap::map::IndexMap;
+use ndarray::Array;
+
+use crate::{base, Float, proto, Warnable};
+use crate::base::{AggregatorProperties, DataType, IndexKey, NodeProperties, SensitivitySpace, Value, ValueProperties};
+use crate::components::{Component, Expandable, Sensitivity};
+use crate::errors::*;
+use crate::utilities::prepend;
+
+
+impl Component for proto::ContinualCount {
+    fn propagate_property(
+        &self,
+        _privacy_definition: &Option<proto::PrivacyDefinition>,
+        _public_arguments: IndexMap<base::IndexKey, &Value>,
+        properties: base::NodeProperties,
+        node_id: u32
+    ) -> Result<Warnable<ValueProperties>> {
+        let mut data_property = properties.get::<IndexKey>(&"data".into())
+            .ok_or("data: missing")?.array()
+            .map_err(prepend("data:"))?.clone();
+
+        // once expanded, the data are the privatized dyadic sums
+        if !data_property.releasable {
+            return Err("data: must be the privatized dyadic sums of the stream".into())
+        }
+
+        let num_records = data_property.num_records()?;
+        if data_property.num_columns()? != get_num_levels(num_records) {
+            return Err("data: number of columns must match the number of levels of the binary tree".into())
+        }
+
+        data_property.num_columns = Some(1);
+        data_property.dimensionality = Some(1);
+        data_property.data_type = DataType::Float;
+        data_property.nature = None;
+        data_property.aggregator = None;
+        data_property.dataset_id = Some(node_id as i64);
+
+        Ok(ValueProperties::Array(data_property).into())
+    }
+}
+
+impl Expandable for proto::ContinualCount {
+    fn expand_component(
+        &self,
+        _privacy_definition: &Option<proto::PrivacyDefinition>,
+        component: &proto::Component,
+        _public_arguments: &IndexMap<IndexKey, &Value>,
+        properties: &base::NodeProperties,
+        component_id: u32,
+        mut maximum_id: u32,
+    ) -> Result<base::ComponentExpansion> {
+        let mut expansion = base::ComponentExpansion::default();
+
+        let data_property = properties.get::<IndexKey>(&"data".into())
+            .ok_or("data: missing")?.array()
+            .map_err(prepend("data:"))?;
+
+        // the dyadic sums have already been privatized
+        if data_property.releasable {
+            return Ok(expansion)
+        }
+
+        let data_id = component.arguments().get::<IndexKey>(&"data".into())
+            .ok_or_else(|| Error::from("data is a required argument to ContinualCount"))?.to_owned();
+
+        // dyadic sums
+        maximum_id += 1;
+        let id_dyadic_sum = maximum_id;
+        expansion.computation_graph.insert(id_dyadic_sum, proto::Component {
+            arguments: Some(proto::ArgumentNodeIds::new(indexmap!["data".into() => data_id])),
+            variant: Some(proto::component::Variant::DyadicSum(proto::DyadicSum {})),
+            omit: true,
+            submission: component.submission,
+        });
+        expansion.traversal.push(id_dyadic_sum);
+
+        // noising, where the usage is spread evenly over each level of the tree
+        maximum_id += 1;
+        let id_mechanism = maximum_id;
+        let variant = Some(match self.mechanism.to_lowercase().as_str() {
+            "laplace" => proto::component::Variant::LaplaceMechanism(proto::LaplaceMechanism {
+                privacy_usage: self.privacy_usage.clone()
+            }),
+            "gaussian" => proto::component::Variant::GaussianMechanism(proto::GaussianMechanism {
+                privacy_usage: self.privacy_usage.clone(),
+                analytic: false
+            }),
+            "analyticgaussian" => proto::component::Variant::GaussianMechanism(proto::GaussianMechanism {
+                privacy_usage: self.privacy_usage.clone(),
+                analytic: true
+            }),
+            _ => bail!("Unexpected invalid token {:?}", self.mechanism.as_str()),
+        });
+        expansion.computation_graph.insert(id_mechanism, proto::Component {
+            arguments: Some(proto::ArgumentNodeIds::new(indexmap!["data".into() => id_dyadic_sum])),
+            variant,
+            omit: true,
+            submission: component.submission,
+        });
+        expansion.traversal.push(id_mechanism);
+
+        // prefix sums
+        let mut component = component.clone();
+        component.insert_argument(&"data".into(), id_mechanism);
+        expansion.computation_graph.insert(component_id, component);
+
+        Ok(expansion)
+    }
+}
+
+
+impl Component for proto::DyadicSum {
+    fn propagate_property(
+        &self,
+        _privacy_definition: &Option<proto::PrivacyDefinition>,
+        _public_arguments: IndexMap<base::IndexKey, &Value>,
+        properties: base::NodeProperties,
+        node_id: u32
+    ) -> Result<Warnable<ValueProperties>> {
+        let mut data_property = properties.get::<IndexKey>(&"data".into())
+            .ok_or("data: missing")?.array()
+            .map_err(prepend("data:"))?.clone();
+
+        if !data_property.releasable {
+            data_property.assert_is_not_aggregated()?;
+        }
+        if data_property.data_type != DataType::Float && data_property.data_type != DataType::Int {
+            return Err("data: atomic type must be numeric".into())
+        }
+        if data_property.num_columns()? != 1 {
+            return Err("data: must contain one column".into())
+        }
+
+        // the shape of the tree depends on the length of the stream
+        let num_levels = get_num_levels(data_property.num_records()
+            .map_err(prepend("data: the length of the stream must be known, so"))?);
+
+        // save a snapshot of the state when aggregating
+        data_property.aggregator = Some(AggregatorProperties::new(
+            proto::component::Variant::DyadicSum(self.clone()), properties, num_levels));
+
+        data_property.num_columns = Some(num_levels);
+        data_property.dimensionality = Some(2);
+        data_property.nature = None;
+        data_property.dataset_id = Some(node_id as i64);
+
+        Ok(ValueProperties::Array(data_property).into())
+    }
+}
+
+impl Sensitivity for proto::DyadicSum {
+    /// Each element of the stream is contained in at most one dyadic interval per level,
+    /// so each column of the dyadic sums has the sensitivity of a sum.
+    fn compute_sensitivity(
+        &self,
+        privacy_definition: &proto::PrivacyDefinition,
+        properties: &NodeProperties,
+        sensitivity_type: &SensitivitySpace,
+    ) -> Result<Value> {
+        let data_property = properties.get::<IndexKey>(&"data".into())
+            .ok_or("data: missing")?.array()
+            .map_err(prepend("data:"))?.clone();
+
+        data_property.assert_is_not_aggregated()?;
+        data_property.assert_non_null()?;
+
+        match sensitivity_type {
+            SensitivitySpace::KNorm(k) => {
+                if *k != 1 && *k != 2 {
+                    return Err("KNorm sensitivity is only supported in L1 and L2 spaces".into())
+                }
+
+                let lower = data_property.lower_float()?[0];
+                let upper = data_property.upper_float()?[0];
+
+                use proto::privacy_definition::Neighboring;
+                // an element of the stream may be zeroed or replaced
+                let sensitivity: Float = match Neighboring::from_i32(privacy_definition.neighboring)
+                    .ok_or_else(|| Error::from("neighboring definition must be either \"AddRemove\" or \"Substitute\""))? {
+                    Neighboring::AddRemove => lower.abs().max(upper.abs()),
+                    Neighboring::Substitute => upper - lower
+                };
+
+                let num_records = data_property.num_records()?;
+                let num_levels = get_num_levels(num_records);
+
+                Ok(Array::from_elem((num_records as usize, num_levels as usize), sensitivity).into_dyn().into())
+            }
+            _ => Err("DyadicSum sensitivity is only implemented for KNorm".into())
+        }
+    }
+}
+
+/// Number of levels of the binary tree over a stream, so that every prefix is a union of at most one dyadic interval per level.
+pub fn get_num_levels(num_records: i64) -> i64 {
+    let mut num_levels = 1;
+    while 1 << num_levels <= num_records {
+        num_levels += 1;
+    }
+    num_levels
+}
+
+
+#[cfg(test)]
+mod test_continual_count {
+    use crate::proto;
+    use crate::base::{IndexKey, SensitivitySpace, test_data};
+    use crate::base::test_usage::usage;
+    use crate::components::Sensitivity;
+    use crate::components::continual_count::get_num_levels;
+    use crate::components::resize::test_resize;
+
+    #[test]
+    fn test_num_levels() {
+        assert_eq!(get_num_levels(1), 1);
+        assert_eq!(get_num_levels(7), 3);
+        assert_eq!(get_num_levels(8), 4);
+    }
+
+    #[test]
+    fn test_dyadic_sum_sensitivity() {
+        let (mut analysis, resized) = test_resize::utilities::analysis_f64_cont_private(
+            test_data::array1d_f64_10_uniform(), 10.into());
+        let properties = indexmap![IndexKey::from("data") => analysis.properties(resized).unwrap()];
+
+        // each record lies in one dyadic interval per level, so no level is more sensitive than a sum
+        let sensitivity = proto::DyadicSum {}.compute_sensitivity(
+            &analysis.privacy_definition, &properties, &SensitivitySpace::KNorm(1))
+            .unwrap().array().unwrap().float().unwrap();
+        assert_eq!(sensitivity.shape(), &[10, get_num_levels(10) as usize]);
+        assert!(sensitivity.iter().all(|v| *v == 10.));
+
+        // public data would be taken as the already privatized dyadic sums
+        let prefix_sums = analysis.continual_count(resized, vec![usage(1.)]).build();
+        let prefix_sums_property = analysis.properties(prefix_sums).unwrap().array().unwrap().clone();
+        assert!(prefix_sums_property.releasable);
+        assert_eq!(prefix_sums_property.num_records, Some(10));
+        assert_eq!(prefix_sums_property.num_columns, Some(1));
+    }
+}
@@ -0,0 +1,238 @@
+use indexmap::map::IndexMap;
+use itertools::Itertools;
+use ndarray::arr1;
+
+use crate::{base, proto, Warnable};
+use crate::base::{DataType, IndexKey, Nature, NatureContinuous, NodeProperties, SensitivitySpace, Value, ValueProperties, Vector1DNull};
+use crate::components::{Component, Expandable, Mechanism, Sensitivity};
+use crate::components::exponential_mechanism::get_selection_privacy_usage;
+use crate::errors::*;
+use crate::utilities::{get_literal, prepend};
+use crate::utilities::inference::infer_property;
+use crate::utilities::privacy::{approximate_usage_check, privacy_usage_check, spread_privacy_usage, LossModel};
+
+
+impl Component for proto::ContinuousExponentialMechanism {
+    fn propagate_property(
+        &self,
+        privacy_definition: &Option<proto::PrivacyDefinition>,
+        _public_arguments: IndexMap<base::IndexKey, &Value>,
+        properties: base::NodeProperties,
+        node_id: u32
+    ) -> Result<Warnable<ValueProperties>> {
+        if self.alpha < 0. || self.alpha > 1. {
+            return Err("alpha: must be within [0, 1]".into())
+        }
+        propagate_interval_property(privacy_definition, &self.privacy_usage, properties, node_id, 1)
+    }
+}
+
+impl Expandable for proto::ContinuousExponentialMechanism {
+    fn expand_component(
+        &self,
+        privacy_definition: &Option<proto::PrivacyDefinition>,
+        component: &proto::Component,
+        _public_arguments: &IndexMap<IndexKey, &Value>,
+        properties: &base::NodeProperties,
+        component_id: u32,
+        maximum_id: u32,
+    ) -> Result<base::ComponentExpansion> {
+        // the utility of each interval is the utility of the quantile at any point within it
+        let sensitivity = proto::Quantile {
+            alpha: self.alpha,
+            interpolation: "midpoint".to_string()
+        }.compute_sensitivity(
+            privacy_definition.as_ref().ok_or_else(|| "privacy definition must be defined")?,
+            properties,
+            &SensitivitySpace::Exponential)?;
+
+        expand_interval_mechanism(
+            privacy_definition, &self.privacy_usage, sensitivity, component, properties, component_id, maximum_id)
+    }
+}
+
+impl Mechanism for proto::ContinuousExponentialMechanism {
+    fn get_privacy_usage(
+        &self,
+        privacy_definition: &proto::PrivacyDefinition,
+        release_usage: Option<&Vec<proto::PrivacyUsage>>,
+        properties: &NodeProperties
+    ) -> Result<Option<Vec<proto::PrivacyUsage>>> {
+        get_interval_privacy_usage(
+            privacy_definition,
+            release_usage.unwrap_or_else(|| &self.privacy_usage),
+            properties)
+    }
+}
+
+/// Derive the properties of a mechanism that samples quantiles from the intervals between the records of clamped data.
+///
+/// The continuous and joint exponential mechanisms share these properties.
+/// The release of each column contains `num_quantiles` records within the bounds of the data.
+pub fn propagate_interval_property(
+    privacy_definition: &Option<proto::PrivacyDefinition>,
+    privacy_usage: &[proto::PrivacyUsage],
+    properties: base::NodeProperties,
+    node_id: u32,
+    num_quantiles: i64,
+) -> Result<Warnable<ValueProperties>> {
+    let privacy_definition = privacy_definition.as_ref()
+        .ok_or_else(|| "privacy_definition must be defined")?;
+
+    if privacy_definition.protect_floating_point {
+        return Err("Floating-point protections are enabled. Exponential mechanisms over continuous intervals are susceptible to floating-point attacks.".into())
+    }
+
+    if privacy_definition.group_size == 0 {
+        return Err("group size must be greater than zero".into())
+    }
+
+    let mut data_property = properties.get::<IndexKey>(&"data".into())
+        .ok_or("data: missing")?.array()
+        .map_err(prepend("data:"))?.clone();
+
+    data_property.assert_is_not_aggregated()?;
+    data_property.assert_non_null()?;
+    if data_property.releasable {
+        return Err("data: is already public".into())
+    }
+    if data_property.data_type != DataType::Float && data_property.data_type != DataType::Int {
+        return Err("data: atomic type must be numeric".into())
+    }
+
+    // the release is sampled from between the bounds
+    let lower = data_property.lower_float()
+        .map_err(prepend("data: the data must be clamped, so"))?;
+    let upper = data_property.upper_float()
+        .map_err(prepend("data: the data must be clamped, so"))?;
+
+    let privacy_usage = privacy_usage.iter().cloned().map(Ok)
+        .fold1(|l, r| l? + r?).ok_or_else(|| "privacy_usage: must be defined")??;
+
+    let warnings = privacy_usage_check(
+        &privacy_usage,
+        data_property.num_records,
+        privacy_definition.strict_parameter_checks)?;
+
+    approximate_usage_check(&privacy_usage)?;
+
+    data_property.num_records = Some(num_quantiles);
+    data_property.releasable = true;
+    data_property.data_type = DataType::Float;
+    data_property.nature = Some(Nature::Continuous(NatureContinuous {
+        lower: Vector1DNull::Float(lower.into_iter().map(Some).collect()),
+        upper: Vector1DNull::Float(upper.into_iter().map(Some).collect()),
+    }));
+    data_property.dataset_id = Some(node_id as i64);
+
+    Ok(Warnable(data_property.into(), warnings))
+}
+
+/// Insert the sensitivity of the utility and the bounds of the data into an interval mechanism,
+/// and convert its usage to an effective usage.
+pub fn expand_interval_mechanism(
+    privacy_definition: &Option<proto::PrivacyDefinition>,
+    privacy_usage: &[proto::PrivacyUsage],
+    sensitivity: Value,
+    component: &proto::Component,
+    properties: &base::NodeProperties,
+    component_id: u32,
+    mut maximum_id: u32,
+) -> Result<base::ComponentExpansion> {
+    let mut expansion = base::ComponentExpansion::default();
+
+    let privacy_definition = privacy_definition.as_ref()
+        .ok_or_else(|| "privacy definition must be defined")?;
+
+    let data_property = properties.get::<IndexKey>(&"data".into())
+        .ok_or("data: missing")?.array()
+        .map_err(prepend("data:"))?.clone();
+
+    let mut noise_component = component.clone();
+    for (name, value) in [
+        ("sensitivity", sensitivity),
+        ("lower", arr1(&data_property.lower_float()?).into_dyn().into()),
+        ("upper", arr1(&data_property.upper_float()?).into_dyn().into())
+    ] {
+        maximum_id += 1;
+        let id_value = maximum_id;
+        let (patch_node, release) = get_literal(value, component.submission)?;
+        expansion.computation_graph.insert(id_value, patch_node);
+        expansion.properties.insert(id_value, infer_property(&release.value, None, id_value)?);
+        expansion.releases.insert(id_value, release);
+        noise_component.insert_argument(&name.into(), id_value);
+    }
+
+    // spread usage over each column, and convert to effective usage
+    let effective_usages = spread_privacy_usage(privacy_usage, data_property.num_columns()? as usize)?
+        .into_iter()
+        .map(|usage| usage.actual_to_effective(
+            data_property.sample_proportion.unwrap_or(1.),
+            data_property.c_stability,
+            privacy_definition))
+        .collect::<Result<Vec<proto::PrivacyUsage>>>()?;
+
+    // update the privacy usage
+    match noise_component.variant.as_mut() {
+        Some(proto::component::Variant::ContinuousExponentialMechanism(variant)) =>
+            variant.privacy_usage = effective_usages,
+        Some(proto::component::Variant::JointExponentialMechanism(variant)) =>
+            variant.privacy_usage = effective_usages,
+        // this case should never happen
+        _ => return Err(Error::from("Variant must be an interval mechanism"))
+    }
+
+    expansion.computation_graph.insert(component_id, noise_component);
+
+    Ok(expansion)
+}
+
+/// Privacy usage of an interval mechanism, after group_size, c_stability and privacy amplification are taken into account.
+///
+/// Sampling an interval with probability proportional to its exponentiated score is the exponential mechanism,
+/// so interval mechanisms are accounted as bounded range mechanisms.
+pub fn get_interval_privacy_usage(
+    privacy_definition: &proto::PrivacyDefinition,
+    privacy_usage: &[proto::PrivacyUsage],
+    properties: &NodeProperties,
+) -> Result<Option<Vec<proto::PrivacyUsage>>> {
+    get_selection_privacy_usage(
+        privacy_definition, privacy_usage, properties, "data", LossModel::BoundedRange)
+}
+
+
+#[cfg(test)]
+mod test_continuous_exponential_mechanism {
+    use crate::base::test_data;
+    use crate::components::resize::test_resize;
+    use crate::base::test_usage::usage;
+
+    #[test]
+    fn test_continuous_exponential() {
+        let (mut analysis, resized) = test_resize::utilities::analysis_f64_cont_private(
+            test_data::array1d_f64_10_uniform(), 10.into());
+
+        let median = analysis.dp_quantile(resized, 0.5, vec![usage(1.)])
+            .mechanism("ContinuousExponential".to_string())
+            .build();
+
+        // the release is sampled from within the bounds of the clamped data
+        let median_property = analysis.properties(median).unwrap().array().unwrap().clone();
+        assert!(median_property.releasable);
+        assert_eq!(median_property.num_records, Some(1));
+        assert_eq!(median_property.lower_float().unwrap(), vec![0.]);
+        assert_eq!(median_property.upper_float().unwrap(), vec![10.]);
+
+        // the interval weights are computed in floating-point
+        analysis.privacy_definition.protect_floating_point = true;
+        assert!(analysis.properties(median).is_err());
+    }
+
+    #[test]
+    fn test_invalid_alpha() {
+        let (mut analysis, resized) = test_resize::utilities::analysis_f64_cont_private(
+            test_data::array1d_f64_10_uniform(), 10.into());
+        let quantile = analysis.continuous_exponential_mechanism(resized, 1.5, vec![usage(1.)]).build();
+        assert!(analysis.properties(quantile).is_err());
+    }
+}
@@ -3,14 +3,15 @@ use ndarray::arr1;
 
 use crate::{base, Float, Integer, proto, Warnable};
 use crate::base::{AggregatorProperties, DataType, IndexKey, Nature, NatureContinuous, NodeProperties, SensitivitySpace, Value, ValueProperties, Vector1DNull};
-use crate::components::{Component, Sensitivity};
+use crate::components::{Component, Expandable, Sensitivity};
 use crate::errors::*;
-use crate::utilities::get_common_value;
+use crate::utilities::{get_common_value, get_literal, get_number_records_argument, prepend};
+use crate::utilities::inference::infer_property;
 
 impl Component for proto::Count {
     fn propagate_property(
         &self,
-        _privacy_definition: &Option<proto::PrivacyDefinition>,
+        privacy_definition: &Option<proto::PrivacyDefinition>,
         _public_arguments: IndexMap<base::IndexKey, &Value>,
         properties: NodeProperties,
         node_id: u32,
@@ -34,6 +35,12 @@ impl Component for proto::Count {
             data_property.assert_is_not_aggregated()?;
         }
 
+        let protect_memory_utilization = privacy_definition.as_ref()
+            .map(|v| v.protect_memory_utilization).unwrap_or(false);
+        if self.distinct && protect_memory_utilization && !data_property.releasable {
+            data_property.assert_num_records_known().map_err(prepend("data:"))?;
+        }
+
         let c_stability = match properties.get::<IndexKey>(&"data".into())
             .ok_or("data: missing")? {
             ValueProperties::Array(value) => {
@@ -70,6 +77,42 @@ impl Component for proto::Count {
     }
 }
 
+impl Expandable for proto::Count {
+    fn expand_component(
+        &self,
+        privacy_definition: &Option<proto::PrivacyDefinition>,
+        component: &proto::Component,
+        public_arguments: &IndexMap<IndexKey, &Value>,
+        properties: &base::NodeProperties,
+        component_id: u32,
+        mut maximum_id: u32,
+    ) -> Result<base::ComponentExpansion> {
+        let mut expansion = base::ComponentExpansion::default();
+
+        // only the distinct count allocates buffers that would otherwise grow with the data
+        if !self.distinct {
+            return Ok(expansion)
+        }
+
+        // buffers in the runtime are sized from the public number of records, instead of from the data
+        if let Some(value) = get_number_records_argument(
+            privacy_definition, component, public_arguments, properties, "data")? {
+            maximum_id += 1;
+            let id_number_records = maximum_id;
+            let (patch_node, release) = get_literal(value, component.submission)?;
+            expansion.computation_graph.insert(id_number_records, patch_node);
+            expansion.properties.insert(id_number_records, infer_property(&release.value, None, id_number_records)?);
+            expansion.releases.insert(id_number_records, release);
+
+            let mut component = component.clone();
+            component.insert_argument(&"number_records".into(), id_number_records);
+            expansion.computation_graph.insert(component_id, component);
+        }
+
+        Ok(expansion)
+    }
+}
+
 impl Sensitivity for proto::Count {
     /// Count query sensitivities [are backed by the the proofs here](https://github.com/opendifferentialprivacy/whitenoise-core/blob/955703e3d80405d175c8f4642597ccdf2c00332a/whitepapers/sensitivities/counts/counts.pdf).
     fn compute_sensitivity(
@@ -2,7 +2,7 @@ use indexmap::map::IndexMap;
 use ndarray::prelude::*;
 
 use crate::{base, Float, proto, Warnable};
-use crate::base::{AggregatorProperties, DataType, IndexKey, Nature, NatureContinuous, NodeProperties, SensitivitySpace, Value, ValueProperties, Vector1DNull};
+use crate::base::{AggregatorProperties, ArrayProperties, DataType, IndexKey, Nature, NatureContinuous, NodeProperties, SensitivitySpace, Value, ValueProperties, Vector1DNull};
 use crate::components::{Component, Sensitivity};
 use crate::errors::*;
 use crate::utilities::prepend;
@@ -10,11 +10,14 @@ use crate::utilities::prepend;
 impl Component for proto::Covariance {
     fn propagate_property(
         &self,
-        _privacy_definition: &Option<proto::PrivacyDefinition>,
+        privacy_definition: &Option<proto::PrivacyDefinition>,
         _public_arguments: IndexMap<base::IndexKey, &Value>,
         properties: base::NodeProperties,
         node_id: u32
     ) -> Result<Warnable<ValueProperties>> {
+        let protect_overflow = privacy_definition.as_ref()
+            .map(|v| v.protect_overflow).unwrap_or(false);
+
         if properties.contains_key(&IndexKey::from("data")) {
             let mut data_property = properties.get::<IndexKey>(&"data".into())
                 .ok_or("data: missing")?.array()
@@ -40,6 +43,10 @@ impl Component for proto::Covariance {
                 return Err("data: atomic type must be float".into());
             }
 
+            if protect_overflow && !data_property.releasable {
+                assert_no_overflow(&data_property).map_err(prepend("data:"))?;
+            }
+
             data_property.nature = match (
                 data_property.lower_float(),
                 data_property.upper_float()) {
@@ -90,6 +97,13 @@ impl Component for proto::Covariance {
                 right_property.assert_is_not_aggregated()?;
             }
 
+            if protect_overflow && !left_property.releasable {
+                assert_no_overflow(&left_property).map_err(prepend("left:"))?;
+            }
+            if protect_overflow && !right_property.releasable {
+                assert_no_overflow(&right_property).map_err(prepend("right:"))?;
+            }
+
             if !left_property.releasable && !right_property.releasable && left_property.group_id != right_property.group_id {
                 return Err("data from separate partitions may not be mixed".into())
             }
@@ -167,6 +181,9 @@ impl Sensitivity for proto::Covariance {
                             .map_err(prepend("data:"))?.clone();
                         data_property.assert_is_not_aggregated()?;
                         data_property.assert_non_null()?;
+                        if privacy_definition.protect_overflow {
+                            assert_no_overflow(&data_property)?;
+                        }
                         let data_lower = data_property.lower_float()?;
                         let data_upper = data_property.upper_float()?;
                         data_n = data_property.num_records()? as f64;
@@ -187,6 +204,9 @@ impl Sensitivity for proto::Covariance {
                             .map_err(prepend("left:"))?.clone();
                         left_property.assert_is_not_aggregated()?;
                         left_property.assert_non_null()?;
+                        if privacy_definition.protect_overflow {
+                            assert_no_overflow(&left_property).map_err(prepend("left:"))?;
+                        }
                         let left_n = left_property.num_records()?;
                         let left_lower = left_property.lower_float()?;
                         let left_upper = left_property.upper_float()?;
@@ -196,6 +216,9 @@ impl Sensitivity for proto::Covariance {
                             .map_err(prepend("right:"))?.clone();
                         right_property.assert_is_not_aggregated()?;
                         right_property.assert_non_null()?;
+                        if privacy_definition.protect_overflow {
+                            assert_no_overflow(&right_property).map_err(prepend("right:"))?;
+                        }
                         let right_n = right_property.num_records()?;
                         let right_lower = right_property.lower_float()?;
                         let right_upper = right_property.upper_float()?;
@@ -245,4 +268,11 @@ impl Sensitivity for proto::Covariance {
             _ => Err("Covariance sensitivity is only implemented for KNorm".into())
         }
     }
+}
+
+/// Ensure that neither the running sums for the means, nor the running sums of products of deviations, can overflow.
+///
+/// The product of the ranges of any two columns is at most the larger of their squared ranges.
+fn assert_no_overflow(data_property: &ArrayProperties) -> Result<()> {
+    data_property.assert_no_float_overflow(|lower, upper| lower.abs().max(upper.abs()).max((upper - lower).powi(2)))
 }
\ No newline at end of file
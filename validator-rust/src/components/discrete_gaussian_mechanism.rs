@@ -0,0 +1,186 @@
+use indexmap::map::IndexMap;
+use itertools::Itertools;
+
+use crate::{base, proto, Warnable};
+use crate::base::{DataType, IndexKey, NodeProperties, SensitivitySpace, Value, ValueProperties};
+use crate::components::{Component, Expandable, Mechanism, Sensitivity};
+use crate::errors::*;
+use crate::utilities::{expand_mechanism, prepend};
+use crate::utilities::privacy::{get_delta, get_epsilon, get_mechanism_privacy_usages, get_rho, privacy_usage_check, LossModel};
+
+impl Component for proto::DiscreteGaussianMechanism {
+    fn propagate_property(
+        &self,
+        privacy_definition: &Option<proto::PrivacyDefinition>,
+        _public_arguments: IndexMap<base::IndexKey, &Value>,
+        properties: base::NodeProperties,
+        _node_id: u32,
+    ) -> Result<Warnable<ValueProperties>> {
+        let privacy_definition = privacy_definition.as_ref()
+            .ok_or_else(|| "privacy_definition must be defined")?;
+
+        if privacy_definition.group_size == 0 {
+            return Err("group size must be greater than zero".into());
+        }
+
+        let mut data_property = properties.get::<IndexKey>(&"data".into())
+            .ok_or("data: missing")?.array()
+            .map_err(prepend("data:"))?.clone();
+
+        if data_property.data_type != DataType::Int {
+            return Err("data: atomic type must be integer".into())
+        }
+
+        let aggregator = data_property.aggregator.clone()
+            .ok_or_else(|| Error::from("aggregator: missing"))?;
+
+        // sensitivity must be computable
+        aggregator.component.compute_sensitivity(
+            privacy_definition,
+            &aggregator.properties,
+            &SensitivitySpace::KNorm(2))?.array()?.float()?;
+
+        let privacy_usage = self.privacy_usage.iter().cloned().map(Ok)
+            .fold1(|l, r| l? + r?).ok_or_else(|| "privacy_usage: must be defined")??;
+
+        let warnings = privacy_usage_check(
+            &privacy_usage,
+            data_property.num_records,
+            privacy_definition.strict_parameter_checks)?;
+
+        get_discrete_gaussian_rho(&privacy_usage).map_err(prepend("privacy_usage:"))?;
+
+        data_property.releasable = true;
+        data_property.aggregator = None;
+
+        Ok(Warnable(data_property.into(), warnings))
+    }
+}
+
+impl Expandable for proto::DiscreteGaussianMechanism {
+    fn expand_component(
+        &self,
+        privacy_definition: &Option<proto::PrivacyDefinition>,
+        component: &proto::Component,
+        _public_arguments: &IndexMap<IndexKey, &Value>,
+        properties: &base::NodeProperties,
+        component_id: u32,
+        maximum_id: u32,
+    ) -> Result<base::ComponentExpansion> {
+        expand_mechanism(
+            &SensitivitySpace::KNorm(2),
+            privacy_definition,
+            self.privacy_usage.as_ref(),
+            component,
+            properties,
+            component_id,
+            maximum_id,
+        )
+    }
+}
+
+impl Mechanism for proto::DiscreteGaussianMechanism {
+    fn get_privacy_usage(
+        &self,
+        privacy_definition: &proto::PrivacyDefinition,
+        release_usage: Option<&Vec<proto::PrivacyUsage>>,
+        properties: &NodeProperties
+    ) -> Result<Option<Vec<proto::PrivacyUsage>>> {
+        let data_property = properties.get::<IndexKey>(&"data".into())
+            .ok_or("data: missing")?.array()
+            .map_err(prepend("data:"))?;
+
+        get_mechanism_privacy_usages(
+            privacy_definition,
+            release_usage.unwrap_or_else(|| &self.privacy_usage),
+            data_property,
+            |usage, stability| Ok(LossModel::Gaussian(get_discrete_gaussian_rho(usage)? * stability.powi(2))))
+            .map(Some)
+    }
+}
+
+/// Retrieve the rho for which the discrete Gaussian mechanism satisfies rho-zCDP.
+///
+/// The discrete Gaussian with variance parameter sigma^2 satisfies (sensitivity^2 / (2 sigma^2))-zCDP,
+/// as shown in [Canonne, Kamath, Steinke (2020)](https://arxiv.org/abs/2004.00010).
+/// An (epsilon, delta) usage is satisfied by the largest rho for which
+/// rho + 2 sqrt(rho ln(1/delta)) <= epsilon, per Proposition 1.3 of [Bun, Steinke (2016)](https://arxiv.org/abs/1605.02065).
+pub fn get_discrete_gaussian_rho(usage: &proto::PrivacyUsage) -> Result<f64> {
+    match usage.distance {
+        Some(proto::privacy_usage::Distance::Concentrated(_)) => get_rho(usage),
+        Some(proto::privacy_usage::Distance::Approximate(_)) => {
+            let (epsilon, delta) = (get_epsilon(usage)?, get_delta(usage)?);
+            if delta <= 0. {
+                return Err("delta: must be positive for the discrete gaussian mechanism".into())
+            }
+            let log_delta_inv = (1. / delta).ln();
+            Ok(((log_delta_inv + epsilon).sqrt() - log_delta_inv.sqrt()).powi(2))
+        },
+        _ => Err("the discrete gaussian mechanism is only defined for concentrated or approximate privacy usages".into())
+    }
+}
+
+
+#[cfg(test)]
+mod test_discrete_gaussian_mechanism {
+    use crate::proto;
+    use crate::base::test_data;
+    use crate::base::test_usage::approximate_usage;
+    use crate::bindings::Analysis;
+    use crate::components::discrete_gaussian_mechanism::get_discrete_gaussian_rho;
+    use crate::components::resize::test_resize;
+    use crate::utilities::privacy::{get_delta, get_epsilon};
+
+    /// Release a discrete gaussian count of the data for each usage.
+    fn analysis_counts(usages: Vec<proto::PrivacyUsage>) -> Analysis {
+        let (mut analysis, resized) = test_resize::utilities::analysis_f64_cont(
+            test_data::array1d_f64_10_uniform(), 10.into(), None, None);
+
+        let lower = analysis.literal().value(0.into()).value_public(true).build();
+        usages.into_iter().for_each(|usage| {
+            analysis.dp_count(resized, lower, vec![usage])
+                .mechanism("DiscreteGaussian".to_string())
+                .build();
+        });
+        analysis
+    }
+
+    #[test]
+    fn test_delta_accounting() {
+        use proto::privacy_definition::Composition;
+
+        // the mechanism is not defined without delta
+        let analysis = analysis_counts(vec![approximate_usage(1., 0.)]);
+        assert!(crate::compute_privacy_usage(
+            analysis.privacy_definition, analysis.components, analysis.release).is_err());
+
+        // deltas add under linear composition
+        let analysis = analysis_counts(vec![approximate_usage(1., 1e-6); 2]);
+        let privacy_usage = crate::compute_privacy_usage(
+            analysis.privacy_definition, analysis.components, analysis.release).unwrap();
+        assert!((get_epsilon(&privacy_usage).unwrap() - 2.).abs() < 1e-8);
+        assert!((get_delta(&privacy_usage).unwrap() - 2e-6).abs() < 1e-14);
+
+        // the counts are accounted as zCDP, so the composition only spends the conversion delta
+        let mut analysis = analysis_counts(vec![approximate_usage(1., 1e-6); 2]);
+        analysis.privacy_definition.composition = Composition::Renyi as i32;
+        analysis.privacy_definition.conversion_delta = 1e-6;
+        let privacy_usage = crate::compute_privacy_usage(
+            analysis.privacy_definition, analysis.components, analysis.release).unwrap();
+        assert!(get_epsilon(&privacy_usage).unwrap() < 2.);
+        assert!((get_delta(&privacy_usage).unwrap() - 1e-6).abs() < 1e-14);
+    }
+
+    #[test]
+    fn test_approximate_rho() {
+        let (epsilon, delta) = (1., 1e-6);
+        let rho = get_discrete_gaussian_rho(&proto::PrivacyUsage {
+            distance: Some(proto::privacy_usage::Distance::Approximate(proto::privacy_usage::DistanceApproximate {
+                epsilon, delta
+            }))
+        }).unwrap();
+
+        // rho-zCDP implies (rho + 2 sqrt(rho ln(1/delta)), delta)-DP
+        assert!((rho + 2. * (rho * (1. / delta).ln()).sqrt() - epsilon).abs() < 1e-10);
+    }
+}
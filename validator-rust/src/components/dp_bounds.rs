@@ -0,0 +1,294 @@
+use indexmap::map::IndexMap;
+use itertools::Itertools;
+
+use crate::{base, Float, proto, Warnable};
+use crate::base::{ArrayProperties, DataframeProperties, DataType, IndexKey, Nature, NatureContinuous, NodeProperties, Value, ValueProperties, Vector1DNull};
+use crate::components::{Component, Expandable, Mechanism, Report};
+use crate::components::exponential_mechanism::get_selection_privacy_usage;
+use crate::errors::*;
+use crate::utilities::prepend;
+use crate::utilities::privacy::{approximate_usage_check, LossModel, privacy_usage_check, spread_privacy_usage};
+use crate::utilities::json::{AlgorithmInfo, JSONRelease, privacy_usage_to_json};
+
+
+impl Component for proto::DpBounds {
+    fn propagate_property(
+        &self,
+        privacy_definition: &Option<proto::PrivacyDefinition>,
+        _public_arguments: IndexMap<base::IndexKey, &Value>,
+        properties: base::NodeProperties,
+        node_id: u32
+    ) -> Result<Warnable<ValueProperties>> {
+        let privacy_definition = privacy_definition.as_ref()
+            .ok_or_else(|| "privacy_definition must be defined")?;
+
+        if privacy_definition.group_size == 0 {
+            return Err("group size must be greater than zero".into())
+        }
+
+        // records are assigned to bins in time proportional to the number of bins they are compared against
+        if privacy_definition.protect_elapsed_time {
+            return Err("the bounds search may not be used when protecting elapsed time".into())
+        }
+
+        let data_property = properties.get::<IndexKey>(&"data".into())
+            .ok_or("data: missing")?.array()
+            .map_err(prepend("data:"))?.clone();
+
+        data_property.assert_is_not_aggregated()?;
+        if data_property.releasable {
+            return Err("data: is already public".into())
+        }
+        if data_property.data_type != DataType::Float && data_property.data_type != DataType::Int {
+            return Err("data: atomic type must be numeric".into())
+        }
+
+        let (lower, upper) = get_widest_bounds(self.min_exponent, self.max_exponent)?;
+        if self.failure_probability <= 0. || self.failure_probability >= 1. {
+            return Err("failure_probability: must be within (0, 1)".into())
+        }
+
+        let privacy_usage = self.privacy_usage.iter().cloned().map(Ok)
+            .fold1(|l, r| l? + r?).ok_or_else(|| "privacy_usage: must be defined")??;
+
+        let warnings = privacy_usage_check(
+            &privacy_usage,
+            data_property.num_records,
+            privacy_definition.strict_parameter_checks)?;
+
+        approximate_usage_check(&privacy_usage)?;
+
+        // until the bounds are released, they are only known to be within the widest bounds
+        let num_columns = data_property.num_columns()?;
+        let bound_property = ArrayProperties {
+            num_records: Some(1),
+            num_columns: Some(num_columns),
+            nullity: false,
+            releasable: true,
+            c_stability: 1,
+            aggregator: None,
+            nature: Some(Nature::Continuous(NatureContinuous {
+                lower: Vector1DNull::Float(vec![Some(lower); num_columns as usize]),
+                upper: Vector1DNull::Float(vec![Some(upper); num_columns as usize]),
+            })),
+            data_type: DataType::Float,
+            dataset_id: Some(node_id as i64),
+            node_id: node_id as i64,
+            is_not_empty: true,
+            dimensionality: Some(1),
+            group_id: data_property.group_id.clone(),
+            naturally_ordered: true,
+            sample_proportion: None,
+        };
+
+        Ok(Warnable(ValueProperties::Dataframe(DataframeProperties {
+            children: indexmap![
+                "lower".into() => bound_property.clone().into(),
+                "upper".into() => bound_property.into()
+            ]
+        }), warnings))
+    }
+}
+
+impl Expandable for proto::DpBounds {
+    fn expand_component(
+        &self,
+        privacy_definition: &Option<proto::PrivacyDefinition>,
+        component: &proto::Component,
+        _public_arguments: &IndexMap<IndexKey, &Value>,
+        properties: &base::NodeProperties,
+        component_id: u32,
+        _maximum_id: u32,
+    ) -> Result<base::ComponentExpansion> {
+        let mut expansion = base::ComponentExpansion::default();
+
+        let data_property = properties.get::<IndexKey>(&"data".into())
+            .ok_or("data: missing")?.array()
+            .map_err(prepend("data:"))?.clone();
+
+        let privacy_definition = privacy_definition.as_ref()
+            .ok_or_else(|| "privacy definition must be defined")?;
+
+        // spread usage over each column, and convert to effective usage
+        let effective_usages = spread_privacy_usage(&self.privacy_usage, data_property.num_columns()? as usize)?
+            .into_iter()
+            .map(|usage| usage.actual_to_effective(
+                data_property.sample_proportion.unwrap_or(1.),
+                data_property.c_stability,
+                privacy_definition))
+            .collect::<Result<Vec<proto::PrivacyUsage>>>()?;
+
+        let mut updated_component = component.clone();
+        if let Some(proto::component::Variant::DpBounds(variant)) = &mut updated_component.variant {
+            variant.privacy_usage = effective_usages;
+            // this case should never happen
+        } else { return Err(Error::from("Variant must be defined")) }
+        expansion.computation_graph.insert(component_id, updated_component);
+
+        Ok(expansion)
+    }
+}
+
+impl Mechanism for proto::DpBounds {
+    fn get_privacy_usage(
+        &self,
+        privacy_definition: &proto::PrivacyDefinition,
+        release_usage: Option<&Vec<proto::PrivacyUsage>>,
+        properties: &NodeProperties
+    ) -> Result<Option<Vec<proto::PrivacyUsage>>> {
+        // changing a record moves each count in one direction only, so the bounds search is epsilon-bounded range
+        get_selection_privacy_usage(
+            privacy_definition,
+            release_usage.unwrap_or_else(|| &self.privacy_usage),
+            properties,
+            "data",
+            LossModel::BoundedRange)
+    }
+}
+
+impl Report for proto::DpBounds {
+    fn summarize(
+        &self,
+        node_id: u32,
+        component: &proto::Component,
+        _public_arguments: IndexMap<base::IndexKey, &Value>,
+        properties: NodeProperties,
+        release: &Value,
+        variable_names: Option<&Vec<base::IndexKey>>,
+    ) -> Result<Option<Vec<JSONRelease>>> {
+        let data_property = properties.get::<base::IndexKey>(&"data".into())
+            .ok_or("data: missing")?.array()
+            .map_err(prepend("data:"))?.clone();
+
+        let bounds = match release {
+            Value::Dataframe(bounds) => bounds,
+            _ => return Err("release must be a dataframe".into())
+        };
+        // each bound contains one value per column
+        let get_bounds = |name: &str| -> Result<Vec<Float>> {
+            Ok(bounds.get::<IndexKey>(&name.into())
+                .ok_or_else(|| Error::from(format!("{}: missing", name)))?
+                .ref_array()?.ref_float()?.iter().cloned().collect())
+        };
+        let (lowers, uppers) = (get_bounds("lower")?, get_bounds("upper")?);
+
+        let mut releases = Vec::new();
+
+        let num_columns = data_property.num_columns()?;
+        let privacy_usages = spread_privacy_usage(&self.privacy_usage, num_columns as usize)?;
+
+        for (column_number, privacy_usage) in privacy_usages.iter().enumerate() {
+            let variable_name = variable_names
+                .and_then(|names| names.get(column_number)).cloned()
+                .unwrap_or_else(|| "[Unknown]".into());
+
+            releases.push(JSONRelease {
+                description: "DP release information".to_string(),
+                statistic: "DPBounds".to_string(),
+                variables: serde_json::json!(variable_name.to_string()),
+                release_info: serde_json::json!({
+                    "lower": lowers.get(column_number).ok_or("lower: missing column")?,
+                    "upper": uppers.get(column_number).ok_or("upper: missing column")?
+                }),
+                privacy_loss: privacy_usage_to_json(privacy_usage),
+                accuracy: None,
+                submission: component.submission,
+                node_id,
+                postprocess: false,
+                algorithm_info: AlgorithmInfo {
+                    name: "".to_string(),
+                    cite: "".to_string(),
+                    mechanism: "SimpleGeometric".to_string(),
+                    argument: serde_json::json!({
+                        "min_exponent": self.min_exponent,
+                        "max_exponent": self.max_exponent,
+                        "failure_probability": self.failure_probability
+                    }),
+                },
+            });
+        }
+        Ok(Some(releases))
+    }
+}
+
+/// Retrieve the bounds released when no bin of the bounds search is found above the threshold.
+pub fn get_widest_bounds(min_exponent: i32, max_exponent: i32) -> Result<(Float, Float)> {
+    if min_exponent >= max_exponent {
+        return Err("min_exponent: must be less than max_exponent".into())
+    }
+    let upper = (2. as Float).powi(max_exponent);
+    if !upper.is_finite() || (2. as Float).powi(min_exponent) == 0. {
+        return Err("min_exponent and max_exponent: powers of two must be finite and non-zero".into())
+    }
+    Ok((-upper, upper))
+}
+
+
+#[cfg(test)]
+mod test_dp_bounds {
+    use ndarray::arr0;
+    use crate::base::test_usage::usage;
+
+    use crate::base::{test_data, ValueProperties};
+    use crate::bindings::Analysis;
+    use crate::components::literal::test_literal;
+
+    /// Resize private data, without clamping, so that the number of columns is known.
+    fn analysis_f64_resized() -> (Analysis, u32) {
+        let (mut analysis, data) = test_literal::analysis_literal(test_data::array1d_f64_10_uniform(), false);
+        let casted = analysis.to_float(data).build();
+
+        let number_columns = analysis.literal().value(1.into()).value_public(true).build();
+        let number_rows = analysis.literal().value(10.into()).value_public(true).build();
+        let lower = analysis.literal().value(0.0.into()).value_public(true).build();
+        let upper = analysis.literal().value(10.0.into()).value_public(true).build();
+        let resized = analysis.resize(casted)
+            .number_columns(number_columns).number_rows(number_rows).lower(lower).upper(upper)
+            .build();
+        (analysis, resized)
+    }
+
+    #[test]
+    fn test_dp_bounds() {
+        let (mut analysis, data) = analysis_f64_resized();
+
+        let bounds = analysis.dp_bounds(data, vec![usage(0.5)])
+            .min_exponent(-4).max_exponent(4).build();
+
+        // until released, each bound is only known to be within the widest bounds
+        match analysis.properties(bounds).unwrap() {
+            ValueProperties::Dataframe(properties) => properties.children.values().for_each(|column| {
+                let column = column.array().unwrap();
+                assert!(column.releasable);
+                assert_eq!(column.lower_float().unwrap(), vec![-16.]);
+                assert_eq!(column.upper_float().unwrap(), vec![16.]);
+            }),
+            _ => panic!("the bounds must be released as a dataframe")
+        };
+
+        // the bounds may be passed to a clamp in the same analysis
+        let names = ["lower", "upper"].iter()
+            .map(|name| analysis.literal()
+                .value(arr0(name.to_string()).into_dyn().into())
+                .value_public(true).build())
+            .collect::<Vec<u32>>();
+        let lower = analysis.index(bounds).names(names[0]).build();
+        let upper = analysis.index(bounds).names(names[1]).build();
+        let clamped = analysis.clamp(data).lower(lower).upper(upper).build();
+        let imputed = analysis.impute(clamped).lower(lower).upper(upper).build();
+        let sum = analysis.dp_sum(imputed, vec![usage(0.5)]).build();
+        assert!(analysis.properties(sum).is_ok());
+    }
+
+    #[test]
+    fn test_invalid_exponents() {
+        let (mut analysis, data) = analysis_f64_resized();
+        let bounds = analysis.dp_bounds(data, vec![usage(1.)])
+            .min_exponent(4).max_exponent(4).build();
+        assert!(analysis.properties(bounds).is_err());
+
+        let bounds = analysis.dp_bounds(data, vec![usage(1.)])
+            .min_exponent(-4).max_exponent(4).build();
+        assert!(analysis.properties(bounds).is_ok());
+    }
+}
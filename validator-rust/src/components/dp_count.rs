@@ -121,6 +121,9 @@ impl Expandable for proto::DpCount {
                     privacy_usage: self.privacy_usage.clone(),
                     analytic: true
                 }),
+                "discretegaussian" => proto::component::Variant::DiscreteGaussianMechanism(proto::DiscreteGaussianMechanism {
+                    privacy_usage: self.privacy_usage.clone()
+                }),
                 "snapping" => {
                     argument_ids.get::<IndexKey>(&"lower".into())
                         .map(|lower| arguments.insert("lower".into(), *lower));
@@ -2,9 +2,11 @@ use indexmap::map::IndexMap;
 
 use crate::{base, proto, Warnable};
 use crate::base::{Array, ArrayProperties, DataType, IndexKey, NodeProperties, Value, ValueProperties};
-use crate::components::{Component, Expandable, Report};
+use crate::components::{Component, Expandable, Mechanism, Report};
+use crate::components::exponential_mechanism::get_selection_privacy_usage;
 use crate::errors::*;
-use crate::utilities::{array::get_ith_column, prepend, privacy::spread_privacy_usage};
+use crate::utilities::{array::get_ith_column, prepend};
+use crate::utilities::privacy::{LossModel, spread_privacy_usage};
 use crate::utilities::json::{AlgorithmInfo, JSONRelease, privacy_usage_to_json, value_to_json};
 
 impl Component for proto::DpGumbelMedian {
@@ -80,7 +82,7 @@ impl Expandable for proto::DpGumbelMedian {
             variant.privacy_usage = vec![self.privacy_usage[0].actual_to_effective(
                 data_property.sample_proportion.unwrap_or(1.),
                 data_property.c_stability,
-                privacy_definition.group_size)?];
+                privacy_definition)?];
             // this case should never happen
         } else { return Err(Error::from("Variant must be defined")) }
         expansion.computation_graph.insert(component_id, updated_component);
@@ -89,6 +91,23 @@ impl Expandable for proto::DpGumbelMedian {
     }
 }
 
+impl Mechanism for proto::DpGumbelMedian {
+    fn get_privacy_usage(
+        &self,
+        privacy_definition: &proto::PrivacyDefinition,
+        release_usage: Option<&Vec<proto::PrivacyUsage>>,
+        properties: &NodeProperties
+    ) -> Result<Option<Vec<proto::PrivacyUsage>>> {
+        // the gumbel median is the exponential mechanism over the candidate medians
+        get_selection_privacy_usage(
+            privacy_definition,
+            release_usage.unwrap_or_else(|| &self.privacy_usage),
+            properties,
+            "data",
+            LossModel::BoundedRange)
+    }
+}
+
 impl Report for proto::DpGumbelMedian {
     fn summarize(
         &self,
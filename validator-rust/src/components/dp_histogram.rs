@@ -40,6 +40,19 @@ impl Expandable for proto::DpHistogram {
             }.to_string()
         } else { self.mechanism.to_lowercase() };
 
+        // the keys are discovered from the data, so no histogram over public categories is formed
+        if mechanism.as_str() == "stability" {
+            expansion.computation_graph.insert(component_id, proto::Component {
+                arguments: Some(proto::ArgumentNodeIds::new(indexmap!["data".into() => data_id])),
+                variant: Some(proto::component::Variant::StabilityHistogram(proto::StabilityHistogram {
+                    privacy_usage: self.privacy_usage.clone()
+                })),
+                omit: component.omit,
+                submission: component.submission,
+            });
+            return Ok(expansion)
+        }
+
         // histogram
         maximum_id += 1;
         let id_histogram = maximum_id;
@@ -124,6 +137,9 @@ impl Expandable for proto::DpHistogram {
                     privacy_usage: self.privacy_usage.clone(),
                     analytic: true
                 }),
+                "discretegaussian" => proto::component::Variant::DiscreteGaussianMechanism(proto::DiscreteGaussianMechanism {
+                    privacy_usage: self.privacy_usage.clone()
+                }),
                 "snapping" => {
                     argument_ids.get::<IndexKey>(&"lower".into())
                         .map(|lower| arguments.insert("lower".into(), *lower));
@@ -168,6 +184,34 @@ impl Report for proto::DpHistogram {
         let variable_names = variable_names.cloned()
             .unwrap_or_else(|| (0..num_columns).map(|_| "[Unknown]".into()).collect());
 
+        // the stability mechanism releases the observed keys alongside their counts
+        if let Value::Dataframe(release) = release {
+            let get_column = |name: &str| release.get::<IndexKey>(&name.into())
+                .ok_or_else(|| Error::from(format!("release: {} missing", name)))
+                .and_then(value_to_json);
+
+            return Ok(Some(vec![JSONRelease {
+                description: "DP release information".to_string(),
+                statistic: "DPHistogram".to_string(),
+                variables: serde_json::json!(variable_names.iter().map(|v| v.to_string()).collect::<Vec<String>>()),
+                release_info: serde_json::json!({
+                    "categories": get_column("categories")?,
+                    "counts": get_column("counts")?
+                }),
+                privacy_loss: privacy_usage_to_json(&privacy_usages[0]),
+                accuracy: None,
+                submission: component.submission,
+                node_id,
+                postprocess: false,
+                algorithm_info: AlgorithmInfo {
+                    name: "".to_string(),
+                    cite: "".to_string(),
+                    mechanism: self.mechanism.clone(),
+                    argument: serde_json::json!({}),
+                },
+            }]))
+        }
+
         let release = release.ref_array()?.ref_int()?;
 
         Ok(Some(privacy_usages.into_iter()
@@ -36,11 +36,30 @@ impl Expandable for proto::DpQuantile {
             self.mechanism.to_lowercase()
         };
 
+        // the continuous exponential mechanism samples directly from the clamped data, without a set of candidates
+        if mechanism.as_str() == "continuousexponential" {
+            expansion.computation_graph.insert(component_id, proto::Component {
+                arguments: Some(proto::ArgumentNodeIds::new(indexmap!["data".into() => data_id])),
+                variant: Some(proto::component::Variant::ContinuousExponentialMechanism(proto::ContinuousExponentialMechanism {
+                    alpha: self.alpha,
+                    privacy_usage: self.privacy_usage.clone()
+                })),
+                omit: component.omit,
+                submission: component.submission,
+            });
+            expansion.traversal.push(component_id);
+            return Ok(expansion)
+        }
+
+        // selection mechanisms score each of the candidates
+        let is_selection = ["exponential", "reportnoisymax", "reportnoisymaxgumbel", "permuteandflip"]
+            .contains(&mechanism.as_str());
+
         // quantile
         let mut quantile_args = indexmap![IndexKey::from("data") => data_id];
-        if mechanism.as_str() == "exponential" {
+        if is_selection {
             quantile_args.insert("candidates".into(), *argument_ids.get::<IndexKey>(&"candidates".into())
-                .ok_or_else(|| Error::from("candidates is a required argument to DPQuantile when the exponential mechanism is used."))?);
+                .ok_or_else(|| Error::from("candidates is a required argument to DPQuantile when a selection mechanism is used."))?);
         }
         maximum_id += 1;
         let id_quantile = maximum_id;
@@ -57,10 +76,10 @@ impl Expandable for proto::DpQuantile {
 
         // sanitizing
         let mut sanitize_args = IndexMap::new();
-        if self.mechanism.to_lowercase().as_str() == "exponential" {
+        if is_selection {
             sanitize_args.insert("utilities".into(), id_quantile);
             sanitize_args.insert("candidates".into(), *argument_ids.get::<IndexKey>(&"candidates".into())
-                .ok_or_else(|| Error::from("candidates is a required argument to DPQuantile when the exponential mechanism is used."))?);
+                .ok_or_else(|| Error::from("candidates is a required argument to DPQuantile when a selection mechanism is used."))?);
         } else {
             sanitize_args.insert("data".into(), id_quantile);
         }
@@ -80,6 +99,17 @@ impl Expandable for proto::DpQuantile {
             "exponential" => proto::component::Variant::ExponentialMechanism(proto::ExponentialMechanism {
                 privacy_usage: self.privacy_usage.clone()
             }),
+            "reportnoisymax" => proto::component::Variant::ReportNoisyMaxMechanism(proto::ReportNoisyMaxMechanism {
+                privacy_usage: self.privacy_usage.clone(),
+                distribution: "laplace".to_string()
+            }),
+            "reportnoisymaxgumbel" => proto::component::Variant::ReportNoisyMaxMechanism(proto::ReportNoisyMaxMechanism {
+                privacy_usage: self.privacy_usage.clone(),
+                distribution: "gumbel".to_string()
+            }),
+            "permuteandflip" => proto::component::Variant::PermuteAndFlipMechanism(proto::PermuteAndFlipMechanism {
+                privacy_usage: self.privacy_usage.clone()
+            }),
             "snapping" => {
                 argument_ids.get::<IndexKey>(&"lower".into())
                     .map(|lower| sanitize_args.insert("lower".into(), *lower));
@@ -161,3 +191,60 @@ impl Report for proto::DpQuantile {
         Ok(Some(releases))
     }
 }
+
+
+#[cfg(test)]
+mod test_dp_quantile {
+    use ndarray::arr1;
+    use crate::base::test_usage::usage;
+
+    use crate::base::test_data;
+    use crate::components::resize::test_resize;
+    use crate::utilities::privacy::get_epsilon;
+
+    #[test]
+    fn test_selection_mechanisms() {
+        for mechanism in &["Exponential", "ReportNoisyMax", "ReportNoisyMaxGumbel", "PermuteAndFlip"] {
+            let (mut analysis, resized) = test_resize::utilities::analysis_f64_cont(
+                test_data::array1d_f64_10_uniform(), 10.into(), None, None);
+
+            let candidates = analysis.literal()
+                .value(arr1(&[0., 2.5, 5., 7.5, 10.]).into_dyn().into())
+                .value_public(true).build();
+            analysis.dp_quantile(resized, 0.5, vec![usage(1.)])
+                .candidates(candidates)
+                .mechanism(mechanism.to_string())
+                .build();
+
+            let privacy_usage = crate::compute_privacy_usage(
+                analysis.privacy_definition, analysis.components, analysis.release).unwrap();
+            assert!((get_epsilon(&privacy_usage).unwrap() - 1.).abs() < 1e-8);
+        }
+    }
+
+    #[test]
+    fn test_exponential_loss_distribution() {
+        use crate::proto::privacy_definition::Composition;
+
+        let (mut analysis, resized) = test_resize::utilities::analysis_f64_cont(
+            test_data::array1d_f64_10_uniform(), 10.into(), None, None);
+        let candidates = analysis.literal()
+            .value(arr1(&[0., 2.5, 5., 7.5, 10.]).into_dyn().into())
+            .value_public(true).build();
+        for alpha in &[0.25, 0.75] {
+            analysis.dp_quantile(resized, *alpha, vec![usage(0.5)])
+                .candidates(candidates)
+                .mechanism("Exponential".to_string())
+                .build();
+        }
+
+        analysis.privacy_definition.composition = Composition::PrivacyLossDistribution as i32;
+        analysis.privacy_definition.conversion_delta = 1e-6;
+        let privacy_usage = crate::compute_privacy_usage(
+            analysis.privacy_definition, analysis.components, analysis.release).unwrap();
+
+        // the exponential mechanisms are accounted as randomized response, which is tighter than linear composition at a positive delta
+        let epsilon = get_epsilon(&privacy_usage).unwrap();
+        assert!(epsilon > 0.5 && epsilon < 1.);
+    }
+}
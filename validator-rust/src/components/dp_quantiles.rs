@@ -0,0 +1,133 @@
+use indexmap::map::IndexMap;
+
+use crate::{base, proto};
+use crate::base::{Array, IndexKey, NodeProperties, Value};
+use crate::components::{Expandable, Report};
+use crate::errors::*;
+use crate::utilities::{array::get_ith_column, prepend, privacy::spread_privacy_usage};
+use crate::utilities::json::{AlgorithmInfo, JSONRelease, privacy_usage_to_json, value_to_json};
+
+
+impl Expandable for proto::DpQuantiles {
+    fn expand_component(
+        &self,
+        _privacy_definition: &Option<proto::PrivacyDefinition>,
+        component: &proto::Component,
+        _public_arguments: &IndexMap<IndexKey, &Value>,
+        _properties: &base::NodeProperties,
+        component_id: u32,
+        _maximum_id: u32,
+    ) -> Result<base::ComponentExpansion> {
+        let mut expansion = base::ComponentExpansion::default();
+
+        let data_id = *component.arguments().get::<IndexKey>(&"data".into())
+            .ok_or_else(|| Error::from("data is a required argument to DPQuantiles"))?;
+
+        expansion.computation_graph.insert(component_id, proto::Component {
+            arguments: Some(proto::ArgumentNodeIds::new(indexmap!["data".into() => data_id])),
+            variant: Some(proto::component::Variant::JointExponentialMechanism(proto::JointExponentialMechanism {
+                alphas: self.alphas.clone(),
+                privacy_usage: self.privacy_usage.clone()
+            })),
+            omit: component.omit,
+            submission: component.submission,
+        });
+        expansion.traversal.push(component_id);
+
+        Ok(expansion)
+    }
+}
+
+impl Report for proto::DpQuantiles {
+    fn summarize(
+        &self,
+        node_id: u32,
+        component: &proto::Component,
+        _public_arguments: IndexMap<base::IndexKey, &Value>,
+        properties: NodeProperties,
+        release: &Value,
+        variable_names: Option<&Vec<base::IndexKey>>,
+    ) -> Result<Option<Vec<JSONRelease>>> {
+        let data_property = properties.get::<base::IndexKey>(&"data".into())
+            .ok_or("data: missing")?.array()
+            .map_err(prepend("data:"))?.clone();
+
+        let mut releases = Vec::new();
+
+        let minimums = data_property.lower_float()?;
+        let maximums = data_property.upper_float()?;
+
+        let num_columns = data_property.num_columns()?;
+        let privacy_usages = spread_privacy_usage(&self.privacy_usage, num_columns as usize)?;
+
+        for column_number in 0..(num_columns as usize) {
+            let variable_name = variable_names
+                .and_then(|names| names.get(column_number)).cloned()
+                .unwrap_or_else(|| "[Unknown]".into());
+
+            releases.push(JSONRelease {
+                description: "DP release information".to_string(),
+                statistic: "DPQuantiles".to_string(),
+                variables: serde_json::json!(variable_name.to_string()),
+                release_info: match release.ref_array()? {
+                    Array::Float(v) => value_to_json(&get_ith_column(v, column_number)?.into())?,
+                    _ => return Err("quantiles must be float".into())
+                },
+                privacy_loss: privacy_usage_to_json(&privacy_usages[column_number].clone()),
+                accuracy: None,
+                submission: component.submission,
+                node_id,
+                postprocess: false,
+                algorithm_info: AlgorithmInfo {
+                    name: "JointExp".to_string(),
+                    cite: "https://arxiv.org/abs/2102.08244".to_string(),
+                    mechanism: "JointExponential".to_string(),
+                    argument: serde_json::json!({
+                        "alphas": self.alphas,
+                        "constraint": {
+                            "lowerbound": minimums[column_number],
+                            "upperbound": maximums[column_number]
+                        }
+                    }),
+                },
+            });
+        }
+        Ok(Some(releases))
+    }
+}
+
+
+#[cfg(test)]
+mod test_dp_quantiles {
+    use crate::base::test_data;
+    use crate::components::joint_exponential_mechanism::check_alphas;
+    use crate::components::resize::test_resize;
+    use crate::base::test_usage::usage;
+
+    #[test]
+    fn test_check_alphas() {
+        assert!(check_alphas(&[0.1, 0.5, 0.5, 0.9]).is_ok());
+        assert!(check_alphas(&[]).is_err());
+        assert!(check_alphas(&[0.5, 0.1]).is_err());
+        assert!(check_alphas(&[1.5]).is_err());
+    }
+
+    #[test]
+    fn test_dp_quantiles() {
+        let (mut analysis, resized) = test_resize::utilities::analysis_f64_cont_private(
+            test_data::array1d_f64_10_uniform(), 10.into());
+
+        let quantiles = analysis.dp_quantiles(resized, vec![0.25, 0.5, 0.75], vec![usage(1.)]).build();
+
+        let quantiles_property = analysis.properties(quantiles).unwrap().array().unwrap().clone();
+        assert!(quantiles_property.releasable);
+        assert_eq!(quantiles_property.num_records, Some(3));
+        // bounds are carried over from the clamped data
+        assert_eq!(quantiles_property.lower_float().unwrap(), vec![0.]);
+        assert_eq!(quantiles_property.upper_float().unwrap(), vec![10.]);
+
+        // the quantiles must be requested in non-decreasing order, so that the release is sorted
+        let quantiles = analysis.dp_quantiles(resized, vec![0.75, 0.25], vec![usage(1.)]).build();
+        assert!(analysis.properties(quantiles).is_err());
+    }
+}
@@ -0,0 +1,238 @@
+use indexmap::map::IndexMap;
+
+use crate::{base, proto, Warnable};
+use crate::base::{DataType, IndexKey, Jagged, Value, ValueProperties};
+use crate::components::{Component, Expandable};
+use crate::errors::*;
+use crate::utilities::{get_literal, prepend, privacy::spread_privacy_usage};
+use crate::utilities::inference::infer_property;
+
+
+impl Component for proto::DpRangeQueries {
+    fn propagate_property(
+        &self,
+        _privacy_definition: &Option<proto::PrivacyDefinition>,
+        public_arguments: IndexMap<base::IndexKey, &Value>,
+        properties: base::NodeProperties,
+        node_id: u32
+    ) -> Result<Warnable<ValueProperties>> {
+        // once expanded, the arguments are the privatized histograms of each level of the tree
+        let mut leaf_property = properties.get::<IndexKey>(&IndexKey::Int(0))
+            .ok_or("levels: missing, the component must be expanded")?.array()
+            .map_err(prepend("levels:"))?.clone();
+
+        let num_levels = properties.keys()
+            .filter(|key| matches!(key, IndexKey::Int(_))).count();
+        (0..num_levels).try_for_each(|level| {
+            let level_property = properties.get::<IndexKey>(&IndexKey::Int(level as i64))
+                .ok_or_else(|| Error::from(format!("level {}: missing", level)))?.array()?;
+            if !level_property.releasable {
+                return Err(format!("level {}: must be privatized", level).into())
+            }
+            Ok::<_, Error>(())
+        })?;
+
+        // the final count of each level is of the records outside of the edges
+        let num_bins = leaf_property.num_records()? - 1;
+
+        leaf_property.num_records = Some(match public_arguments.get::<IndexKey>(&"queries".into()) {
+            Some(queries) => {
+                let queries = queries.ref_array()?.ref_int()?;
+                if queries.ndim() != 2 || queries.shape()[1] != 2 {
+                    return Err("queries: must have two columns".into())
+                }
+                if queries.iter().any(|edge| *edge < 0 || *edge > num_bins) {
+                    return Err("queries: must be indices into the edges".into())
+                }
+                if queries.genrows().into_iter().any(|query| query[0] > query[1]) {
+                    return Err("queries: the lower edge of each query may not exceed the upper edge".into())
+                }
+                queries.shape()[0] as i64
+            },
+            None => num_bins
+        });
+        leaf_property.num_columns = Some(1);
+        leaf_property.dimensionality = Some(1);
+        leaf_property.data_type = DataType::Float;
+        leaf_property.nature = None;
+        leaf_property.aggregator = None;
+        leaf_property.dataset_id = Some(node_id as i64);
+
+        Ok(ValueProperties::Array(leaf_property).into())
+    }
+}
+
+impl Expandable for proto::DpRangeQueries {
+    fn expand_component(
+        &self,
+        _privacy_definition: &Option<proto::PrivacyDefinition>,
+        component: &proto::Component,
+        public_arguments: &IndexMap<IndexKey, &Value>,
+        _properties: &base::NodeProperties,
+        component_id: u32,
+        mut maximum_id: u32,
+    ) -> Result<base::ComponentExpansion> {
+        let mut expansion = base::ComponentExpansion::default();
+
+        let argument_ids = component.arguments();
+
+        // the histograms of each level have already been formed
+        let data_id = match argument_ids.get::<IndexKey>(&"data".into()) {
+            Some(data_id) => *data_id,
+            None => return Ok(expansion)
+        };
+
+        if self.branching < 2 {
+            return Err("branching: must be at least two".into())
+        }
+
+        let edges = public_arguments.get::<IndexKey>(&"edges".into())
+            .ok_or_else(|| Error::from("edges: missing, must be public"))?.ref_jagged()?;
+        if edges.num_columns() != 1 {
+            return Err("edges: must contain one column".into())
+        }
+        let num_bins = edges.num_records()[0] - 1;
+        if num_bins < 1 {
+            return Err("edges: must contain at least two edges".into())
+        }
+
+        let level_edge_indices = get_level_edge_indices(num_bins, self.branching as i64);
+        let level_usages = spread_privacy_usage(&self.privacy_usage, level_edge_indices.len())?;
+
+        let mut level_ids = IndexMap::<IndexKey, u32>::new();
+        for (level, (edge_indices, usage)) in level_edge_indices.iter().zip(level_usages).enumerate() {
+
+            // edges of the bins of this level
+            maximum_id += 1;
+            let id_edges = maximum_id;
+            let value = Value::Jagged(match edges {
+                Jagged::Float(edges) => vec![edge_indices.iter()
+                    .map(|idx| edges[0][*idx as usize]).collect::<Vec<_>>()].into(),
+                Jagged::Int(edges) => vec![edge_indices.iter()
+                    .map(|idx| edges[0][*idx as usize]).collect::<Vec<_>>()].into(),
+                _ => return Err("edges: must be numeric".into())
+            });
+            let (patch_node, edges_release) = get_literal(value, component.submission)?;
+            expansion.computation_graph.insert(id_edges, patch_node);
+            expansion.properties.insert(id_edges, infer_property(&edges_release.value, None, id_edges)?);
+            expansion.releases.insert(id_edges, edges_release);
+
+            // histogram
+            maximum_id += 1;
+            let id_histogram = maximum_id;
+            let mut histogram_arguments = indexmap!["data".into() => data_id, "edges".into() => id_edges];
+            argument_ids.get::<IndexKey>(&"inclusive_left".into())
+                .map(|v| histogram_arguments.insert("inclusive_left".into(), *v));
+            expansion.computation_graph.insert(id_histogram, proto::Component {
+                arguments: Some(proto::ArgumentNodeIds::new(histogram_arguments)),
+                variant: Some(proto::component::Variant::Histogram(proto::Histogram {})),
+                omit: true,
+                submission: component.submission,
+            });
+            expansion.traversal.push(id_histogram);
+
+            // noising
+            maximum_id += 1;
+            let id_mechanism = maximum_id;
+            let variant = Some(match self.mechanism.to_lowercase().as_str() {
+                "laplace" => proto::component::Variant::LaplaceMechanism(proto::LaplaceMechanism {
+                    privacy_usage: vec![usage]
+                }),
+                "gaussian" => proto::component::Variant::GaussianMechanism(proto::GaussianMechanism {
+                    privacy_usage: vec![usage],
+                    analytic: false
+                }),
+                "analyticgaussian" => proto::component::Variant::GaussianMechanism(proto::GaussianMechanism {
+                    privacy_usage: vec![usage],
+                    analytic: true
+                }),
+                _ => bail!("Unexpected invalid token {:?}", self.mechanism.as_str()),
+            });
+            expansion.computation_graph.insert(id_mechanism, proto::Component {
+                arguments: Some(proto::ArgumentNodeIds::new(indexmap!["data".into() => id_histogram])),
+                variant,
+                omit: true,
+                submission: component.submission,
+            });
+            expansion.traversal.push(id_mechanism);
+
+            level_ids.insert(IndexKey::Int(level as i64), id_mechanism);
+        }
+
+        // constrained inference and queries
+        argument_ids.get::<IndexKey>(&"queries".into())
+            .map(|v| level_ids.insert("queries".into(), *v));
+        expansion.computation_graph.insert(component_id, proto::Component {
+            arguments: Some(proto::ArgumentNodeIds::new(level_ids)),
+            variant: component.variant.clone(),
+            omit: component.omit,
+            submission: component.submission,
+        });
+
+        Ok(expansion)
+    }
+}
+
+/// Indices into the edges of the bins at each level of a tree, from the finest bins to the root.
+///
+/// Each bin is the union of `branching` adjacent bins of the level below,
+/// except for the final bin of each level, which may have fewer.
+pub fn get_level_edge_indices(num_bins: i64, branching: i64) -> Vec<Vec<i64>> {
+    let mut levels = Vec::new();
+    let mut width = 1;
+    loop {
+        let mut indices = (0..num_bins).step_by(width as usize).collect::<Vec<i64>>();
+        indices.push(num_bins);
+        let is_root = indices.len() == 2;
+        levels.push(indices);
+        if is_root { return levels }
+        width *= branching;
+    }
+}
+
+
+#[cfg(test)]
+mod test_dp_range_queries {
+    use ndarray::{arr2, Array2};
+
+    use crate::base::{test_data, Value};
+    use crate::base::test_usage::usage;
+    use crate::components::dp_range_queries::get_level_edge_indices;
+    use crate::components::resize::test_resize;
+
+    #[test]
+    fn test_level_edge_indices() {
+        assert_eq!(get_level_edge_indices(5, 2), vec![
+            vec![0, 1, 2, 3, 4, 5], vec![0, 2, 4, 5], vec![0, 4, 5], vec![0, 5]]);
+        assert_eq!(get_level_edge_indices(1, 2), vec![vec![0, 1]]);
+    }
+
+    #[test]
+    fn test_dp_range_queries() {
+        let (mut analysis, resized) = test_resize::utilities::analysis_f64_cont(
+            test_data::array1d_f64_10_uniform(), 10.into(), None, None);
+
+        let edges = analysis.literal()
+            .value(Value::Jagged(vec![(0..=10).map(|v| v as f64).collect::<Vec<f64>>()].into()))
+            .value_public(true).build();
+        let inclusive_left = analysis.literal().value(true.into()).value_public(true).build();
+        let mut range_queries = |queries: Array2<i64>| {
+            let queries = analysis.literal()
+                .value(queries.into_dyn().into())
+                .value_public(true).build();
+            let counts = analysis.dp_range_queries(resized, edges, inclusive_left, vec![usage(1.)])
+                .queries(queries)
+                .build();
+            analysis.properties(counts)
+        };
+
+        // one count is released for each query
+        let counts_property = range_queries(arr2(&[[0, 10], [2, 7]])).unwrap().array().unwrap().clone();
+        assert!(counts_property.releasable);
+        assert_eq!(counts_property.num_records, Some(2));
+
+        // queries are intervals of the bins between the edges
+        assert!(range_queries(arr2(&[[7, 2]])).is_err());
+        assert!(range_queries(arr2(&[[0, 11]])).is_err());
+    }
+}
@@ -1,8 +1,9 @@
 use crate::errors::*;
 
-use crate::components::Component;
+use crate::components::{Component, Expandable};
 use crate::base::{Value, ValueProperties, DataType, IndexKey};
-use crate::utilities::prepend;
+use crate::utilities::{prepend, get_literal, get_number_records_argument};
+use crate::utilities::inference::infer_property;
 use crate::{base, Warnable};
 use crate::proto;
 use crate::components::transforms::propagate_binary_shape;
@@ -11,7 +12,7 @@ use indexmap::map::IndexMap;
 impl Component for proto::Filter {
     fn propagate_property(
         &self,
-        _privacy_definition: &Option<proto::PrivacyDefinition>,
+        privacy_definition: &Option<proto::PrivacyDefinition>,
         _public_arguments: IndexMap<base::IndexKey, &Value>,
         properties: base::NodeProperties,
         node_id: u32
@@ -24,6 +25,12 @@ impl Component for proto::Filter {
             data_property.assert_is_not_aggregated()?;
         }
 
+        let protect_memory_utilization = privacy_definition.as_ref()
+            .map(|v| v.protect_memory_utilization).unwrap_or(false);
+        if protect_memory_utilization && !data_property.releasable {
+            data_property.assert_num_records_known().map_err(prepend("data:"))?;
+        }
+
         let mask_property = properties.get::<IndexKey>(&"mask".into())
             .ok_or("mask: missing")?.array()
             .map_err(prepend("mask:"))?.clone();
@@ -53,4 +60,67 @@ impl Component for proto::Filter {
 
         Ok(ValueProperties::Array(data_property).into())
     }
-}
\ No newline at end of file
+}
+
+impl Expandable for proto::Filter {
+    fn expand_component(
+        &self,
+        privacy_definition: &Option<proto::PrivacyDefinition>,
+        component: &proto::Component,
+        public_arguments: &IndexMap<IndexKey, &Value>,
+        properties: &base::NodeProperties,
+        component_id: u32,
+        mut maximum_id: u32,
+    ) -> Result<base::ComponentExpansion> {
+        let mut expansion = base::ComponentExpansion::default();
+
+        // buffers in the runtime are sized from the public number of records, instead of from the data
+        if let Some(value) = get_number_records_argument(
+            privacy_definition, component, public_arguments, properties, "data")? {
+            maximum_id += 1;
+            let id_number_records = maximum_id;
+            let (patch_node, release) = get_literal(value, component.submission)?;
+            expansion.computation_graph.insert(id_number_records, patch_node);
+            expansion.properties.insert(id_number_records, infer_property(&release.value, None, id_number_records)?);
+            expansion.releases.insert(id_number_records, release);
+
+            let mut component = component.clone();
+            component.insert_argument(&"number_records".into(), id_number_records);
+            expansion.computation_graph.insert(component_id, component);
+        }
+
+        Ok(expansion)
+    }
+}
+
+#[cfg(test)]
+mod test_filter {
+    use ndarray::arr1;
+
+    use crate::base::test_data;
+    use crate::components::literal::test_literal;
+
+    #[test]
+    fn test_protect_memory_utilization() {
+        let (mut analysis, data) = test_literal::analysis_literal(test_data::array1d_f64_10_uniform(), false);
+        let number_columns = analysis.literal().value(1.into()).value_public(true).build();
+        let number_rows = analysis.literal().value(10.into()).value_public(true).build();
+        let lower = analysis.literal().value(0.0.into()).value_public(true).build();
+        let upper = analysis.literal().value(10.0.into()).value_public(true).build();
+        let casted = analysis.to_float(data).build();
+        let resized = analysis.resize(casted)
+            .number_columns(number_columns).number_rows(number_rows).lower(lower).upper(upper)
+            .build();
+
+        let mask = analysis.literal()
+            .value(arr1(&[true; 10]).into_dyn().into()).value_public(true).build();
+        let filtered = analysis.filter(resized, mask).build();
+        let sampled = analysis.poisson_sample(filtered, 0.5).build();
+        assert!(analysis.properties(sampled).is_ok());
+
+        // the number of records is unknown after filtering, so sampling would allocate based on the data
+        analysis.privacy_definition.protect_memory_utilization = true;
+        assert!(analysis.properties(filtered).is_ok());
+        assert!(analysis.properties(sampled).is_err());
+    }
+}
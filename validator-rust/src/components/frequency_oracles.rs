@@ -0,0 +1,158 @@
+use indexmap::map::IndexMap;
+
+use crate::{base, proto, Warnable};
+use crate::base::{ArrayProperties, DataType, IndexKey, NodeProperties, Value, ValueProperties};
+use crate::components::{Component, Expandable, Mechanism};
+use crate::components::randomized_response::{expand_local_categories, get_local_privacy_usage, get_num_local_categories, propagate_local_estimate_property, propagate_local_property};
+use crate::errors::*;
+use crate::utilities::prepend;
+
+
+macro_rules! impl_frequency_oracle {
+    ($oracle:ident, $estimate:ident, $data_type:expr, $num_columns:expr, $estimate_num_columns:expr) => {
+        impl Component for proto::$oracle {
+            fn propagate_property(
+                &self,
+                privacy_definition: &Option<proto::PrivacyDefinition>,
+                _public_arguments: IndexMap<base::IndexKey, &Value>,
+                properties: base::NodeProperties,
+                _node_id: u32
+            ) -> Result<Warnable<ValueProperties>> {
+                let privacy_definition = privacy_definition.as_ref()
+                    .ok_or_else(|| "privacy_definition must be defined")?;
+
+                let mut data_property = properties.get::<IndexKey>(&"data".into())
+                    .ok_or("data: missing")?.array()
+                    .map_err(prepend("data:"))?.clone();
+
+                let warnings = propagate_local_property(privacy_definition, &self.privacy_usage, &data_property)?;
+                let num_categories = get_num_local_categories(&data_property)?;
+
+                let num_columns: fn(i64) -> i64 = $num_columns;
+                set_oracle_property(&mut data_property, $data_type, num_columns(num_categories));
+
+                Ok(Warnable(data_property.into(), warnings))
+            }
+        }
+
+        impl Expandable for proto::$oracle {
+            fn expand_component(
+                &self,
+                _privacy_definition: &Option<proto::PrivacyDefinition>,
+                component: &proto::Component,
+                public_arguments: &IndexMap<IndexKey, &Value>,
+                properties: &base::NodeProperties,
+                component_id: u32,
+                maximum_id: u32,
+            ) -> Result<base::ComponentExpansion> {
+                expand_local_categories(component, public_arguments, properties, component_id, maximum_id)
+            }
+        }
+
+        impl Mechanism for proto::$oracle {
+            fn get_privacy_usage(
+                &self,
+                privacy_definition: &proto::PrivacyDefinition,
+                release_usage: Option<&Vec<proto::PrivacyUsage>>,
+                properties: &NodeProperties
+            ) -> Result<Option<Vec<proto::PrivacyUsage>>> {
+                get_local_privacy_usage(
+                    privacy_definition,
+                    release_usage.unwrap_or_else(|| &self.privacy_usage),
+                    properties)
+            }
+        }
+
+        impl Component for proto::$estimate {
+            fn propagate_property(
+                &self,
+                _privacy_definition: &Option<proto::PrivacyDefinition>,
+                public_arguments: IndexMap<base::IndexKey, &Value>,
+                properties: base::NodeProperties,
+                node_id: u32
+            ) -> Result<Warnable<ValueProperties>> {
+                let data_property = properties.get::<IndexKey>(&"data".into())
+                    .ok_or("data: missing")?.array()
+                    .map_err(prepend("data:"))?.clone();
+
+                let num_categories = public_arguments.get::<IndexKey>(&"categories".into())
+                    .ok_or_else(|| Error::from("categories: must be public"))?
+                    .ref_array()?.num_records()? as i64;
+
+                if data_property.data_type != $data_type {
+                    return Err(format!("data: atomic type must be {:?}", $data_type).into())
+                }
+                let num_columns: fn(i64) -> i64 = $estimate_num_columns;
+                if data_property.num_columns()? != num_columns(num_categories) {
+                    return Err("data: number of columns is not consistent with the number of categories".into())
+                }
+
+                propagate_local_estimate_property(&self.privacy_usage, public_arguments, data_property, node_id)
+            }
+        }
+    }
+}
+
+// one bit for each category
+impl_frequency_oracle!(OptimizedUnaryEncoding, OptimizedUnaryEncodingEstimate, DataType::Bool, |k| k, |k| k);
+// the seed of the hash, and the perturbed hash
+impl_frequency_oracle!(OptimizedLocalHashing, OptimizedLocalHashingEstimate, DataType::Int, |_| 2, |_| 2);
+// an index into the columns of the Hadamard matrix
+impl_frequency_oracle!(HadamardResponse, HadamardResponseEstimate, DataType::Int, |_| 1, |_| 1);
+
+
+/// Update the properties of categorical data to those of its local perturbation.
+fn set_oracle_property(data_property: &mut ArrayProperties, data_type: DataType, num_columns: i64) {
+    data_property.releasable = true;
+    data_property.data_type = data_type;
+    if num_columns != 1 {
+        data_property.dimensionality = Some(2);
+    }
+    data_property.num_columns = Some(num_columns);
+    data_property.nature = None;
+}
+
+
+#[cfg(test)]
+mod test_frequency_oracles {
+    use crate::base::{DataType, Value};
+    use crate::components::resize::test_resize;
+    use crate::base::test_usage::usage;
+
+    #[test]
+    fn test_frequency_oracles() {
+        let (mut analysis, resized) = test_resize::utilities::analysis_i64_cat_private(
+            ndarray::arr1(&[0, 1, 2, 1, 0, 2, 2, 1, 0, 1]).into(), 10.into(),
+            Value::Jagged(vec![vec![0, 1, 2]].into()));
+        // the null value of the clamp is also a category
+        let categories = analysis.literal()
+            .value(ndarray::arr1(&[0, 1, 2, -1]).into_dyn().into()).value_public(true)
+            .build();
+
+        let encoded = analysis.optimized_unary_encoding(resized, vec![usage(1.)]).build();
+        let encoded_estimate = analysis.optimized_unary_encoding_estimate(encoded, categories, vec![usage(1.)]).build();
+        let hashed = analysis.optimized_local_hashing(resized, vec![usage(1.)]).build();
+        let hashed_estimate = analysis.optimized_local_hashing_estimate(hashed, categories, vec![usage(1.)]).build();
+        let hadamard = analysis.hadamard_response(resized, vec![usage(1.)]).build();
+        let hadamard_estimate = analysis.hadamard_response_estimate(hadamard, categories, vec![usage(1.)]).build();
+
+        // each oracle releases its own encoding of the rows
+        for (oracle, data_type, num_columns) in &[
+            (encoded, DataType::Bool, 4), (hashed, DataType::Int, 2), (hadamard, DataType::Int, 1)] {
+            let oracle_property = analysis.properties(*oracle).unwrap().array().unwrap().clone();
+            assert!(oracle_property.releasable);
+            assert_eq!(oracle_property.data_type, *data_type);
+            assert_eq!(oracle_property.num_columns, Some(*num_columns));
+        }
+
+        // there is one estimated count per category
+        for estimate in &[encoded_estimate, hashed_estimate, hadamard_estimate] {
+            let estimate_property = analysis.properties(*estimate).unwrap().array().unwrap().clone();
+            assert_eq!(estimate_property.num_records, Some(4));
+        }
+
+        // the estimate must match the encoding of the oracle
+        let mismatched = analysis.hadamard_response_estimate(hashed, categories, vec![usage(1.)]).build();
+        assert!(analysis.properties(mismatched).is_err());
+    }
+}
@@ -9,7 +9,7 @@ use crate::components::{Accuracy, Mechanism, Sensitivity};
 use crate::components::{Component, Expandable};
 use crate::errors::*;
 use crate::utilities::{expand_mechanism, prepend};
-use crate::utilities::privacy::{get_delta, get_epsilon, privacy_usage_check, spread_privacy_usage};
+use crate::utilities::privacy::{get_delta, get_epsilon, get_mechanism_privacy_usages, get_mu, get_rho, privacy_usage_check, LossModel, spread_privacy_usage};
 
 impl Component for proto::GaussianMechanism {
     fn propagate_property(
@@ -57,18 +57,21 @@ impl Component for proto::GaussianMechanism {
             data_property.num_records,
             privacy_definition.strict_parameter_checks)?;
 
-        let epsilon = get_epsilon(&privacy_usage)?;
-        if !self.analytic && epsilon > 1.0 {
-            let message = Error::from(format!(
-                "Warning: A privacy parameter of epsilon = {} is in use. \
-                Privacy is only guaranteed for the Gaussian mechanism for epsilon between 0 and 1. \
-                Use the 'AnalyticGaussian' instead.", epsilon));
-
-            return Err(message)
-        }
-
-        if get_delta(&privacy_usage)? == 0.0 {
-            return Err("delta: may not be zero".into())
+        // the gaussian mechanism satisfies rho-zCDP and mu-GDP for any rho or mu, with no restrictions on epsilon or delta
+        if let Some(proto::privacy_usage::Distance::Approximate(_)) = privacy_usage.distance {
+            let epsilon = get_epsilon(&privacy_usage)?;
+            if !self.analytic && epsilon > 1.0 {
+                let message = Error::from(format!(
+                    "Warning: A privacy parameter of epsilon = {} is in use. \
+                    Privacy is only guaranteed for the Gaussian mechanism for epsilon between 0 and 1. \
+                    Use the 'AnalyticGaussian' instead.", epsilon));
+
+                return Err(message)
+            }
+
+            if get_delta(&privacy_usage)? == 0.0 {
+                return Err("delta: may not be zero".into())
+            }
         }
 
         data_property.releasable = true;
@@ -112,12 +115,29 @@ impl Mechanism for proto::GaussianMechanism {
             .ok_or("data: missing")?.array()
             .map_err(prepend("data:"))?;
 
-        Some(release_usage.unwrap_or_else(|| &self.privacy_usage).iter()
-            .map(|usage| usage.effective_to_actual(
-                data_property.sample_proportion.unwrap_or(1.),
-                data_property.c_stability,
-                privacy_definition.group_size))
-            .collect::<Result<Vec<proto::PrivacyUsage>>>()).transpose()
+        get_mechanism_privacy_usages(
+            privacy_definition,
+            release_usage.unwrap_or_else(|| &self.privacy_usage),
+            data_property,
+            |usage, stability| {
+                let rho = match usage.distance {
+                    Some(proto::privacy_usage::Distance::Concentrated(_)) => get_rho(usage)?,
+                    // a mu-GDP gaussian mechanism has sigma = sensitivity / mu
+                    Some(proto::privacy_usage::Distance::Gaussian(_)) => get_mu(usage)?.powi(2) / 2.,
+                    _ => {
+                        let (epsilon, delta) = (get_epsilon(usage)?, get_delta(usage)?);
+                        // noise scale when the sensitivity is one, as computed by the runtime
+                        let sigma = if self.analytic {
+                            get_analytic_gaussian_sigma(epsilon, delta, 1.)
+                        } else {
+                            (2. * (1.25 / delta).ln()).sqrt() / epsilon
+                        };
+                        1. / (2. * sigma.powi(2))
+                    }
+                };
+                Ok(LossModel::Gaussian(rho * stability.powi(2)))
+            })
+            .map(Some)
     }
 }
 
@@ -145,23 +165,36 @@ impl Accuracy for proto::GaussianMechanism {
         // sensitivity must be computable
         let sensitivities = sensitivity_value.array()?.float()?;
         let usages = spread_privacy_usage(&self.privacy_usage, sensitivities.len())?;
-        let delta = usages.iter().map(get_delta).collect::<Result<Vec<f64>>>()?;
-        let iter = izip!(sensitivities.into_iter(), accuracies.values.iter(), delta.into_iter());
+        let iter = izip!(sensitivities.into_iter(), accuracies.values.iter(), usages.into_iter());
 
-        use proto::privacy_usage::{Distance, DistanceApproximate};
+        use proto::privacy_usage::{Distance, DistanceApproximate, DistanceConcentrated, DistanceGaussian};
+
+        Some(iter.map(|(sensitivity, accuracy, usage)| {
+            let sigma = accuracy.value / (2.0_f64.sqrt() * erf::erf_inv(1.0_f64 - accuracy.alpha));
 
-        Some(iter.map(|(sensitivity, accuracy, delta)| {
-            let sigma: f64 = if self.analytic {
-                let c: f64 = 2.0_f64 * (1.25_f64 / delta).ln();
-                c.sqrt() * *sensitivity as f64 / accuracy.value
-            } else {
-                return Err(Error::from("converting to privacy usage is not implemented for the analytic gaussian"))
-            };
             Ok(proto::PrivacyUsage {
-                distance: Some(Distance::Approximate(DistanceApproximate {
-                    epsilon: sigma * 2.0_f64.sqrt() * erf::erf_inv(1.0_f64 - accuracy.alpha),
-                    delta,
-                }))
+                distance: Some(match usage.distance.ok_or("distance must be defined")? {
+                    Distance::Concentrated(_) => Distance::Concentrated(DistanceConcentrated {
+                        rho: (*sensitivity / sigma).powi(2) / 2.
+                    }),
+                    Distance::Gaussian(_) => Distance::Gaussian(DistanceGaussian {
+                        mu: *sensitivity / sigma
+                    }),
+                    Distance::Approximate(DistanceApproximate { delta, .. }) => {
+                        let sigma: f64 = if self.analytic {
+                            let c: f64 = 2.0_f64 * (1.25_f64 / delta).ln();
+                            c.sqrt() * *sensitivity as f64 / accuracy.value
+                        } else {
+                            return Err(Error::from("converting to privacy usage is not implemented for the analytic gaussian"))
+                        };
+                        Distance::Approximate(DistanceApproximate {
+                            epsilon: sigma * 2.0_f64.sqrt() * erf::erf_inv(1.0_f64 - accuracy.alpha),
+                            delta,
+                        })
+                    }
+                    Distance::Renyi(_) | Distance::PrivacyLoss(_) =>
+                        return Err(Error::from("privacy usages reported by the accountant may not be requested"))
+                })
             })
         }).collect()).transpose()
     }
@@ -189,25 +222,26 @@ impl Accuracy for proto::GaussianMechanism {
         let sensitivities = sensitivities_value.array()?.float()?;
 
         let usages = spread_privacy_usage(&self.privacy_usage, sensitivities.len())?;
-        let epsilons = usages.iter().map(get_epsilon).collect::<Result<Vec<f64>>>()?;
-        let deltas = usages.iter().map(get_delta).collect::<Result<Vec<f64>>>()?;
-        let iter = izip!(sensitivities.into_iter(), epsilons.into_iter(), deltas.into_iter());
-
-        Ok(Some(
-            iter.map(|(sensitivity, epsilon, delta)| {
-
-                let sigma: f64 = if self.analytic {
-                    let c: f64 = 2.0_f64 * (1.25_f64 / delta).ln();
-                    c.sqrt() * *sensitivity as f64 / epsilon
-                } else {
-                    get_analytic_gaussian_sigma(epsilon, delta, *sensitivity)
-                };
+        let iter = izip!(sensitivities.into_iter(), usages.iter());
+
+        Some(iter.map(|(sensitivity, usage)| {
+
+            let sigma: f64 = if let Some(proto::privacy_usage::Distance::Concentrated(_)) = usage.distance {
+                get_concentrated_gaussian_sigma(get_rho(usage)?, *sensitivity)
+            } else if let Some(proto::privacy_usage::Distance::Gaussian(_)) = usage.distance {
+                *sensitivity / get_mu(usage)?
+            } else if self.analytic {
+                let c: f64 = 2.0_f64 * (1.25_f64 / get_delta(usage)?).ln();
+                c.sqrt() * *sensitivity as f64 / get_epsilon(usage)?
+            } else {
+                get_analytic_gaussian_sigma(get_epsilon(usage)?, get_delta(usage)?, *sensitivity)
+            };
 
-                proto::Accuracy {
-                    value: sigma * 2.0_f64.sqrt() * erf::erf_inv(1.0_f64 - alpha),
-                    alpha
-                }
-            }).collect()))
+            Ok(proto::Accuracy {
+                value: sigma * 2.0_f64.sqrt() * erf::erf_inv(1.0_f64 - alpha),
+                alpha
+            })
+        }).collect::<Result<Vec<proto::Accuracy>>>()).transpose()
     }
 }
 
@@ -285,4 +319,70 @@ pub fn get_analytic_gaussian_sigma(epsilon: f64, delta: f64, sensitivity: f64) -
     };
 
     alpha * sensitivity / (2. * epsilon).sqrt()
-}
\ No newline at end of file
+}
+/// Standard deviation of gaussian noise that satisfies rho-zCDP.
+///
+/// Bun, Steinke. "Concentrated Differential Privacy: Simplifications, Extensions, and Lower Bounds." TCC 2016.
+pub fn get_concentrated_gaussian_sigma(rho: f64, sensitivity: f64) -> f64 {
+    sensitivity / (2. * rho).sqrt()
+}
+
+
+#[cfg(test)]
+mod test_gaussian_mechanism {
+    use crate::proto;
+    use crate::base::test_data;
+    use crate::components::resize::test_resize;
+    use crate::utilities::privacy::get_epsilon;
+
+    fn compute_usage(composition: proto::privacy_definition::Composition) -> proto::PrivacyUsage {
+        let (mut analysis, resized) = test_resize::utilities::analysis_f64_cont(
+            test_data::array1d_f64_10_uniform(), 10.into(), None, None);
+
+        let usage = proto::PrivacyUsage {
+            distance: Some(proto::privacy_usage::Distance::Approximate(proto::privacy_usage::DistanceApproximate {
+                epsilon: 0.1, delta: 1e-8,
+            }))
+        };
+        (0..20).for_each(|_| {
+            analysis.dp_mean(resized, vec![usage.clone()])
+                .mechanism("gaussian".to_string())
+                .build();
+        });
+
+        analysis.privacy_definition.composition = composition as i32;
+        analysis.privacy_definition.composition_delta = 1e-6;
+        analysis.privacy_definition.conversion_delta = 1e-6;
+        crate::compute_privacy_usage(
+            analysis.privacy_definition, analysis.components, analysis.release).unwrap()
+    }
+
+    #[test]
+    fn test_renyi_composition() {
+        use proto::privacy_definition::Composition;
+
+        let linear = get_epsilon(&compute_usage(Composition::Linear)).unwrap();
+        let renyi = get_epsilon(&compute_usage(Composition::Renyi)).unwrap();
+        assert!((linear - 2.).abs() < 1e-8);
+        assert!(renyi < linear);
+    }
+
+    #[test]
+    fn test_gaussian_composition() {
+        use proto::privacy_definition::Composition;
+
+        // gaussian composition is exact, so it is at least as tight as the other accountants
+        let renyi = get_epsilon(&compute_usage(Composition::Renyi)).unwrap();
+        let gaussian = get_epsilon(&compute_usage(Composition::Gaussian)).unwrap();
+        assert!(gaussian < renyi);
+    }
+
+    #[test]
+    fn test_privacy_loss_composition() {
+        use proto::privacy_definition::Composition;
+
+        let renyi = get_epsilon(&compute_usage(Composition::Renyi)).unwrap();
+        let privacy_loss = get_epsilon(&compute_usage(Composition::PrivacyLossDistribution)).unwrap();
+        assert!(privacy_loss < renyi);
+    }
+}
@@ -0,0 +1,159 @@
+use indexmap::map::IndexMap;
+
+use crate::{base, proto, Warnable};
+use crate::base::{Array, DataType, IndexKey, Nature, NatureContinuous, NodeProperties, Value, ValueProperties, Vector1DNull};
+use crate::components::{Component, Report};
+use crate::errors::*;
+use crate::utilities::{prepend, array::get_ith_column};
+use crate::utilities::json::{AlgorithmInfo, JSONRelease, privacy_usage_to_json, value_to_json};
+
+
+impl Component for proto::HistogramProjection {
+    fn propagate_property(
+        &self,
+        _privacy_definition: &Option<proto::PrivacyDefinition>,
+        _public_arguments: IndexMap<base::IndexKey, &Value>,
+        properties: base::NodeProperties,
+        node_id: u32
+    ) -> Result<Warnable<ValueProperties>> {
+        let mut data_property = properties.get::<IndexKey>(&"data".into())
+            .ok_or("data: missing")?.array()
+            .map_err(prepend("data:"))?.clone();
+
+        // projection is post-processing, so it may only be applied to released histograms
+        if !data_property.releasable {
+            return Err("data: must be privatized".into())
+        }
+        if data_property.data_type != DataType::Float && data_property.data_type != DataType::Int {
+            return Err("data: atomic type must be numeric".into())
+        }
+        let num_columns = data_property.num_columns()?;
+
+        if let Some(total_property) = properties.get::<IndexKey>(&"total".into()) {
+            let total_property = total_property.array()
+                .map_err(prepend("total:"))?;
+            if !total_property.releasable {
+                return Err("total: must be public or privatized".into())
+            }
+            if total_property.data_type != DataType::Float && total_property.data_type != DataType::Int {
+                return Err("total: atomic type must be numeric".into())
+            }
+            let num_totals = total_property.num_records()? * total_property.num_columns()?;
+            if num_totals != 1 && num_totals != num_columns {
+                return Err("total: must contain either one total, or one total per column of the data".into())
+            }
+        }
+
+        data_property.data_type = DataType::Float;
+        data_property.nature = Some(Nature::Continuous(NatureContinuous {
+            lower: Vector1DNull::Float((0..num_columns).map(|_| Some(0.)).collect()),
+            upper: Vector1DNull::Float((0..num_columns).map(|_| None).collect()),
+        }));
+        data_property.aggregator = None;
+        data_property.dataset_id = Some(node_id as i64);
+
+        Ok(ValueProperties::Array(data_property).into())
+    }
+}
+
+impl Report for proto::HistogramProjection {
+    fn summarize(
+        &self,
+        node_id: u32,
+        component: &proto::Component,
+        _public_arguments: IndexMap<base::IndexKey, &Value>,
+        properties: NodeProperties,
+        release: &Value,
+        variable_names: Option<&Vec<base::IndexKey>>,
+    ) -> Result<Option<Vec<JSONRelease>>> {
+        let data_property = properties.get::<IndexKey>(&"data".into())
+            .ok_or("data: missing")?.array()
+            .map_err(prepend("data:"))?.clone();
+
+        // post-processing of a release does not consume any additional budget
+        let privacy_usage = proto::PrivacyUsage {
+            distance: Some(proto::privacy_usage::Distance::Approximate(proto::privacy_usage::DistanceApproximate {
+                epsilon: 0., delta: 0.
+            }))
+        };
+
+        let mut releases = Vec::new();
+
+        for column_number in 0..(data_property.num_columns()? as usize) {
+            let variable_name = variable_names
+                .and_then(|names| names.get(column_number)).cloned()
+                .unwrap_or_else(|| "[Unknown]".into());
+
+            releases.push(JSONRelease {
+                description: "DP release information".to_string(),
+                statistic: "HistogramProjection".to_string(),
+                variables: serde_json::json!(variable_name.to_string()),
+                release_info: match release.ref_array()? {
+                    Array::Float(v) => value_to_json(&get_ith_column(v, column_number)?.into())?,
+                    _ => return Err("projected histogram must be float".into())
+                },
+                privacy_loss: privacy_usage_to_json(&privacy_usage),
+                accuracy: None,
+                submission: component.submission,
+                node_id,
+                postprocess: true,
+                algorithm_info: AlgorithmInfo {
+                    name: "Euclidean projection onto the simplex".to_string(),
+                    cite: "https://doi.org/10.1145/1390156.1390191".to_string(),
+                    mechanism: "".to_string(),
+                    argument: serde_json::json!({
+                        "total": component.arguments().contains_key::<IndexKey>(&"total".into())
+                    }),
+                },
+            });
+        }
+        Ok(Some(releases))
+    }
+}
+
+
+#[cfg(test)]
+mod test_histogram_projection {
+    use ndarray::arr1;
+
+    use crate::base::{test_data, Value};
+    use crate::base::test_usage::usage;
+    use crate::components::resize::test_resize;
+
+    #[test]
+    fn test_histogram_projection() {
+        let (mut analysis, resized) = test_resize::utilities::analysis_f64_cont_private(
+            test_data::array1d_f64_10_uniform(), 10.into());
+
+        let edges = analysis.literal()
+            .value(Value::Jagged(vec![vec![0., 5., 10.]].into()))
+            .value_public(true).build();
+        let lower = analysis.literal().value(0.into()).value_public(true).build();
+        let inclusive_left = analysis.literal().value(true.into()).value_public(true).build();
+        let histogram = analysis.dp_histogram(resized, lower, inclusive_left, vec![usage(1.)])
+            .edges(edges)
+            .mechanism("Laplace".to_string())
+            .build();
+        let total = analysis.literal()
+            .value(10.into())
+            .value_public(true).build();
+        let projected = analysis.histogram_projection(histogram)
+            .total(total)
+            .build();
+
+        let projected_property = analysis.properties(projected).unwrap().array().unwrap().clone();
+        assert!(projected_property.releasable);
+        assert_eq!(projected_property.lower_float().unwrap(), vec![0.]);
+
+        // the data is only post-processed if it has already been released
+        let unreleased = analysis.histogram_projection(resized).total(total).build();
+        assert!(analysis.properties(unreleased).is_err());
+
+        // the data has one column, so there may only be one total
+        let totals = analysis.literal()
+            .value(arr1(&[5., 5.]).into_dyn().into())
+            .value_public(true).build();
+        let mismatched = analysis.histogram_projection(histogram).total(totals).build();
+        assert!(analysis.properties(mismatched).is_err());
+    }
+}
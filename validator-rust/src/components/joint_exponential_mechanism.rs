@@ -0,0 +1,73 @@
+use indexmap::map::IndexMap;
+
+use crate::{base, Float, proto, Warnable};
+use crate::base::{IndexKey, NodeProperties, Value, ValueProperties};
+use crate::components::{Component, Expandable, Mechanism};
+use crate::components::continuous_exponential_mechanism::{expand_interval_mechanism, get_interval_privacy_usage, propagate_interval_property};
+use crate::errors::*;
+use crate::utilities::prepend;
+
+
+impl Component for proto::JointExponentialMechanism {
+    fn propagate_property(
+        &self,
+        privacy_definition: &Option<proto::PrivacyDefinition>,
+        _public_arguments: IndexMap<base::IndexKey, &Value>,
+        properties: base::NodeProperties,
+        node_id: u32
+    ) -> Result<Warnable<ValueProperties>> {
+        check_alphas(&self.alphas).map_err(prepend("alphas:"))?;
+        propagate_interval_property(privacy_definition, &self.privacy_usage, properties, node_id, self.alphas.len() as i64)
+    }
+}
+
+impl Expandable for proto::JointExponentialMechanism {
+    fn expand_component(
+        &self,
+        privacy_definition: &Option<proto::PrivacyDefinition>,
+        component: &proto::Component,
+        _public_arguments: &IndexMap<IndexKey, &Value>,
+        properties: &base::NodeProperties,
+        component_id: u32,
+        maximum_id: u32,
+    ) -> Result<base::ComponentExpansion> {
+        let num_columns = properties.get::<IndexKey>(&"data".into())
+            .ok_or("data: missing")?.array()
+            .map_err(prepend("data:"))?.num_columns()?;
+
+        // substituting a record changes the number of records in at most two of the gaps between quantiles by one,
+        //     and adding or removing a record changes the number in one gap by one, and the expected numbers by one in total
+        let sensitivity = ndarray::Array::from(vec![2. as Float; num_columns as usize]).into_dyn().into();
+
+        expand_interval_mechanism(
+            privacy_definition, &self.privacy_usage, sensitivity, component, properties, component_id, maximum_id)
+    }
+}
+
+impl Mechanism for proto::JointExponentialMechanism {
+    fn get_privacy_usage(
+        &self,
+        privacy_definition: &proto::PrivacyDefinition,
+        release_usage: Option<&Vec<proto::PrivacyUsage>>,
+        properties: &NodeProperties
+    ) -> Result<Option<Vec<proto::PrivacyUsage>>> {
+        get_interval_privacy_usage(
+            privacy_definition,
+            release_usage.unwrap_or_else(|| &self.privacy_usage),
+            properties)
+    }
+}
+
+/// Check that the quantiles to release jointly are non-empty, within [0, 1], and non-decreasing.
+pub fn check_alphas(alphas: &[f64]) -> Result<()> {
+    if alphas.is_empty() {
+        return Err("must contain at least one quantile".into())
+    }
+    if alphas.iter().any(|alpha| !(0. ..=1.).contains(alpha)) {
+        return Err("must be within [0, 1]".into())
+    }
+    if alphas.windows(2).any(|pair| pair[0] > pair[1]) {
+        return Err("must be in non-decreasing order".into())
+    }
+    Ok(())
+}
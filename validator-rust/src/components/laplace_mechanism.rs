@@ -6,7 +6,7 @@ use crate::base::{DataType, IndexKey, NodeProperties, SensitivitySpace, Value, V
 use crate::components::{Accuracy, Component, Expandable, Mechanism, Sensitivity};
 use crate::errors::*;
 use crate::utilities::{expand_mechanism, prepend};
-use crate::utilities::privacy::{get_epsilon, privacy_usage_check, spread_privacy_usage};
+use crate::utilities::privacy::{approximate_usage_check, get_epsilon, get_mechanism_privacy_usages, privacy_usage_check, LossModel, spread_privacy_usage};
 
 impl Component for proto::LaplaceMechanism {
     fn propagate_property(
@@ -52,6 +52,8 @@ impl Component for proto::LaplaceMechanism {
             data_property.num_records,
             privacy_definition.strict_parameter_checks)?;
 
+        approximate_usage_check(&privacy_usage)?;
+
         data_property.releasable = true;
         data_property.aggregator = None;
 
@@ -93,12 +95,12 @@ impl Mechanism for proto::LaplaceMechanism {
             .ok_or("data: missing")?.array()
             .map_err(prepend("data:"))?;
 
-        Some(release_usage.unwrap_or_else(|| &self.privacy_usage).iter()
-            .map(|usage| usage.effective_to_actual(
-                data_property.sample_proportion.unwrap_or(1.),
-                data_property.c_stability,
-                privacy_definition.group_size))
-            .collect::<Result<Vec<proto::PrivacyUsage>>>()).transpose()
+        get_mechanism_privacy_usages(
+            privacy_definition,
+            release_usage.unwrap_or_else(|| &self.privacy_usage),
+            data_property,
+            |usage, stability| Ok(LossModel::Laplace(get_epsilon(usage)? * stability)))
+            .map(Some)
     }
 }
 
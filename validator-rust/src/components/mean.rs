@@ -3,15 +3,15 @@ use crate::errors::*;
 use crate::{proto, base, Warnable, Float};
 
 use crate::components::{Component, Sensitivity};
-use crate::base::{Value, NodeProperties, AggregatorProperties, SensitivitySpace, ValueProperties, DataType, IndexKey};
-use crate::utilities::prepend;
+use crate::base::{Value, NodeProperties, AggregatorProperties, ArrayProperties, SensitivitySpace, ValueProperties, DataType, IndexKey};
+use crate::utilities::{get_pairwise_summation_gamma, prepend};
 use ndarray::prelude::*;
 use indexmap::map::IndexMap;
 
 impl Component for proto::Mean {
     fn propagate_property(
         &self,
-        _privacy_definition: &Option<proto::PrivacyDefinition>,
+        privacy_definition: &Option<proto::PrivacyDefinition>,
         _public_arguments: IndexMap<base::IndexKey, &Value>,
         properties: base::NodeProperties,
         node_id: u32
@@ -34,6 +34,12 @@ impl Component for proto::Mean {
             return Err("data: atomic type must be float".into())
         }
 
+        let protect_overflow = privacy_definition.as_ref()
+            .map(|v| v.protect_overflow).unwrap_or(false);
+        if protect_overflow && !data_property.releasable {
+            assert_no_overflow(&data_property).map_err(prepend("data:"))?;
+        }
+
         data_property.num_records = Some(1);
         data_property.dataset_id = Some(node_id as i64);
 
@@ -45,7 +51,7 @@ impl Sensitivity for proto::Mean {
     /// Mean sensitivities [are backed by the the proofs here](https://github.com/opendifferentialprivacy/whitenoise-core/blob/955703e3d80405d175c8f4642597ccdf2c00332a/whitepapers/sensitivities/mean/mean.pdf).
     fn compute_sensitivity(
         &self,
-        _privacy_definition: &proto::PrivacyDefinition,
+        privacy_definition: &proto::PrivacyDefinition,
         properties: &NodeProperties,
         sensitivity_type: &SensitivitySpace,
     ) -> Result<Value> {
@@ -57,6 +63,9 @@ impl Sensitivity for proto::Mean {
 
                 data_property.assert_non_null()?;
                 data_property.assert_is_not_aggregated()?;
+                if privacy_definition.protect_overflow {
+                    assert_no_overflow(&data_property)?;
+                }
                 let data_lower = data_property.lower_float()?;
                 let data_upper = data_property.upper_float()?;
                 let data_n = data_property.num_records()? as Float;
@@ -71,6 +80,15 @@ impl Sensitivity for proto::Mean {
                     _ => return Err("KNorm sensitivity is only supported in L1 and L2 spaces".into())
                 };
 
+                // the runtime sums floats pairwise and then divides, with an error proportional to the largest magnitude
+                let row_sensitivity = if privacy_definition.protect_floating_point {
+                    let gamma = get_pairwise_summation_gamma(data_property.num_records()?, 1)?;
+                    row_sensitivity.into_iter()
+                        .zip(data_lower.iter().zip(data_upper.iter()))
+                        .map(|(sensitivity, (min, max))| sensitivity + 2. * gamma * min.abs().max(max.abs()))
+                        .collect::<Vec<Float>>()
+                } else { row_sensitivity };
+
                 let mut array_sensitivity = Array::from(row_sensitivity).into_dyn();
                 array_sensitivity.insert_axis_inplace(Axis(0));
 
@@ -79,4 +97,9 @@ impl Sensitivity for proto::Mean {
             _ => Err("Mean sensitivity is only implemented for KNorm".into())
         }
     }
+}
+
+/// Ensure that the running sum of each column cannot overflow.
+fn assert_no_overflow(data_property: &ArrayProperties) -> Result<()> {
+    data_property.assert_no_float_overflow(|lower, upper| lower.abs().max(upper.abs()))
 }
\ No newline at end of file
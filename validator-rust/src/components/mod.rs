@@ -12,12 +12,17 @@ use crate::errors::*;
 
 mod transforms;
 //mod bin;
+mod above_threshold;
 mod cast;
 mod clamp;
+pub mod continual_count;
+mod continuous_exponential_mechanism;
 mod count;
 mod covariance;
 mod column_bind;
 mod digitize;
+pub mod discrete_gaussian_mechanism;
+mod dp_bounds;
 mod dp_count;
 mod dp_variance;
 mod dp_covariance;
@@ -29,25 +34,36 @@ mod dp_median;
 mod dp_minimum;
 mod dp_mean;
 mod dp_quantile;
+mod dp_quantiles;
+mod dp_range_queries;
 mod dp_raw_moment;
 mod dp_sum;
 mod filter;
+mod frequency_oracles;
 mod histogram;
+mod histogram_projection;
 mod impute;
+mod joint_exponential_mechanism;
 pub mod index;
 mod raw_moment;
 mod literal;
 mod map;
 mod materialize;
 pub mod partition;
+mod poisson_sample;
 mod quantile;
+mod randomized_response;
 mod reshape;
 mod mean;
 mod exponential_mechanism;
 pub mod gaussian_mechanism;
 mod laplace_mechanism;
+mod permute_and_flip_mechanism;
+mod report_noisy_max_mechanism;
 mod simple_geometric_mechanism;
 pub mod snapping_mechanism;
+pub mod sparse_vector;
+mod stability_histogram;
 mod resize;
 mod theil_sen;
 mod to_dataframe;
@@ -261,12 +277,17 @@ impl Component for proto::Component {
 
         propagate_property!(
             // INSERT COMPONENT LIST
-            Cast, Clamp, ColumnBind, Count, Covariance, Digitize,
-            Filter, Histogram, Impute, Index, Literal, Materialize, Mean,
-            Partition, Quantile, RawMoment, Reshape, Resize, Sum, ToDataframe, Union, Variance,
+            Cast, Clamp, ColumnBind, ContinualCount, Count, Covariance, Digitize, DyadicSum,
+            DpRangeQueries, Filter, Histogram, HistogramProjection, Impute, Index, Literal, Materialize, Mean,
+            Partition, PoissonSample, Quantile, RawMoment, Reshape, Resize, Sum, ToDataframe, Union, Variance,
 
-            ExponentialMechanism, GaussianMechanism, LaplaceMechanism,
-            SimpleGeometricMechanism, SnappingMechanism,
+            ContinuousExponentialMechanism, DiscreteGaussianMechanism, ExponentialMechanism, GaussianMechanism,
+            JointExponentialMechanism, LaplaceMechanism, PermuteAndFlipMechanism, RandomizedResponse, RandomizedResponseEstimate,
+            ReportNoisyMaxMechanism, SimpleGeometricMechanism, SnappingMechanism, SparseVector, StabilityHistogram,
+            DpBounds,
+
+            HadamardResponse, HadamardResponseEstimate, OptimizedLocalHashing, OptimizedLocalHashingEstimate,
+            OptimizedUnaryEncoding, OptimizedUnaryEncodingEstimate,
 
             Abs, Add, LogicalAnd, Divide, Equal, GreaterThan, LessThan, Log, Modulo, Multiply,
             Negate, Negative, LogicalOr, Power, RowMax, RowMin, Subtract, TheilSen, DpGumbelMedian
@@ -337,13 +358,18 @@ impl Expandable for proto::Component {
 
         expand_component!(
             // INSERT COMPONENT LIST
-            Clamp, Digitize, Histogram, Impute, Map, Maximum, Median, Minimum, Partition, Resize,
+            Clamp, ContinualCount, Count, Digitize, Filter, Histogram, Impute, Map, Maximum, Median, Minimum, Partition,
+            PoissonSample, Resize,
 
             DpCount, DpCovariance, DpHistogram, DpLinearRegression, DpMaximum, DpMean, DpMedian,
-            DpMinimum, DpQuantile, DpRawMoment, DpSum, DpVariance,
+            DpMinimum, DpQuantile, DpQuantiles, DpRangeQueries, DpRawMoment, DpSum, DpVariance,
+
+            AboveThreshold, ContinuousExponentialMechanism, DiscreteGaussianMechanism, ExponentialMechanism,
+            GaussianMechanism, JointExponentialMechanism, LaplaceMechanism, PermuteAndFlipMechanism, RandomizedResponse,
+            RandomizedResponseEstimate, ReportNoisyMaxMechanism, SimpleGeometricMechanism, SnappingMechanism, SparseVector, DpGumbelMedian,
+            DpBounds,
 
-            ExponentialMechanism, GaussianMechanism, LaplaceMechanism,
-            SimpleGeometricMechanism, SnappingMechanism, DpGumbelMedian,
+            HadamardResponse, OptimizedLocalHashing, OptimizedUnaryEncoding,
 
             ToBool, ToFloat, ToInt, ToString
         );
@@ -379,8 +405,11 @@ impl Mechanism for proto::Component {
 
         get_privacy_usage!(
             // INSERT COMPONENT LIST
-            ExponentialMechanism, GaussianMechanism, LaplaceMechanism,
-            SimpleGeometricMechanism, SnappingMechanism
+            ContinuousExponentialMechanism, DiscreteGaussianMechanism, DpBounds, DpGumbelMedian, ExponentialMechanism,
+            GaussianMechanism, JointExponentialMechanism, LaplaceMechanism,
+            HadamardResponse, OptimizedLocalHashing, OptimizedUnaryEncoding,
+            PermuteAndFlipMechanism, RandomizedResponse, ReportNoisyMaxMechanism,
+            SimpleGeometricMechanism, SnappingMechanism, SparseVector, StabilityHistogram
         );
 
         Ok(None)
@@ -413,7 +442,7 @@ impl Sensitivity for proto::component::Variant {
 
         compute_sensitivity!(
             // INSERT COMPONENT LIST
-            Count, Covariance, Histogram, Mean, Quantile, RawMoment, Sum, Union, Variance
+            Count, Covariance, DyadicSum, Histogram, Mean, Quantile, RawMoment, Sum, Union, Variance
         );
 
         Err(format!("sensitivity is not implemented for proto component {:?}", self).into())
@@ -528,8 +557,8 @@ impl Report for proto::Component {
 
         summarize!(
             // INSERT COMPONENT LIST
-            DpCount, DpCovariance, DpHistogram, DpMaximum, DpMean, DpMinimum, DpQuantile,
-            DpRawMoment, DpSum, DpVariance
+            DpBounds, DpCount, DpCovariance, DpHistogram, DpMaximum, DpMean, DpMinimum, DpQuantile, DpQuantiles,
+            DpRawMoment, DpSum, DpVariance, HistogramProjection
         );
 
         Ok(None)
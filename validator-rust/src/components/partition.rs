@@ -4,7 +4,7 @@ use crate::{proto, base, Warnable, Integer};
 
 use crate::components::{Component, Expandable};
 use crate::base::{IndexKey, Value, Jagged, ValueProperties, ArrayProperties, NodeProperties, PartitionsProperties};
-use crate::utilities::{prepend, get_literal, get_argument};
+use crate::utilities::{prepend, get_literal, get_argument, get_number_records_argument};
 use indexmap::map::IndexMap;
 use itertools::Itertools;
 use crate::utilities::inference::infer_property;
@@ -25,6 +25,15 @@ impl Component for proto::Partition {
             .ok_or_else(|| Error::from("privacy_definition must be defined"))?.neighboring)
             .ok_or_else(|| Error::from("neighboring must be defined"))?;
 
+        if privacy_definition.as_ref().map(|v| v.protect_memory_utilization).unwrap_or(false) && !data_property.is_public() {
+            match &data_property {
+                ValueProperties::Array(data_property) => data_property.assert_num_records_known(),
+                ValueProperties::Dataframe(data_property) => data_property.num_records()?
+                    .map(|_| ()).ok_or_else(|| "number of records must be known to protect memory utilization".into()),
+                _ => Err("must be a dataframe or array".into())
+            }.map_err(prepend("data:"))?;
+        }
+
         Ok(ValueProperties::Partitions(match properties.get::<IndexKey>(&"by".into()) {
 
             // propagate properties when partitioning "by" some array
@@ -83,15 +92,16 @@ impl Component for proto::Partition {
 impl Expandable for proto::Partition {
     fn expand_component(
         &self,
-        _privacy_definition: &Option<proto::PrivacyDefinition>,
+        privacy_definition: &Option<proto::PrivacyDefinition>,
         component: &proto::Component,
-        _public_arguments: &IndexMap<IndexKey, &Value>,
+        public_arguments: &IndexMap<IndexKey, &Value>,
         properties: &NodeProperties,
         component_id: u32,
         mut maximum_id: u32
     ) -> Result<base::ComponentExpansion> {
 
         let mut expansion = base::ComponentExpansion::default();
+        let mut component = component.clone();
 
         if let Some(by) = properties.get::<IndexKey>(&"by".into()) {
             if !properties.contains_key::<IndexKey>(&"categories".into()) {
@@ -103,8 +113,21 @@ impl Expandable for proto::Partition {
                 expansion.properties.insert(id_categories, infer_property(&release.value, None, id_categories)?);
                 expansion.releases.insert(id_categories, release);
 
-                let mut component = component.clone();
                 component.insert_argument(&"categories".into(), id_categories);
+                expansion.computation_graph.insert(component_id, component.clone());
+            }
+
+            // partition buffers in the runtime are sized from the public number of records, instead of from the data
+            if let Some(value) = get_number_records_argument(
+                privacy_definition, &component, public_arguments, properties, "data")? {
+                maximum_id += 1;
+                let id_number_records = maximum_id;
+                let (patch_node, release) = get_literal(value, component.submission)?;
+                expansion.computation_graph.insert(id_number_records, patch_node);
+                expansion.properties.insert(id_number_records, infer_property(&release.value, None, id_number_records)?);
+                expansion.releases.insert(id_number_records, release);
+
+                component.insert_argument(&"number_records".into(), id_number_records);
                 expansion.computation_graph.insert(component_id, component);
             }
         }
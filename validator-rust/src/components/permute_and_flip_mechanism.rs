@@ -0,0 +1,51 @@
+use indexmap::map::IndexMap;
+
+use crate::{base, proto, Warnable};
+use crate::base::{IndexKey, NodeProperties, Value, ValueProperties};
+use crate::components::{Component, Expandable, Mechanism};
+use crate::components::exponential_mechanism::{expand_selection_mechanism, get_selection_privacy_usage, propagate_selection_property};
+use crate::errors::*;
+use crate::utilities::privacy::LossModel;
+
+impl Component for proto::PermuteAndFlipMechanism {
+    fn propagate_property(
+        &self,
+        privacy_definition: &Option<proto::PrivacyDefinition>,
+        _public_arguments: IndexMap<base::IndexKey, &Value>,
+        properties: base::NodeProperties,
+        node_id: u32,
+    ) -> Result<Warnable<ValueProperties>> {
+        propagate_selection_property(privacy_definition, &self.privacy_usage, properties, node_id)
+    }
+}
+
+impl Expandable for proto::PermuteAndFlipMechanism {
+    fn expand_component(
+        &self,
+        privacy_definition: &Option<proto::PrivacyDefinition>,
+        component: &proto::Component,
+        _public_arguments: &IndexMap<IndexKey, &Value>,
+        properties: &base::NodeProperties,
+        component_id: u32,
+        maximum_id: u32,
+    ) -> Result<base::ComponentExpansion> {
+        expand_selection_mechanism(
+            privacy_definition, &self.privacy_usage, component, properties, component_id, maximum_id)
+    }
+}
+
+impl Mechanism for proto::PermuteAndFlipMechanism {
+    fn get_privacy_usage(
+        &self,
+        privacy_definition: &proto::PrivacyDefinition,
+        release_usage: Option<&Vec<proto::PrivacyUsage>>,
+        properties: &NodeProperties,
+    ) -> Result<Option<Vec<proto::PrivacyUsage>>> {
+        get_selection_privacy_usage(
+            privacy_definition,
+            release_usage.unwrap_or_else(|| &self.privacy_usage),
+            properties,
+            "utilities",
+            LossModel::Pure)
+    }
+}
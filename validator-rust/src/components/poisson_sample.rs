@@ -0,0 +1,142 @@
+use crate::errors::*;
+
+use crate::components::{Component, Expandable};
+use crate::base::{IndexKey, Value, ValueProperties};
+use crate::utilities::{prepend, get_literal, get_number_records_argument};
+use crate::utilities::inference::infer_property;
+use crate::{base, Warnable};
+use crate::proto;
+use indexmap::map::IndexMap;
+
+impl Component for proto::PoissonSample {
+    fn propagate_property(
+        &self,
+        privacy_definition: &Option<proto::PrivacyDefinition>,
+        _public_arguments: IndexMap<base::IndexKey, &Value>,
+        properties: base::NodeProperties,
+        node_id: u32
+    ) -> Result<Warnable<ValueProperties>> {
+        let mut data_property = properties.get::<base::IndexKey>(&"data".into())
+            .ok_or("data: missing")?.array()
+            .map_err(prepend("data:"))?.clone();
+
+        data_property.assert_is_not_aggregated()?;
+
+        let protect_memory_utilization = privacy_definition.as_ref()
+            .map(|v| v.protect_memory_utilization).unwrap_or(false);
+        if protect_memory_utilization && !data_property.releasable {
+            data_property.assert_num_records_known().map_err(prepend("data:"))?;
+        }
+
+        if self.sample_proportion <= 0. || self.sample_proportion > 1. {
+            return Err("sample_proportion: must be within (0, 1]".into())
+        }
+
+        if data_property.sample_proportion.is_some() {
+            return Err("multiple samplings is not currently supported".into())
+        }
+        data_property.sample_proportion = Some(self.sample_proportion);
+
+        // the number of records is not known after sampling rows
+        data_property.num_records = None;
+
+        // This exists to prevent binary ops on non-conformable arrays from being approved
+        data_property.dataset_id = Some(node_id as i64);
+
+        // no longer know if the data has a nonzero number of records
+        data_property.is_not_empty = false;
+
+        Ok(ValueProperties::Array(data_property).into())
+    }
+}
+
+impl Expandable for proto::PoissonSample {
+    fn expand_component(
+        &self,
+        privacy_definition: &Option<proto::PrivacyDefinition>,
+        component: &proto::Component,
+        public_arguments: &IndexMap<IndexKey, &Value>,
+        properties: &base::NodeProperties,
+        component_id: u32,
+        mut maximum_id: u32,
+    ) -> Result<base::ComponentExpansion> {
+        let mut expansion = base::ComponentExpansion::default();
+
+        // buffers in the runtime are sized from the public number of records, instead of from the data
+        if let Some(value) = get_number_records_argument(
+            privacy_definition, component, public_arguments, properties, "data")? {
+            maximum_id += 1;
+            let id_number_records = maximum_id;
+            let (patch_node, release) = get_literal(value, component.submission)?;
+            expansion.computation_graph.insert(id_number_records, patch_node);
+            expansion.properties.insert(id_number_records, infer_property(&release.value, None, id_number_records)?);
+            expansion.releases.insert(id_number_records, release);
+
+            let mut component = component.clone();
+            component.insert_argument(&"number_records".into(), id_number_records);
+            expansion.computation_graph.insert(component_id, component);
+        }
+
+        Ok(expansion)
+    }
+}
+
+#[cfg(test)]
+mod test_poisson_sample {
+    use crate::proto;
+    use crate::base::test_data;
+    use crate::components::Expandable;
+    use crate::components::resize::test_resize;
+    use crate::utilities::privacy::get_epsilon;
+    use crate::base::test_usage::{approximate_usage, usage};
+
+    #[test]
+    fn test_poisson_sample() {
+        let (mut analysis, resized) = test_resize::utilities::analysis_f64_cont(
+            test_data::array1d_f64_10_uniform(), 10.into(), None, None);
+
+        let sampled = analysis.poisson_sample(resized, 0.1).build();
+        let sampled_property = analysis.properties(sampled).unwrap().array().unwrap().clone();
+        assert_eq!(sampled_property.sample_proportion, Some(0.1));
+        assert!(sampled_property.num_records.is_none());
+
+        let count = analysis.count(sampled).build();
+        let laplace = analysis.laplace_mechanism(count, vec![usage(1.)]).build();
+
+        // the mechanism on the sample is amplified, so it may spend a larger effective epsilon
+        let properties = indexmap!["data".into() => analysis.properties(count).unwrap()];
+        let component = analysis.components.get(&laplace).unwrap().clone();
+        let expansion = match component.variant.as_ref() {
+            Some(proto::component::Variant::LaplaceMechanism(variant)) => variant.expand_component(
+                &Some(analysis.privacy_definition.clone()), &component,
+                &indexmap![], &properties, laplace, laplace + 1).unwrap(),
+            _ => panic!("the component must be a laplace mechanism")
+        };
+        match expansion.computation_graph.get(&laplace).and_then(|v| v.variant.as_ref()) {
+            Some(proto::component::Variant::LaplaceMechanism(variant)) =>
+                assert!(get_epsilon(&variant.privacy_usage[0]).unwrap() > 1.),
+            _ => panic!("the expanded component must be a laplace mechanism")
+        }
+    }
+
+    #[test]
+    fn test_amplification() {
+        use proto::privacy_definition::Neighboring;
+
+        for neighboring in &[Neighboring::AddRemove, Neighboring::Substitute] {
+            let privacy_definition = proto::PrivacyDefinition {
+                group_size: 1,
+                neighboring: *neighboring as i32,
+                ..Default::default()
+            };
+
+            // mechanisms on a sample may spend more than the actual usage
+            let effective = approximate_usage(1., 1e-6).actual_to_effective(0.1, 2, &privacy_definition).unwrap();
+            assert!(get_epsilon(&effective).unwrap() > 0.5);
+
+            let actual = effective.effective_to_actual(0.1, 2, &privacy_definition).unwrap();
+            assert!((get_epsilon(&actual).unwrap() - 1.).abs() < 1e-8);
+            assert!((crate::utilities::privacy::get_delta(&actual).unwrap() - 1e-6).abs() < 1e-14);
+        }
+    }
+}
@@ -0,0 +1,301 @@
+use indexmap::map::IndexMap;
+use itertools::Itertools;
+use ndarray::arr1;
+
+use crate::{base, proto, Warnable};
+use crate::base::{ArrayProperties, DataType, IndexKey, Jagged, NodeProperties, Value, ValueProperties};
+use crate::components::{Component, Expandable, Mechanism};
+use crate::errors::*;
+use crate::utilities::{get_literal, prepend};
+use crate::utilities::inference::infer_property;
+use crate::utilities::privacy::{approximate_usage_check, get_epsilon, get_mechanism_privacy_usages, privacy_usage_check, LossModel};
+
+
+impl Component for proto::RandomizedResponse {
+    fn propagate_property(
+        &self,
+        privacy_definition: &Option<proto::PrivacyDefinition>,
+        _public_arguments: IndexMap<base::IndexKey, &Value>,
+        properties: base::NodeProperties,
+        _node_id: u32
+    ) -> Result<Warnable<ValueProperties>> {
+        let privacy_definition = privacy_definition.as_ref()
+            .ok_or_else(|| "privacy_definition must be defined")?;
+
+        let mut data_property = properties.get::<IndexKey>(&"data".into())
+            .ok_or("data: missing")?.array()
+            .map_err(prepend("data:"))?.clone();
+
+        let warnings = propagate_local_property(privacy_definition, &self.privacy_usage, &data_property)?;
+        get_num_local_categories(&data_property)?;
+
+        data_property.releasable = true;
+
+        Ok(Warnable(data_property.into(), warnings))
+    }
+}
+
+impl Expandable for proto::RandomizedResponse {
+    fn expand_component(
+        &self,
+        _privacy_definition: &Option<proto::PrivacyDefinition>,
+        component: &proto::Component,
+        public_arguments: &IndexMap<IndexKey, &Value>,
+        properties: &base::NodeProperties,
+        component_id: u32,
+        maximum_id: u32,
+    ) -> Result<base::ComponentExpansion> {
+        expand_local_categories(component, public_arguments, properties, component_id, maximum_id)
+    }
+}
+
+impl Mechanism for proto::RandomizedResponse {
+    fn get_privacy_usage(
+        &self,
+        privacy_definition: &proto::PrivacyDefinition,
+        release_usage: Option<&Vec<proto::PrivacyUsage>>,
+        properties: &NodeProperties
+    ) -> Result<Option<Vec<proto::PrivacyUsage>>> {
+        get_local_privacy_usage(
+            privacy_definition,
+            release_usage.unwrap_or_else(|| &self.privacy_usage),
+            properties)
+    }
+}
+
+
+impl Component for proto::RandomizedResponseEstimate {
+    fn propagate_property(
+        &self,
+        _privacy_definition: &Option<proto::PrivacyDefinition>,
+        public_arguments: IndexMap<base::IndexKey, &Value>,
+        properties: base::NodeProperties,
+        node_id: u32
+    ) -> Result<Warnable<ValueProperties>> {
+        let data_property = properties.get::<IndexKey>(&"data".into())
+            .ok_or("data: missing")?.array()
+            .map_err(prepend("data:"))?.clone();
+
+        propagate_local_estimate_property(&self.privacy_usage, public_arguments, data_property, node_id)
+    }
+}
+
+impl Expandable for proto::RandomizedResponseEstimate {
+    fn expand_component(
+        &self,
+        _privacy_definition: &Option<proto::PrivacyDefinition>,
+        component: &proto::Component,
+        public_arguments: &IndexMap<IndexKey, &Value>,
+        properties: &base::NodeProperties,
+        component_id: u32,
+        maximum_id: u32,
+    ) -> Result<base::ComponentExpansion> {
+        expand_local_categories(component, public_arguments, properties, component_id, maximum_id)
+    }
+}
+
+
+/// Check the properties shared by local perturbations of categorical data.
+///
+/// Every row is perturbed independently, so the number of rows is released unperturbed.
+/// Under AddRemove neighboring the number of rows must already be public.
+pub fn propagate_local_property(
+    privacy_definition: &proto::PrivacyDefinition,
+    privacy_usage: &[proto::PrivacyUsage],
+    data_property: &ArrayProperties
+) -> Result<Vec<Error>> {
+    if privacy_definition.group_size == 0 {
+        return Err("group size must be greater than zero".into())
+    }
+
+    data_property.assert_is_not_aggregated()?;
+    data_property.assert_non_null()?;
+    if data_property.releasable {
+        return Err("data: is already public".into())
+    }
+
+    use proto::privacy_definition::Neighboring;
+    let neighboring_type = Neighboring::from_i32(privacy_definition.neighboring)
+        .ok_or_else(|| Error::from("neighboring definition must be either \"AddRemove\" or \"Substitute\""))?;
+    if neighboring_type == Neighboring::AddRemove {
+        data_property.assert_num_records_known()
+            .map_err(prepend("data: the number of records is released, so"))?;
+    }
+
+    let privacy_usage = privacy_usage.iter().cloned().map(Ok)
+        .fold1(|l, r| l? + r?).ok_or_else(|| "privacy_usage: must be defined")??;
+
+    let warnings = privacy_usage_check(
+        &privacy_usage,
+        data_property.num_records,
+        privacy_definition.strict_parameter_checks)?;
+
+    approximate_usage_check(&privacy_usage)?;
+
+    Ok(warnings)
+}
+
+/// Derive the properties of counts estimated from a local perturbation.
+///
+/// The estimates are post-processing of a release, so they are releasable.
+/// There is one estimate per category, in the order of the categories.
+pub fn propagate_local_estimate_property(
+    privacy_usage: &[proto::PrivacyUsage],
+    public_arguments: IndexMap<base::IndexKey, &Value>,
+    mut data_property: ArrayProperties,
+    node_id: u32
+) -> Result<Warnable<ValueProperties>> {
+    if !data_property.releasable {
+        return Err("data: must be the release of a local perturbation".into())
+    }
+
+    let privacy_usage = privacy_usage.iter().cloned().map(Ok)
+        .fold1(|l, r| l? + r?).ok_or_else(|| "privacy_usage: must be defined")??;
+    approximate_usage_check(&privacy_usage)?;
+
+    let num_categories = match public_arguments.get::<IndexKey>(&"categories".into()) {
+        Some(categories) => categories.ref_array()?.num_records()? as i64,
+        None => get_num_local_categories(&data_property)?
+    };
+
+    data_property.num_records = Some(num_categories);
+    data_property.num_columns = Some(1);
+    data_property.dimensionality = Some(1);
+    data_property.data_type = DataType::Float;
+    data_property.nature = None;
+    data_property.nullity = false;
+    data_property.is_not_empty = num_categories > 0;
+    data_property.dataset_id = Some(node_id as i64);
+
+    Ok(ValueProperties::Array(data_property).into())
+}
+
+/// Retrieve the number of categories of a single-column, categorical dataset.
+pub fn get_num_local_categories(data_property: &ArrayProperties) -> Result<i64> {
+    if data_property.num_columns()? != 1 {
+        return Err("data: must contain one column".into())
+    }
+    let num_categories = data_property.categories()
+        .map_err(prepend("data:"))?.num_records()[0];
+    if num_categories < 2 {
+        return Err("data: must have at least two categories".into())
+    }
+    Ok(num_categories)
+}
+
+/// Add the categories of the data as a public argument, clamping the data first if jagged categories are passed.
+pub fn expand_local_categories(
+    component: &proto::Component,
+    public_arguments: &IndexMap<IndexKey, &Value>,
+    properties: &base::NodeProperties,
+    component_id: u32,
+    mut maximum_id: u32,
+) -> Result<base::ComponentExpansion> {
+    let mut expansion = base::ComponentExpansion::default();
+    let mut component = component.clone();
+
+    let data_property = properties.get::<IndexKey>(&"data".into())
+        .ok_or("data: missing")?.array()
+        .map_err(prepend("data:"))?.clone();
+
+    match public_arguments.get::<IndexKey>(&"categories".into()) {
+        // categories are already in the form used by the runtime
+        Some(Value::Array(_)) => return Ok(expansion),
+        Some(_) => (),
+        None => if component.arguments().contains_key::<IndexKey>(&"categories".into()) {
+            return Err("categories: must be public".into())
+        }
+    }
+
+    if let Some(categories_id) = component.arguments().get::<IndexKey>(&"categories".into()) {
+        let data_id = component.arguments().get::<IndexKey>(&"data".into())
+            .ok_or_else(|| Error::from("data is a required argument"))?.to_owned();
+        let null_id = component.arguments().get::<IndexKey>(&"null_value".into())
+            .ok_or_else(|| Error::from("null_value is a required argument when categories are passed"))?.to_owned();
+
+        maximum_id += 1;
+        let id_clamp = maximum_id;
+        expansion.computation_graph.insert(id_clamp, proto::Component {
+            arguments: Some(proto::ArgumentNodeIds::new(indexmap![
+                "data".into() => data_id,
+                "categories".into() => *categories_id,
+                "null_value".into() => null_id
+            ])),
+            variant: Some(proto::component::Variant::Clamp(proto::Clamp {})),
+            omit: true,
+            submission: component.submission,
+        });
+        component.arguments = Some(proto::ArgumentNodeIds::new(indexmap!["data".into() => id_clamp]));
+        expansion.traversal.push(id_clamp);
+    } else {
+        maximum_id += 1;
+        let id_categories = maximum_id;
+        let value = match data_property.categories().map_err(prepend("data:"))? {
+            Jagged::Int(jagged) => arr1(&jagged[0]).into_dyn().into(),
+            Jagged::Float(jagged) => arr1(&jagged[0]).into_dyn().into(),
+            Jagged::Bool(jagged) => arr1(&jagged[0]).into_dyn().into(),
+            Jagged::Str(jagged) => arr1(&jagged[0]).into_dyn().into(),
+        };
+        let (patch_node, categories_release) = get_literal(value, component.submission)?;
+        expansion.computation_graph.insert(id_categories, patch_node);
+        expansion.properties.insert(id_categories, infer_property(&categories_release.value, None, id_categories)?);
+        expansion.releases.insert(id_categories, categories_release);
+        component.insert_argument(&"categories".into(), id_categories);
+    }
+
+    expansion.computation_graph.insert(component_id, component);
+    Ok(expansion)
+}
+
+/// Each row is released under epsilon-DP in the local model, so the whole release is epsilon-DP.
+pub fn get_local_privacy_usage(
+    privacy_definition: &proto::PrivacyDefinition,
+    usages: &[proto::PrivacyUsage],
+    properties: &NodeProperties
+) -> Result<Option<Vec<proto::PrivacyUsage>>> {
+    let data_property = properties.get::<IndexKey>(&"data".into())
+        .ok_or("data: missing")?.array()
+        .map_err(prepend("data:"))?;
+
+    get_mechanism_privacy_usages(
+        privacy_definition,
+        usages,
+        data_property,
+        |usage, stability| Ok(LossModel::Pure(get_epsilon(usage)? * stability)))
+        .map(Some)
+}
+
+
+#[cfg(test)]
+mod test_randomized_response {
+    use crate::base::{DataType, Value};
+    use crate::components::randomized_response::get_num_local_categories;
+    use crate::components::resize::test_resize;
+    use crate::base::test_usage::usage;
+
+    #[test]
+    fn test_randomized_response() {
+        let (mut analysis, resized) = test_resize::utilities::analysis_i64_cat_private(
+            ndarray::arr1(&[0, 1, 2, 1, 0, 2, 2, 1, 0, 1]).into(), 10.into(),
+            Value::Jagged(vec![vec![0, 1, 2]].into()));
+
+        let perturbed = analysis.randomized_response(resized, vec![usage(1.)]).build();
+        let estimate = analysis.randomized_response_estimate(perturbed, vec![usage(1.)]).build();
+
+        // the perturbed rows are released with the same categories
+        let resized_property = analysis.properties(resized).unwrap().array().unwrap().clone();
+        let num_categories = get_num_local_categories(&resized_property).unwrap();
+        let perturbed_property = analysis.properties(perturbed).unwrap().array().unwrap().clone();
+        assert!(perturbed_property.releasable);
+        assert_eq!(get_num_local_categories(&perturbed_property).unwrap(), num_categories);
+
+        // there is one estimated count per category
+        let estimate_property = analysis.properties(estimate).unwrap().array().unwrap().clone();
+        assert_eq!(estimate_property.num_records, Some(num_categories));
+        assert_eq!(estimate_property.data_type, DataType::Float);
+
+        // released rows may not be perturbed again
+        let reperturbed = analysis.randomized_response(perturbed, vec![usage(1.)]).build();
+        assert!(analysis.properties(reperturbed).is_err());
+    }
+}
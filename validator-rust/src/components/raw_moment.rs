@@ -3,7 +3,7 @@ use crate::errors::*;
 use crate::{proto, base, Warnable, Float};
 
 use crate::components::{Component, Sensitivity};
-use crate::base::{Value, NodeProperties, AggregatorProperties, SensitivitySpace, ValueProperties, DataType};
+use crate::base::{Value, NodeProperties, AggregatorProperties, ArrayProperties, SensitivitySpace, ValueProperties, DataType};
 use crate::utilities::prepend;
 use ndarray::prelude::*;
 use std::convert::TryFrom;
@@ -12,7 +12,7 @@ use indexmap::map::IndexMap;
 impl Component for proto::RawMoment {
     fn propagate_property(
         &self,
-        _privacy_definition: &Option<proto::PrivacyDefinition>,
+        privacy_definition: &Option<proto::PrivacyDefinition>,
         _public_arguments: IndexMap<base::IndexKey, &Value>,
         properties: base::NodeProperties,
         node_id: u32
@@ -30,6 +30,12 @@ impl Component for proto::RawMoment {
         }
         data_property.assert_is_not_empty()?;
 
+        let protect_overflow = privacy_definition.as_ref()
+            .map(|v| v.protect_overflow).unwrap_or(false);
+        if protect_overflow && !data_property.releasable {
+            assert_no_overflow(&data_property, self.order).map_err(prepend("data:"))?;
+        }
+
         let num_columns = data_property.num_columns()?;
         // save a snapshot of the state when aggregating
         data_property.aggregator = Some(AggregatorProperties::new(
@@ -43,7 +49,7 @@ impl Component for proto::RawMoment {
 impl Sensitivity for proto::RawMoment {
     fn compute_sensitivity(
         &self,
-        _privacy_definition: &proto::PrivacyDefinition,
+        privacy_definition: &proto::PrivacyDefinition,
         properties: &NodeProperties,
         sensitivity_type: &SensitivitySpace
     ) -> Result<Value> {
@@ -51,6 +57,10 @@ impl Sensitivity for proto::RawMoment {
             .ok_or("data: missing")?.array()
             .map_err(prepend("data:"))?.clone();
 
+        if privacy_definition.protect_overflow {
+            assert_no_overflow(&data_property, self.order)?;
+        }
+
         match sensitivity_type {
             SensitivitySpace::KNorm(k) => {
                 let k = i32::try_from(*k)?;
@@ -72,4 +82,10 @@ impl Sensitivity for proto::RawMoment {
             _ => Err("RawMoment is only implemented for KNorm sensitivity spaces".into())
         }
     }
+}
+
+/// Ensure that the running sum of the `order`th power of each column cannot overflow.
+fn assert_no_overflow(data_property: &ArrayProperties, order: u32) -> Result<()> {
+    let order = i32::try_from(order)?;
+    data_property.assert_no_float_overflow(|lower, upper| lower.abs().max(upper.abs()).powi(order))
 }
\ No newline at end of file
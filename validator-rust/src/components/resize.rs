@@ -335,6 +335,7 @@ pub mod test_resize {
         use crate::base::Value;
         use crate::bindings::Analysis;
         use crate::components::impute::test_impute;
+        use crate::components::literal::test_literal;
 
         pub fn analysis_f64_cont(value: Value, number_rows: Value, lower: Option<Value>, upper: Option<Value>) -> (Analysis, u32) {
 
@@ -358,6 +359,34 @@ pub mod test_resize {
             (analysis, resized)
         }
 
+        pub fn analysis_f64_cont_private(value: Value, number_rows: Value) -> (Analysis, u32) {
+            let (mut analysis, literal) = test_literal::analysis_literal(value, false);
+            let casted = analysis.to_float(literal).build();
+
+            let lower = analysis.literal().value(0.0.into()).value_public(true).build();
+            let upper = analysis.literal().value(10.0.into()).value_public(true).build();
+            let number_columns = analysis.literal()
+                .value(1.into()).value_public(true)
+                .build();
+            let number_rows = analysis.literal()
+                .value(number_rows).value_public(true)
+                .build();
+
+            let resized = analysis.resize(casted)
+                .number_columns(number_columns)
+                .number_rows(number_rows)
+                .lower(lower).upper(upper)
+                .build();
+            let clamped = analysis.clamp(resized)
+                .lower(lower).upper(upper)
+                .build();
+            let imputed = analysis.impute(clamped)
+                .lower(lower).upper(upper)
+                .build();
+
+            (analysis, imputed)
+        }
+
         pub fn analysis_i64_cont(value: Value, number_rows: Value, lower: Option<Value>, upper: Option<Value>) -> (Analysis, u32) {
             let (mut analysis, imputed) = test_impute::utilities::analysis_i64_cont(
                 value, None, None);
@@ -398,6 +427,40 @@ pub mod test_resize {
             (analysis, resized)
         }
 
+        pub fn analysis_i64_cat_private(value: Value, number_rows: Value, categories: Value) -> (Analysis, u32) {
+            let (mut analysis, literal) = test_literal::analysis_literal(value, false);
+
+            let lower = analysis.literal().value(i64::min_value().into()).value_public(true).build();
+            let upper = analysis.literal().value(i64::max_value().into()).value_public(true).build();
+            let casted = analysis.to_int(literal, lower, upper).build();
+
+            let categories = analysis.literal()
+                .value(categories).value_public(true)
+                .build();
+            let number_columns = analysis.literal()
+                .value(1.into()).value_public(true)
+                .build();
+            let number_rows = analysis.literal()
+                .value(number_rows).value_public(true)
+                .build();
+
+            let null_value = analysis.literal()
+                .value((-1).into()).value_public(true)
+                .build();
+
+            let resized = analysis.resize(casted)
+                .number_columns(number_columns)
+                .number_rows(number_rows)
+                .categories(categories)
+                .build();
+            let clamped = analysis.clamp(resized)
+                .categories(categories)
+                .null_value(null_value)
+                .build();
+
+            (analysis, clamped)
+        }
+
         pub fn analysis_string_cat(value: Value, number_rows: Value, categories: Option<Value>) -> (Analysis, u32) {
             let (mut analysis, imputed) = test_impute::utilities::analysis_string_cat(
                 value, None, None);
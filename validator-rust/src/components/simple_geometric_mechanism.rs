@@ -4,9 +4,9 @@ use crate::components::{Sensitivity, Accuracy, Mechanism};
 use crate::{proto, base, Warnable};
 
 use crate::components::{Component, Expandable};
-use crate::base::{Value, SensitivitySpace, ValueProperties, DataType, NodeProperties, IndexKey};
+use crate::base::{Value, SensitivitySpace, ValueProperties, DataType, NodeProperties, IndexKey, ArrayProperties};
 use crate::utilities::{prepend, expand_mechanism, get_literal};
-use crate::utilities::privacy::{spread_privacy_usage, get_epsilon, privacy_usage_check};
+use crate::utilities::privacy::{approximate_usage_check, get_epsilon, get_mechanism_privacy_usages, privacy_usage_check, LossModel, spread_privacy_usage};
 use itertools::Itertools;
 use indexmap::map::IndexMap;
 use crate::utilities::inference::infer_property;
@@ -54,6 +54,8 @@ impl Component for proto::SimpleGeometricMechanism {
             data_property.num_records,
             privacy_definition.strict_parameter_checks)?;
 
+        approximate_usage_check(&privacy_usage)?;
+
         data_property.releasable = true;
         data_property.aggregator = None;
 
@@ -135,15 +137,41 @@ impl Mechanism for proto::SimpleGeometricMechanism {
             .ok_or("data: missing")?.array()
             .map_err(prepend("data:"))?;
 
-        Some(release_usage.unwrap_or_else(|| &self.privacy_usage).iter()
-            .map(|usage| usage.effective_to_actual(
-                data_property.sample_proportion.unwrap_or(1.),
-                data_property.c_stability,
-                privacy_definition.group_size))
-            .collect::<Result<Vec<proto::PrivacyUsage>>>()).transpose()
+        let steps = get_sensitivity_steps(privacy_definition, data_property)?;
+
+        get_mechanism_privacy_usages(
+            privacy_definition,
+            release_usage.unwrap_or_else(|| &self.privacy_usage),
+            data_property,
+            |usage, stability| Ok(match steps {
+                Some(steps) => LossModel::DiscreteLaplace(get_epsilon(usage)? * stability, steps * stability as u64),
+                // without a shared integer sensitivity, the mechanism is only known to be epsilon-DP
+                None => LossModel::Pure(get_epsilon(usage)? * stability)
+            }))
+            .map(Some)
     }
 }
 
+/// Retrieve the sensitivity of the aggregator as a number of integer steps, if all columns share the same integer sensitivity.
+fn get_sensitivity_steps(
+    privacy_definition: &proto::PrivacyDefinition,
+    data_property: &ArrayProperties,
+) -> Result<Option<u64>> {
+    let aggregator = data_property.aggregator.as_ref()
+        .ok_or_else(|| Error::from("aggregator: missing"))?;
+
+    let mut sensitivity = aggregator.component.compute_sensitivity(
+        privacy_definition,
+        &aggregator.properties,
+        &SensitivitySpace::KNorm(1))?.array()?.float()?;
+    sensitivity *= &aggregator.lipschitz_constants.clone().array()?.float()?;
+
+    Ok(sensitivity.iter().next()
+        .filter(|first| sensitivity.iter().all(|v| v == *first))
+        .filter(|first| **first >= 1. && first.fract() == 0.)
+        .map(|first| *first as u64))
+}
+
 
 impl Accuracy for proto::SimpleGeometricMechanism {
     fn accuracy_to_privacy_usage(
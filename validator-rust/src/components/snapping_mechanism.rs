@@ -9,7 +9,7 @@ use crate::components::{Component, Expandable};
 use crate::errors::*;
 use crate::utilities::{expand_mechanism, get_literal, prepend, standardize_numeric_argument};
 use crate::utilities::inference::infer_property;
-use crate::utilities::privacy::{privacy_usage_check, spread_privacy_usage, get_epsilon};
+use crate::utilities::privacy::{approximate_usage_check, get_epsilon, get_mechanism_privacy_usages, privacy_usage_check, LossModel, spread_privacy_usage};
 use ieee754::Ieee754;
 use std::cmp::Ordering;
 
@@ -67,6 +67,8 @@ impl Component for proto::SnappingMechanism {
             data_property.num_records,
             privacy_definition.strict_parameter_checks)?;
 
+        approximate_usage_check(&privacy_usage)?;
+
         data_property.releasable = true;
         data_property.aggregator = None;
 
@@ -147,12 +149,12 @@ impl Mechanism for proto::SnappingMechanism {
             .ok_or("data: missing")?.array()
             .map_err(prepend("data:"))?;
 
-        Some(release_usage.unwrap_or_else(|| &self.privacy_usage).iter()
-            .map(|usage| usage.effective_to_actual(
-                data_property.sample_proportion.unwrap_or(1.),
-                data_property.c_stability,
-                privacy_definition.group_size))
-            .collect::<Result<Vec<proto::PrivacyUsage>>>()).transpose()
+        get_mechanism_privacy_usages(
+            privacy_definition,
+            release_usage.unwrap_or_else(|| &self.privacy_usage),
+            data_property,
+            |usage, stability| Ok(LossModel::Pure(get_epsilon(usage)? * stability)))
+            .map(Some)
     }
 }
 
@@ -0,0 +1,194 @@
+use indexmap::map::IndexMap;
+use itertools::Itertools;
+
+use crate::{base, proto, Warnable, Float};
+use crate::base::{Array, ArrayProperties, DataType, IndexKey, NodeProperties, SensitivitySpace, Value, ValueProperties};
+use crate::components::{Component, Expandable, Mechanism, Sensitivity};
+use crate::errors::*;
+use crate::utilities::{expand_mechanism, prepend};
+use crate::utilities::privacy::{approximate_usage_check, get_epsilon, get_mechanism_privacy_usages, privacy_usage_check, LossModel};
+
+impl Component for proto::SparseVector {
+    fn propagate_property(
+        &self,
+        privacy_definition: &Option<proto::PrivacyDefinition>,
+        public_arguments: IndexMap<base::IndexKey, &Value>,
+        properties: base::NodeProperties,
+        node_id: u32,
+    ) -> Result<Warnable<ValueProperties>> {
+        let privacy_definition = privacy_definition.as_ref()
+            .ok_or_else(|| "privacy_definition must be defined")?;
+
+        if privacy_definition.protect_floating_point {
+            return Err("Floating-point protections are enabled. The sparse vector mechanism is susceptible to floating-point attacks.".into())
+        }
+
+        let data_property: ArrayProperties = properties.get::<IndexKey>(&"data".into())
+            .ok_or("data: missing")?.array()
+            .map_err(prepend("data:"))?.clone();
+
+        if data_property.data_type != DataType::Float && data_property.data_type != DataType::Int {
+            return Err("data: atomic type must be numeric".into());
+        }
+
+        if data_property.num_columns()? != 1 {
+            return Err("data: the sparse vector mechanism only works with one column at a time".into())
+        }
+
+        get_threshold(public_arguments.get::<IndexKey>(&"threshold".into())
+            .ok_or_else(|| Error::from("threshold: must be public"))?.ref_array()?)
+            .map_err(prepend("threshold:"))?;
+
+        if self.cutoff == 0 {
+            return Err("cutoff: must be greater than zero".into())
+        }
+
+        let aggregator = data_property.aggregator.clone()
+            .ok_or_else(|| Error::from("aggregator: missing"))?;
+
+        // sensitivity must be computable
+        aggregator.component.compute_sensitivity(
+            privacy_definition,
+            &aggregator.properties,
+            &SensitivitySpace::KNorm(1))?.array()?.float()?;
+
+        let privacy_usage = self.privacy_usage.iter().cloned().map(Ok)
+            .fold1(|l, r| l? + r?).ok_or_else(|| "privacy_usage: must be defined")??;
+
+        let warnings = privacy_usage_check(
+            &privacy_usage,
+            data_property.num_records,
+            privacy_definition.strict_parameter_checks)?;
+
+        approximate_usage_check(&privacy_usage)?;
+
+        Ok(Warnable(ArrayProperties {
+            // the number of queries found above the threshold is not known until evaluation
+            num_records: None,
+            num_columns: Some(1),
+            nullity: false,
+            releasable: true,
+            c_stability: 1,
+            aggregator: None,
+            nature: None,
+            data_type: DataType::Int,
+            dataset_id: None,
+            node_id: node_id as i64,
+            is_not_empty: false,
+            dimensionality: Some(1),
+            group_id: data_property.group_id,
+            naturally_ordered: true,
+            sample_proportion: None
+        }.into(), warnings))
+    }
+}
+
+impl Expandable for proto::SparseVector {
+    fn expand_component(
+        &self,
+        privacy_definition: &Option<proto::PrivacyDefinition>,
+        component: &proto::Component,
+        _public_arguments: &IndexMap<IndexKey, &Value>,
+        properties: &base::NodeProperties,
+        component_id: u32,
+        maximum_id: u32,
+    ) -> Result<base::ComponentExpansion> {
+        expand_mechanism(
+            &SensitivitySpace::KNorm(1),
+            privacy_definition,
+            self.privacy_usage.as_ref(),
+            component,
+            properties,
+            component_id,
+            maximum_id
+        )
+    }
+}
+
+impl Mechanism for proto::SparseVector {
+    fn get_privacy_usage(
+        &self,
+        privacy_definition: &proto::PrivacyDefinition,
+        release_usage: Option<&Vec<proto::PrivacyUsage>>,
+        properties: &NodeProperties
+    ) -> Result<Option<Vec<proto::PrivacyUsage>>> {
+        let data_property = properties.get::<IndexKey>(&"data".into())
+            .ok_or("data: missing")?.array()
+            .map_err(prepend("data:"))?;
+
+        // the usage is independent of the number of queries, and of the number found below the threshold
+        get_mechanism_privacy_usages(
+            privacy_definition,
+            release_usage.unwrap_or_else(|| &self.privacy_usage),
+            data_property,
+            |usage, stability| Ok(LossModel::Pure(get_epsilon(usage)? * stability)))
+            .map(Some)
+    }
+}
+
+/// Retrieve the scalar threshold a sparse vector mechanism compares queries against.
+pub fn get_threshold(threshold: &Array) -> Result<Float> {
+    match threshold {
+        Array::Int(_) => Ok(threshold.first_int()? as Float),
+        _ => threshold.first_float()
+    }
+}
+
+
+#[cfg(test)]
+mod test_sparse_vector {
+    use crate::base::{test_data, Value};
+    use crate::components::resize::test_resize;
+    use crate::utilities::privacy::get_epsilon;
+    use crate::base::test_usage::usage;
+
+    #[test]
+    fn test_sparse_vector() {
+        let (mut analysis, resized) = test_resize::utilities::analysis_f64_cont(
+            test_data::array1d_f64_10_uniform(), 10.into(), None, None);
+
+        let count = analysis.count(resized).build();
+        let threshold = analysis.literal().value(5.0.into()).value_public(true).build();
+        analysis.sparse_vector(count, threshold, vec![usage(1.)]).cutoff(3).build();
+
+        // the usage of the sparse vector does not scale with the cutoff
+        let privacy_usage = crate::compute_privacy_usage(
+            analysis.privacy_definition, analysis.components, analysis.release).unwrap();
+        assert!((get_epsilon(&privacy_usage).unwrap() - 1.).abs() < 1e-8);
+    }
+
+    #[test]
+    fn test_histogram_queries() {
+        let (mut analysis, resized) = test_resize::utilities::analysis_f64_cont(
+            test_data::array1d_f64_10_uniform(), 10.into(), None, None);
+
+        // each bin of the histogram is a query
+        let edges = analysis.literal()
+            .value(Value::Jagged(vec![vec![0., 2.5, 5., 7.5, 10.]].into()))
+            .value_public(true).build();
+        let histogram = analysis.histogram(resized).edges(edges).build();
+        let threshold = analysis.literal().value(2.into()).value_public(true).build();
+        let indices = analysis.sparse_vector(histogram, threshold, vec![usage(1.)]).cutoff(2).build();
+
+        let indices_property = analysis.properties(indices).unwrap().array().unwrap().clone();
+        assert!(indices_property.releasable);
+        assert_eq!(indices_property.num_columns, Some(1));
+
+        let privacy_usage = crate::compute_privacy_usage(
+            analysis.privacy_definition, analysis.components, analysis.release).unwrap();
+        assert!((get_epsilon(&privacy_usage).unwrap() - 1.).abs() < 1e-8);
+    }
+
+    #[test]
+    fn test_private_threshold() {
+        let (mut analysis, resized) = test_resize::utilities::analysis_f64_cont(
+            test_data::array1d_f64_10_uniform(), 10.into(), None, None);
+
+        let count = analysis.count(resized).build();
+        let threshold = analysis.count(resized).build();
+        analysis.above_threshold(count, threshold, vec![usage(1.)]).build();
+
+        assert!(crate::compute_privacy_usage(
+            analysis.privacy_definition, analysis.components, analysis.release).is_err());
+    }
+}
@@ -0,0 +1,167 @@
+use indexmap::map::IndexMap;
+use itertools::Itertools;
+
+use crate::{base, proto, Warnable};
+use crate::base::{ArrayProperties, DataframeProperties, DataType, IndexKey, NodeProperties, Value, ValueProperties};
+use crate::components::{Component, Mechanism};
+use crate::errors::*;
+use crate::utilities::prepend;
+use crate::utilities::privacy::{get_delta, get_epsilon, privacy_usage_check};
+
+
+impl Component for proto::StabilityHistogram {
+    fn propagate_property(
+        &self,
+        privacy_definition: &Option<proto::PrivacyDefinition>,
+        _public_arguments: IndexMap<base::IndexKey, &Value>,
+        properties: base::NodeProperties,
+        node_id: u32
+    ) -> Result<Warnable<ValueProperties>> {
+        let privacy_definition = privacy_definition.as_ref()
+            .ok_or_else(|| "privacy_definition must be defined")?;
+
+        if privacy_definition.group_size == 0 {
+            return Err("group size must be greater than zero".into())
+        }
+
+        // the released keys are only known after evaluation, so work done is proportional to the number of observed keys
+        if privacy_definition.protect_elapsed_time || privacy_definition.protect_memory_utilization {
+            return Err("the stability histogram may not be used when protecting elapsed time or memory utilization".into())
+        }
+
+        let data_property = properties.get::<IndexKey>(&"data".into())
+            .ok_or("data: missing")?.array()
+            .map_err(prepend("data:"))?.clone();
+
+        data_property.assert_is_not_aggregated()?;
+        if data_property.releasable {
+            return Err("data: is already public".into())
+        }
+        if data_property.num_columns()? != 1 {
+            return Err("data: must contain one column".into())
+        }
+        if data_property.data_type == DataType::Float || data_property.data_type == DataType::Unknown {
+            return Err("data: atomic type must be bool, int or string".into())
+        }
+
+        let privacy_usage = self.privacy_usage.iter().cloned().map(Ok)
+            .fold1(|l, r| l? + r?).ok_or_else(|| "privacy_usage: must be defined")??;
+
+        let warnings = privacy_usage_check(
+            &privacy_usage,
+            data_property.num_records,
+            privacy_definition.strict_parameter_checks)?;
+
+        // the threshold is only defined for (epsilon, delta) privacy usages
+        let epsilon = get_epsilon(&privacy_usage).map_err(prepend("privacy_usage:"))?;
+        let delta = get_delta(&privacy_usage).map_err(prepend("privacy_usage:"))?;
+        if epsilon <= 0. {
+            return Err("epsilon: must be greater than zero".into())
+        }
+        if delta <= 0. || delta >= 1. {
+            return Err("delta: must be within (0, 1), as unobserved keys are dropped".into())
+        }
+
+        let column_property = |data_type: DataType| ArrayProperties {
+            num_records: None,
+            num_columns: Some(1),
+            nullity: false,
+            releasable: true,
+            c_stability: 1,
+            aggregator: None,
+            nature: None,
+            data_type,
+            dataset_id: Some(node_id as i64),
+            node_id: node_id as i64,
+            is_not_empty: false,
+            dimensionality: Some(1),
+            group_id: data_property.group_id.clone(),
+            naturally_ordered: true,
+            sample_proportion: None,
+        };
+
+        Ok(Warnable(ValueProperties::Dataframe(DataframeProperties {
+            children: indexmap![
+                "categories".into() => column_property(data_property.data_type.clone()).into(),
+                "counts".into() => column_property(DataType::Int).into()
+            ]
+        }), warnings))
+    }
+}
+
+impl Mechanism for proto::StabilityHistogram {
+    fn get_privacy_usage(
+        &self,
+        privacy_definition: &proto::PrivacyDefinition,
+        release_usage: Option<&Vec<proto::PrivacyUsage>>,
+        properties: &NodeProperties
+    ) -> Result<Option<Vec<proto::PrivacyUsage>>> {
+        let data_property = properties.get::<IndexKey>(&"data".into())
+            .ok_or("data: missing")?.array()
+            .map_err(prepend("data:"))?;
+
+        // the thresholding is not captured by a renyi curve or privacy loss distribution,
+        //     so the usage is always reported as (epsilon, delta)
+        release_usage.unwrap_or_else(|| &self.privacy_usage).iter()
+            .map(|usage| usage.effective_to_actual(
+                data_property.sample_proportion.unwrap_or(1.),
+                data_property.c_stability,
+                privacy_definition))
+            .collect::<Result<Vec<proto::PrivacyUsage>>>()
+            .map(Some)
+    }
+}
+
+
+#[cfg(test)]
+mod test_stability_histogram {
+    use ndarray::arr1;
+
+    use crate::base::{Value, ValueProperties};
+    use crate::base::test_usage::approximate_usage;
+    use crate::components::literal::test_literal;
+    use crate::components::resize::test_resize;
+    use crate::utilities::privacy::{get_delta, get_epsilon};
+
+    #[test]
+    fn test_stability_histogram() {
+        let (mut analysis, data) = test_literal::analysis_literal(
+            arr1(&["a", "b", "a", "c"]).mapv(String::from).into_dyn().into(), false);
+        let casted = analysis.to_string(data).build();
+
+        // the number of columns of private data is only known once resized
+        let number_columns = analysis.literal().value(1.into()).value_public(true).build();
+        let categories = analysis.literal()
+            .value(Value::Jagged(vec![vec!["a", "b", "c"].into_iter().map(String::from).collect::<Vec<String>>()].into()))
+            .value_public(true).build();
+        let resized = analysis.resize(casted)
+            .number_columns(number_columns).categories(categories)
+            .build();
+
+        let lower = analysis.literal().value(0.into()).value_public(true).build();
+        let inclusive_left = analysis.literal().value(true.into()).value_public(true).build();
+
+        let histogram = analysis.dp_histogram(resized, lower, inclusive_left, vec![approximate_usage(1., 1e-6)])
+            .mechanism("Stability".to_string())
+            .build();
+
+        match analysis.properties(histogram).unwrap() {
+            ValueProperties::Dataframe(properties) => assert!(properties.children.values()
+                .all(|column| column.array().unwrap().releasable)),
+            _ => panic!("the stability histogram must release a dataframe")
+        };
+
+        let privacy_usage = crate::compute_privacy_usage(
+            analysis.privacy_definition, analysis.components, analysis.release).unwrap();
+        assert!((get_epsilon(&privacy_usage).unwrap() - 1.).abs() < 1e-8);
+        assert!((get_delta(&privacy_usage).unwrap() - 1e-6).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_zero_delta() {
+        let (mut analysis, data) = test_resize::utilities::analysis_i64_cat_private(
+            arr1(&[1, 2, 2]).into_dyn().into(), 3.into(), Value::Jagged(vec![vec![1, 2]].into()));
+        let histogram = analysis.stability_histogram(data, vec![approximate_usage(1., 0.)]).build();
+        assert!(analysis.properties(histogram).is_err());
+    }
+}
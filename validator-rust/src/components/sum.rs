@@ -2,15 +2,15 @@ use indexmap::map::IndexMap;
 use ndarray::prelude::*;
 
 use crate::{base, Float, proto, Warnable};
-use crate::base::{AggregatorProperties, DataType, IndexKey, Nature, NatureContinuous, NodeProperties, SensitivitySpace, Value, ValueProperties, Vector1DNull};
+use crate::base::{AggregatorProperties, ArrayProperties, DataType, IndexKey, Nature, NatureContinuous, NodeProperties, SensitivitySpace, Value, ValueProperties, Vector1DNull};
 use crate::components::{Component, Sensitivity};
 use crate::errors::*;
-use crate::utilities::prepend;
+use crate::utilities::{get_pairwise_summation_gamma, prepend};
 
 impl Component for proto::Sum {
     fn propagate_property(
         &self,
-        _privacy_definition: &Option<proto::PrivacyDefinition>,
+        privacy_definition: &Option<proto::PrivacyDefinition>,
         _public_arguments: IndexMap<base::IndexKey, &Value>,
         properties: base::NodeProperties,
         node_id: u32
@@ -31,17 +31,24 @@ impl Component for proto::Sum {
         if data_property.data_type != DataType::Float && data_property.data_type != DataType::Int {
             return Err("data: atomic type must be numeric".into())
         }
+
+        let protect_overflow = privacy_definition.as_ref()
+            .map(|v| v.protect_overflow).unwrap_or(false);
+        if protect_overflow && !data_property.releasable {
+            assert_no_overflow(&data_property).map_err(prepend("data:"))?;
+        }
+
         data_property.nature = data_property.num_records.and_then(|n| Some(Nature::Continuous(NatureContinuous {
             lower: match data_property.data_type {
                 DataType::Int => Vector1DNull::Int(data_property
-                    .lower_int().ok()?.iter().map(|l| Some(l * n)).collect()),
+                    .lower_int().ok()?.iter().map(|l| l.checked_mul(n)).collect()),
                 DataType::Float => Vector1DNull::Float(data_property
                     .lower_float().ok()?.iter().map(|l| Some(l * (n as Float))).collect()),
                 _ => unreachable!()
             },
             upper: match data_property.data_type {
                 DataType::Int => Vector1DNull::Int(data_property
-                    .upper_int().ok()?.iter().map(|u| Some(u * n)).collect()),
+                    .upper_int().ok()?.iter().map(|u| u.checked_mul(n)).collect()),
                 DataType::Float => Vector1DNull::Float(data_property
                     .upper_float().ok()?.iter().map(|u| Some(u * (n as Float))).collect()),
                 _ => unreachable!()
@@ -73,6 +80,9 @@ impl Sensitivity for proto::Sum {
 
                 data_property.assert_is_not_aggregated()?;
                 data_property.assert_non_null()?;
+                if privacy_definition.protect_overflow {
+                    assert_no_overflow(&data_property)?;
+                }
                 let data_lower = data_property.lower_float()?;
                 let data_upper = data_property.upper_float()?;
 
@@ -94,6 +104,19 @@ impl Sensitivity for proto::Sum {
                     _ => return Err("KNorm sensitivity is only supported in L1 and L2 spaces".into())
                 };
 
+                // the runtime sums floats pairwise, with an error proportional to the sum of magnitudes
+                let row_sensitivity = if privacy_definition.protect_floating_point && data_property.data_type == DataType::Float {
+                    let num_records = data_property.num_records()
+                        .map_err(|_| Error::from("number of records must be known to protect floating-point summation"))?;
+                    // the neighboring dataset may have one more record
+                    let gamma = get_pairwise_summation_gamma(num_records + 1, 0)?;
+                    row_sensitivity.into_iter()
+                        .zip(data_lower.iter().zip(data_upper.iter()))
+                        .map(|(sensitivity, (min, max))| sensitivity
+                            + 2. * gamma * (num_records + 1) as Float * min.abs().max(max.abs()))
+                        .collect::<Vec<Float>>()
+                } else { row_sensitivity };
+
                 let mut array_sensitivity = Array::from(row_sensitivity).into_dyn();
                 array_sensitivity.insert_axis_inplace(Axis(0));
 
@@ -102,4 +125,55 @@ impl Sensitivity for proto::Sum {
             _ => Err("Sum sensitivity is only implemented for KNorm of 1".into())
         }
     }
-}
\ No newline at end of file
+}
+
+/// Ensure that the sum of each column cannot overflow the atomic type.
+fn assert_no_overflow(data_property: &ArrayProperties) -> Result<()> {
+    match data_property.data_type {
+        DataType::Int => data_property.assert_no_int_overflow(),
+        DataType::Float => data_property.assert_no_float_overflow(|lower, upper| lower.abs().max(upper.abs())),
+        _ => Err("atomic type must be numeric".into())
+    }
+}
+
+
+#[cfg(test)]
+mod test_sum {
+    use crate::{Integer, proto};
+    use crate::base::{IndexKey, SensitivitySpace, test_data};
+    use crate::components::Sensitivity;
+    use crate::components::resize::test_resize;
+
+    #[test]
+    fn test_protect_overflow() {
+        let (mut analysis, resized) = test_resize::utilities::analysis_i64_cont(
+            test_data::array1d_i64_10_uniform(), 10.into(), None, Some((Integer::MAX / 5).into()));
+        let properties = indexmap![IndexKey::from("data") => analysis.properties(resized).unwrap()];
+
+        // the sum of ten records may exceed the range of integers
+        assert!(proto::Sum {}.compute_sensitivity(
+            &analysis.privacy_definition, &properties, &SensitivitySpace::KNorm(1)).is_ok());
+
+        analysis.privacy_definition.protect_overflow = true;
+        assert!(proto::Sum {}.compute_sensitivity(
+            &analysis.privacy_definition, &properties, &SensitivitySpace::KNorm(1)).is_err());
+    }
+
+    #[test]
+    fn test_protect_floating_point() {
+        let (mut analysis, resized) = test_resize::utilities::analysis_f64_cont(
+            test_data::array1d_f64_10_uniform(), 10.into(), None, None);
+        let properties = indexmap![IndexKey::from("data") => analysis.properties(resized).unwrap()];
+
+        let sensitivity = proto::Sum {}.compute_sensitivity(
+            &analysis.privacy_definition, &properties, &SensitivitySpace::KNorm(1))
+            .unwrap().array().unwrap().first_float().unwrap();
+
+        // the summation error widens the sensitivity
+        analysis.privacy_definition.protect_floating_point = true;
+        let protected_sensitivity = proto::Sum {}.compute_sensitivity(
+            &analysis.privacy_definition, &properties, &SensitivitySpace::KNorm(1))
+            .unwrap().array().unwrap().first_float().unwrap();
+        assert!(protected_sensitivity > sensitivity);
+    }
+}
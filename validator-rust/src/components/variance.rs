@@ -3,7 +3,7 @@ use ndarray::prelude::*;
 
 use crate::{base, Float, proto, Warnable};
 use crate::base::{
-    AggregatorProperties, DataType, IndexKey, Nature, NatureContinuous,
+    AggregatorProperties, ArrayProperties, DataType, IndexKey, Nature, NatureContinuous,
     NodeProperties, SensitivitySpace, Value, ValueProperties, Vector1DNull,
 };
 use crate::components::{Component, Sensitivity};
@@ -13,7 +13,7 @@ use crate::utilities::prepend;
 impl Component for proto::Variance {
     fn propagate_property(
         &self,
-        _privacy_definition: &Option<proto::PrivacyDefinition>,
+        privacy_definition: &Option<proto::PrivacyDefinition>,
         _public_arguments: IndexMap<base::IndexKey, &Value>,
         properties: base::NodeProperties,
         node_id: u32
@@ -37,6 +37,12 @@ impl Component for proto::Variance {
             return Err("data: atomic type must be float".into())
         }
 
+        let protect_overflow = privacy_definition.as_ref()
+            .map(|v| v.protect_overflow).unwrap_or(false);
+        if protect_overflow && !data_property.releasable {
+            assert_no_overflow(&data_property).map_err(prepend("data:"))?;
+        }
+
         data_property.nature = match (data_property.lower_float(), data_property.upper_float()) {
             (Ok(lower), Ok(upper)) => Some(Nature::Continuous(NatureContinuous {
                 lower: Vector1DNull::Float((0..num_columns).map(|_| Some(0.)).collect()),
@@ -71,6 +77,9 @@ impl Sensitivity for proto::Variance {
 
                 data_property.assert_non_null()?;
                 data_property.assert_is_not_aggregated()?;
+                if privacy_definition.protect_overflow {
+                    assert_no_overflow(&data_property)?;
+                }
                 let data_min = data_property.lower_float()?;
                 let data_max = data_property.upper_float()?;
                 let data_n = data_property.num_records()? as f64;
@@ -103,4 +112,9 @@ impl Sensitivity for proto::Variance {
             _ => Err("Variance sensitivity is only implemented for KNorm of 1".into())
         }
     }
+}
+
+/// Ensure that neither the running sum for the mean, nor the running sum of squared deviations, can overflow.
+fn assert_no_overflow(data_property: &ArrayProperties) -> Result<()> {
+    data_property.assert_no_float_overflow(|lower, upper| lower.abs().max(upper.abs()).max((upper - lower).powi(2)))
 }
\ No newline at end of file
@@ -108,7 +108,7 @@ pub fn validate_analysis(
 
 /// Compute overall privacy usage of an analysis.
 ///
-/// The privacy usage is sum of the privacy usages for each node.
+/// The privacy usages of each node are composed according to the composition in the privacy definition.
 /// The Release's actual privacy usage, if defined, takes priority over the maximum allowable privacy usage defined in the Analysis.
 pub fn compute_privacy_usage(
     privacy_definition: proto::PrivacyDefinition,
@@ -124,12 +124,75 @@ pub fn compute_privacy_usage(
     let privacy_usage = compute_graph_privacy_usage(
         &computation_graph, &privacy_definition, &properties, &release)?;
 
+    // concentrated privacy usages are reported in terms of (epsilon, delta)
+    if !matches!(privacy_usage.distance, Some(proto::privacy_usage::Distance::Approximate(_))) {
+        utilities::privacy::check_conversion_delta(&privacy_definition)?;
+    }
+    let privacy_usage = utilities::privacy::to_approximate(
+        &privacy_usage, privacy_definition.conversion_delta)?;
+
     utilities::privacy::privacy_usage_check(&privacy_usage, None, false)?;
 
     Ok(privacy_usage)
 }
 
 
+/// Compute the smallest epsilon the analysis satisfies at the given delta.
+///
+/// The privacy usages of each node are composed according to the composition in the privacy definition,
+/// and the composed usage is then converted to epsilon at the requested delta.
+/// Privacy loss distributions give the tightest conversion, but every composition is supported.
+pub fn compute_epsilon(
+    privacy_definition: proto::PrivacyDefinition,
+    mut computation_graph: HashMap<u32, proto::Component>,
+    mut release: base::Release,
+    delta: f64
+) -> Result<f64> {
+
+    if !(0. ..1.).contains(&delta) {
+        return Err("delta: must be within [0, 1)".into())
+    }
+
+    let properties = utilities::propagate_properties(
+        &Some(privacy_definition.clone()),
+        &mut computation_graph,
+        &mut release, None, false)?.0;
+
+    let privacy_usage = compute_graph_privacy_usage(
+        &computation_graph, &privacy_definition, &properties, &release)?;
+
+    if let Some(proto::privacy_usage::Distance::Approximate(usage)) = &privacy_usage.distance {
+        if usage.delta > delta {
+            bail!("delta: the analysis consumes a delta of {}, which exceeds the requested delta", usage.delta)
+        }
+        return Ok(usage.epsilon)
+    }
+
+    utilities::privacy::get_epsilon(&utilities::privacy::to_approximate(&privacy_usage, delta)?)
+}
+
+
+/// Compute the privacy spent by the mechanisms in a release, when usages were chosen adaptively.
+///
+/// Only mechanisms that have already been released are counted, and the bound holds at any point in an adaptive analysis.
+pub fn compute_privacy_odometer(
+    privacy_definition: proto::PrivacyDefinition,
+    mut computation_graph: HashMap<u32, proto::Component>,
+    mut release: base::Release
+) -> Result<proto::PrivacyUsage> {
+
+    let properties = utilities::propagate_properties(
+        &Some(privacy_definition.clone()),
+        &mut computation_graph,
+        &mut release, None, false)?.0;
+
+    let usages = utilities::privacy_filter::get_released_privacy_usages(
+        &computation_graph, &privacy_definition, &properties, &release)?;
+
+    utilities::privacy_filter::privacy_odometer(&privacy_definition, &usages)
+}
+
+
 /// Generate a json string with a summary/report of the Analysis and Release
 pub fn generate_report(
     privacy_definition: proto::PrivacyDefinition,
@@ -138,7 +201,7 @@ pub fn generate_report(
 ) -> Result<String> {
 
     let graph_properties = utilities::propagate_properties(
-        &Some(privacy_definition),
+        &Some(privacy_definition.clone()),
         &mut computation_graph.clone(),
         &mut release, None, false)?.0;
 
@@ -174,7 +237,7 @@ pub fn generate_report(
     });
 
     // generate summaries for any component that has a release, and has summarize implemented on it
-    let release_schemas = computation_graph.iter()
+    let mut release_schemas = computation_graph.iter()
         .map(|(node_id, component)| {
             let public_arguments = utilities::get_public_arguments(&component, &release)?;
             let input_properties = utilities::get_input_properties(&component, &graph_properties)?;
@@ -197,6 +260,12 @@ pub fn generate_report(
         .filter_map(|v| v).flat_map(|v| v)
         .collect::<Vec<utilities::json::JSONRelease>>();
 
+    // report concentrated privacy usages in terms of (epsilon, delta) as well
+    if privacy_definition.conversion_delta > 0. {
+        release_schemas.iter_mut().try_for_each(|release_schema| utilities::json::append_approximate_privacy_loss(
+            &mut release_schema.privacy_loss, privacy_definition.conversion_delta))?;
+    }
+
     match serde_json::to_string(&release_schemas) {
         Ok(serialized) => Ok(serialized),
         Err(_) => Err("unable to parse report into json".into())
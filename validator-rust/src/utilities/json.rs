@@ -5,6 +5,7 @@ use serde::{Deserialize, Serialize};
 
 use crate::proto;
 use crate::base;
+use crate::utilities::privacy::{to_approximate, get_epsilon};
 
 use serde_json::Value;
 use ndarray::prelude::*;
@@ -90,10 +91,51 @@ pub fn arraynd_to_json<T: Serialize + Clone>(array: &ArrayD<T>) -> Result<serde_
 
 /// Converts the prost Protobuf PrivacyLoss into a json representation.
 ///
-/// User provide a value for either epsilon, delta, or rho depending on the type of dp definitions (i.e. approximate and concentrated).
+/// User provide a value for either epsilon, delta, rho or mu depending on the type of dp definitions (i.e. approximate, concentrated and gaussian).
 pub fn privacy_usage_to_json(privacy_usage: &proto::PrivacyUsage) -> serde_json::Value {
     match privacy_usage.distance.clone().unwrap() {
         proto::privacy_usage::Distance::Approximate(distance) =>
-            serde_json::json!({"name": "approximate", "epsilon": distance.epsilon, "delta": distance.delta})
+            serde_json::json!({"name": "approximate", "epsilon": distance.epsilon, "delta": distance.delta}),
+        proto::privacy_usage::Distance::Concentrated(distance) =>
+            serde_json::json!({"name": "concentrated", "rho": distance.rho}),
+        proto::privacy_usage::Distance::Gaussian(distance) =>
+            serde_json::json!({"name": "gaussian", "mu": distance.mu}),
+        proto::privacy_usage::Distance::Renyi(distance) =>
+            serde_json::json!({"name": "renyi", "orders": distance.orders, "epsilons": distance.epsilons}),
+        proto::privacy_usage::Distance::PrivacyLoss(distance) =>
+            serde_json::json!({
+                "name": "privacy_loss_distribution",
+                "discretization": distance.discretization,
+                "offset": distance.offset,
+                "masses": distance.masses,
+                "infinity_mass": distance.infinity_mass
+            })
+    }
+}
+
+/// Supplement json representations of concentrated and gaussian privacy usages with the equivalent epsilon at the given delta.
+pub fn append_approximate_privacy_loss(privacy_loss: &mut Value, delta: f64) -> Result<()> {
+    match privacy_loss {
+        Value::Array(privacy_losses) => privacy_losses.iter_mut()
+            .try_for_each(|privacy_loss| append_approximate_privacy_loss(privacy_loss, delta)),
+        Value::Object(privacy_loss) => {
+            let distance = match privacy_loss.get("name").and_then(Value::as_str) {
+                Some("concentrated") => proto::privacy_usage::Distance::Concentrated(proto::privacy_usage::DistanceConcentrated {
+                    rho: privacy_loss.get("rho").and_then(Value::as_f64)
+                        .ok_or("rho must be defined on a concentrated privacy loss")?
+                }),
+                Some("gaussian") => proto::privacy_usage::Distance::Gaussian(proto::privacy_usage::DistanceGaussian {
+                    mu: privacy_loss.get("mu").and_then(Value::as_f64)
+                        .ok_or("mu must be defined on a gaussian privacy loss")?
+                }),
+                _ => return Ok(())
+            };
+
+            let approximate = to_approximate(&proto::PrivacyUsage { distance: Some(distance) }, delta)?;
+            privacy_loss.insert("epsilon".to_string(), serde_json::json!(get_epsilon(&approximate)?));
+            privacy_loss.insert("delta".to_string(), serde_json::json!(delta));
+            Ok(())
+        }
+        _ => Ok(())
     }
 }
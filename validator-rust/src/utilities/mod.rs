@@ -7,7 +7,7 @@ use itertools::Itertools;
 use ndarray::prelude::*;
 use noisy_float::prelude::n64;
 
-use crate::{base, Float, proto, Warnable};
+use crate::{base, Float, Integer, proto, Warnable};
 use crate::base::{IndexKey, NodeProperties, Release, SensitivitySpace, Value, ValueProperties, ArrayProperties};
 // import all trait implementations
 use crate::components::*;
@@ -20,6 +20,8 @@ pub mod inference;
 pub mod serial;
 pub mod array;
 pub mod privacy;
+pub mod privacy_loss;
+pub mod privacy_filter;
 pub mod properties;
 
 /// Retrieve the specified Value from the arguments to a component.
@@ -480,6 +482,42 @@ pub fn standardize_weight_argument(
     }
 }
 
+/// Retrieve the public number of records of an argument, for the runtime to size buffers from when protecting memory utilization.
+///
+/// Returns None if memory utilization is not protected, or if the component already has a `number_records` argument.
+/// The number of records of releasable data is only known once released, so it is also None until then.
+#[doc(hidden)]
+pub fn get_number_records_argument(
+    privacy_definition: &Option<proto::PrivacyDefinition>,
+    component: &proto::Component,
+    public_arguments: &IndexMap<IndexKey, &Value>,
+    properties: &NodeProperties,
+    argument: &str
+) -> Result<Option<Value>> {
+    if !privacy_definition.as_ref().map(|v| v.protect_memory_utilization).unwrap_or(false)
+        || component.arguments().contains_key::<IndexKey>(&"number_records".into()) {
+        return Ok(None)
+    }
+
+    let property = properties.get::<IndexKey>(&argument.into())
+        .ok_or_else(|| format!("{}: missing", argument))?;
+    let num_records = match property {
+        ValueProperties::Array(property) => property.num_records,
+        ValueProperties::Dataframe(property) => property.num_records()?,
+        _ => return Err(format!("{}: must be a dataframe or array", argument).into())
+    };
+
+    Ok(match (num_records, public_arguments.get::<IndexKey>(&argument.into())) {
+        (Some(num_records), _) => Some(num_records.into()),
+        (None, Some(Value::Array(value))) => Some((value.num_records()? as Integer).into()),
+        (None, Some(Value::Dataframe(value))) => Some((value.values().next()
+            .ok_or_else(|| format!("{}: dataframe may not be empty", argument))?
+            .ref_array()?.num_records()? as Integer).into()),
+        (None, _) if property.is_public() => None,
+        _ => return Err(format!("{}: number of records must be known to protect memory utilization", argument).into())
+    })
+}
+
 /// Utility for building extra Components to pass back when conducting expansions.
 #[doc(hidden)]
 pub fn get_literal(value: Value, submission: u32) -> Result<(proto::Component, base::ReleaseNode)> {
@@ -562,7 +600,7 @@ pub fn expand_mechanism(
         .map(|usage| usage.actual_to_effective(
             data_property.sample_proportion.unwrap_or(1.),
             data_property.c_stability,
-            privacy_definition.group_size))
+            privacy_definition))
         .collect::<Result<Vec<proto::PrivacyUsage>>>()?;
 
     // insert sensitivity and usage
@@ -578,7 +616,9 @@ pub fn expand_mechanism(
             }
         }
     }
-    assign_usage!(LaplaceMechanism, GaussianMechanism, SimpleGeometricMechanism, SnappingMechanism);
+    assign_usage!(
+        DiscreteGaussianMechanism, LaplaceMechanism, GaussianMechanism,
+        SimpleGeometricMechanism, SnappingMechanism, SparseVector);
 
     expansion.computation_graph.insert(component_id, noise_component);
 
@@ -613,6 +653,31 @@ pub fn deduplicate<T: Eq + Hash + Clone>(values: Vec<T>) -> Vec<T> {
 }
 
 
+/// Bound the relative rounding error of a pairwise float summation.
+///
+/// Pairwise summation of `num_records` values, followed by `num_extra_operations` further floating-point operations,
+/// returns a value within `gamma * sum(|x_i|)` of the exact result,
+/// where `gamma = k u / (1 - k u)`, `k = ceil(log2(num_records)) + num_extra_operations` and `u` is the unit roundoff.
+///
+/// # Arguments
+/// * `num_records` - Number of values summed.
+/// * `num_extra_operations` - Number of operations applied to the sum afterwards, like dividing by the number of records.
+///
+/// # Return
+/// The relative error bound `gamma`.
+pub fn get_pairwise_summation_gamma(num_records: i64, num_extra_operations: i64) -> Result<Float> {
+    if num_records < 0 {
+        return Err("number of records must be non-negative".into())
+    }
+    let depth = (num_records.max(1) as Float).log2().ceil() + num_extra_operations as Float;
+    let unit_roundoff = Float::EPSILON / 2.;
+    if depth * unit_roundoff >= 1. {
+        return Err("summation error cannot be bounded".into())
+    }
+    Ok(depth * unit_roundoff / (1. - depth * unit_roundoff))
+}
+
+
 #[cfg(test)]
 mod test_utilities {
     use crate::utilities;
@@ -623,4 +688,13 @@ mod test_utilities {
         let deduplicated = utilities::deduplicate(values.clone());
         assert!(deduplicated == vec![2, 0, 1]);
     }
+
+    #[test]
+    fn test_pairwise_summation_gamma() {
+        assert_eq!(utilities::get_pairwise_summation_gamma(1, 0).unwrap(), 0.);
+        let gamma_4 = utilities::get_pairwise_summation_gamma(16, 0).unwrap();
+        assert_eq!(gamma_4, utilities::get_pairwise_summation_gamma(8, 1).unwrap());
+        assert!(gamma_4 > 4. * f64::EPSILON / 2.);
+        assert!(utilities::get_pairwise_summation_gamma(-1, 0).is_err());
+    }
 }
\ No newline at end of file
@@ -2,29 +2,215 @@ use std::cmp::Ordering;
 use std::collections::{HashMap, HashSet};
 
 use itertools::Itertools;
+use statrs::function::erf;
 
 use crate::proto;
-use crate::base::{GroupId, IndexKey, Release, ValueProperties};
+use crate::base::{ArrayProperties, GroupId, IndexKey, Release, ValueProperties};
 use crate::components::Mechanism;
 use crate::errors::*;
 use crate::utilities::{get_common_value, get_dependents, get_input_properties};
+use crate::utilities::privacy_loss::PrivacyLossDistribution;
 
 type BatchIdentifier = (u32, u32);
 type PartitionIds = Vec<u32>;
 
-fn compute_batch_privacy_usage(
-    privacy_usages: Vec<&proto::PrivacyUsage>
+/// Compose a collection of privacy usages into a single privacy usage.
+///
+/// The composition theorem is chosen by the `composition` field of the privacy definition.
+/// Advanced and optimal composition spend an additional `composition_delta` to reduce epsilon,
+/// and fall back to linear composition whenever linear composition is tighter.
+/// Rényi composition sums the Rényi curves reported by mechanisms.
+/// Privacy loss distribution composition convolves the privacy loss distributions reported by mechanisms.
+/// Gaussian usages compose exactly, by the root sum of squares of mu.
+/// When distances are mixed, usages are converted to (epsilon, delta) at the `conversion_delta`.
+pub fn compose_privacy_usages(
+    privacy_definition: &proto::PrivacyDefinition,
+    privacy_usages: &[proto::PrivacyUsage],
 ) -> Result<proto::PrivacyUsage> {
-    // TODO: insert advanced composition here
-    //    This is just linear composition
-    privacy_usages.into_iter().cloned().map(Ok)
-        .fold1(|l, r| l? + r?)
-        .unwrap_or_else(|| Ok(proto::PrivacyUsage {
-            distance: Some(proto::privacy_usage::Distance::Approximate(proto::privacy_usage::DistanceApproximate {
-                epsilon: 0.,
-                delta: 0.,
-            }))
+    use proto::privacy_definition::Composition;
+    use proto::privacy_usage::Distance;
+
+    // usages that consume no budget have no influence on the composition
+    let privacy_usages = privacy_usages.iter()
+        .map(|usage| Ok((usage, is_zero_usage(usage)?)))
+        .collect::<Result<Vec<_>>>()?.into_iter()
+        .filter(|(_, is_zero)| !is_zero)
+        .map(|(usage, _)| usage)
+        .collect::<Vec<&proto::PrivacyUsage>>();
+
+    let composition = Composition::from_i32(privacy_definition.composition)
+        .ok_or_else(|| Error::from("composition: unrecognized composition"))?;
+
+    if composition == Composition::PrivacyLossDistribution {
+        return compose_privacy_loss_distributions(privacy_definition, &privacy_usages)
+    }
+
+    let (mut renyi, mut concentrated, mut loss, mut gaussian, mut approximate) = (vec![], vec![], vec![], vec![], vec![]);
+    privacy_usages.into_iter().cloned().try_for_each(|usage| {
+        match usage.distance.as_ref().ok_or("distance must be defined on a PrivacyUsage")? {
+            Distance::Renyi(_) => renyi.push(usage),
+            Distance::PrivacyLoss(_) => loss.push(usage),
+            // when composing via renyi differential privacy, concentrated and pure usages are tracked as renyi curves
+            Distance::Concentrated(_) if composition == Composition::Renyi =>
+                renyi.push(renyi_usage(LossModel::Gaussian(get_rho(&usage)?))),
+            Distance::Gaussian(distance) if composition == Composition::Renyi =>
+                renyi.push(renyi_usage(LossModel::Gaussian(distance.mu.powi(2) / 2.))),
+            Distance::Approximate(distance) if composition == Composition::Renyi && distance.delta == 0. =>
+                renyi.push(renyi_usage(LossModel::Pure(distance.epsilon))),
+            Distance::Concentrated(_) => concentrated.push(usage),
+            Distance::Gaussian(_) => gaussian.push(usage),
+            Distance::Approximate(_) => approximate.push(usage)
+        };
+        Ok::<_, Error>(())
+    })?;
+
+    // renyi usages compose by summing the curves, concentrated usages by summing rho,
+    // privacy loss distributions by convolution, and gaussian usages by the root sum of squares of mu
+    let composed = vec![renyi, concentrated, loss, gaussian].into_iter()
+        .map(|usages| usages.into_iter().map(Ok).fold1(|l, r| l? + r?).transpose())
+        .collect::<Result<Vec<Option<proto::PrivacyUsage>>>>()?.into_iter()
+        .flatten().collect::<Vec<proto::PrivacyUsage>>();
+
+    if composed.len() == 1 && approximate.is_empty() {
+        return Ok(composed[0].clone())
+    }
+
+    // when distances are mixed, usages are converted to (epsilon, delta)
+    if !composed.is_empty() {
+        check_conversion_delta(privacy_definition)?;
+    }
+    composed.into_iter()
+        .try_for_each(|usage| {
+            approximate.push(to_approximate(&usage, privacy_definition.conversion_delta)?);
+            Ok::<_, Error>(())
+        })?;
+
+    let usages = approximate.iter()
+        .map(|usage| Ok((get_epsilon(usage)?, get_delta(usage)?)))
+        .collect::<Result<Vec<(f64, f64)>>>()?;
+
+    let linear = (
+        usages.iter().map(|(epsilon, _)| epsilon).sum::<f64>(),
+        usages.iter().map(|(_, delta)| delta).sum::<f64>());
+
+    let (epsilon, delta) = match composition {
+        Composition::Linear | Composition::Renyi | Composition::PrivacyLossDistribution | Composition::Gaussian => linear,
+        Composition::Advanced | Composition::Optimal => {
+            let slack = privacy_definition.composition_delta;
+            if slack <= 0. || slack >= 1. {
+                bail!("composition_delta: must be within (0, 1) when using {:?} composition", composition)
+            }
+
+            let (epsilon, delta) = match composition {
+                Composition::Advanced => advanced_composition(&usages, slack),
+                _ => optimal_composition(&usages, slack)
+            };
+
+            // only spend the slack delta if it tightens epsilon
+            if epsilon < linear.0 { (epsilon, delta) } else { linear }
+        }
+    };
+
+    Ok(proto::PrivacyUsage {
+        distance: Some(proto::privacy_usage::Distance::Approximate(proto::privacy_usage::DistanceApproximate {
+            epsilon, delta,
+        }))
+    })
+}
+
+/// Compose privacy usages by convolving their privacy loss distributions.
+///
+/// Usages that are not already privacy loss distributions are dominated by randomized response,
+/// after converting them to (epsilon, delta) at the `conversion_delta` if necessary.
+///
+/// Koskela, Jälkö, Honkela. "Computing Tight Differential Privacy Guarantees Using FFT." AISTATS 2020.
+fn compose_privacy_loss_distributions(
+    privacy_definition: &proto::PrivacyDefinition,
+    privacy_usages: &[&proto::PrivacyUsage],
+) -> Result<proto::PrivacyUsage> {
+    use proto::privacy_usage::Distance;
+
+    let distributions = privacy_usages.iter()
+        .map(|usage| Ok(match usage.distance.as_ref().ok_or("distance must be defined on a PrivacyUsage")? {
+            Distance::PrivacyLoss(distance) => PrivacyLossDistribution::from(distance.clone()),
+            Distance::Gaussian(distance) => PrivacyLossDistribution::gaussian(distance.mu.powi(2) / 2.),
+            _ => {
+                let usage = to_approximate(usage, privacy_definition.conversion_delta)?;
+                PrivacyLossDistribution::randomized_response(get_epsilon(&usage)?, get_delta(&usage)?)
+            }
         }))
+        .collect::<Result<Vec<PrivacyLossDistribution>>>()?;
+
+    Ok(distributions.iter()
+        .try_fold(PrivacyLossDistribution::identity(), |composed, distribution| composed.compose(distribution))?
+        .into())
+}
+
+/// Advanced composition theorem for heterogeneous (epsilon, delta) mechanisms.
+///
+/// Dwork, Rothblum, Vadhan. "Boosting and Differential Privacy." FOCS 2010.
+fn advanced_composition(usages: &[(f64, f64)], slack: f64) -> (f64, f64) {
+    let sum_squares = usages.iter().map(|(epsilon, _)| epsilon.powi(2)).sum::<f64>();
+
+    let epsilon = (2. * (1. / slack).ln() * sum_squares).sqrt() + usages.iter()
+        .map(|(epsilon, _)| epsilon * epsilon.exp_m1())
+        .sum::<f64>();
+
+    (epsilon, usages.iter().map(|(_, delta)| delta).sum::<f64>() + slack)
+}
+
+/// Optimal composition theorem.
+///
+/// When all mechanisms share the same privacy usage, the exact optimal k-fold composition is used.
+/// Otherwise the bound for heterogeneous mechanisms is used.
+///
+/// Kairouz, Oh, Viswanath. "The Composition Theorem for Differential Privacy." ICML 2015.
+fn optimal_composition(usages: &[(f64, f64)], slack: f64) -> (f64, f64) {
+    if let Some((epsilon, delta)) = usages.first() {
+        if usages.iter().all(|usage| usage == &(*epsilon, *delta)) {
+            return optimal_homogeneous_composition(*epsilon, *delta, usages.len() as u64, slack)
+        }
+    }
+
+    let sum = usages.iter().map(|(epsilon, _)| epsilon).sum::<f64>();
+    let sum_squares = usages.iter().map(|(epsilon, _)| epsilon.powi(2)).sum::<f64>();
+    let offset = usages.iter()
+        .map(|(epsilon, _)| epsilon * epsilon.exp_m1() / (epsilon.exp() + 1.))
+        .sum::<f64>();
+
+    let epsilon = sum
+        .min(offset + (2. * (std::f64::consts::E + sum_squares.sqrt() / slack).ln() * sum_squares).sqrt())
+        .min(offset + (2. * (1. / slack).ln() * sum_squares).sqrt());
+
+    (epsilon, 1. - (1. - slack) * usages.iter().map(|(_, delta)| 1. - delta).product::<f64>())
+}
+
+/// Exact optimal composition of k mechanisms that are each (epsilon, delta)-DP.
+///
+/// The k-fold composition is ((k - 2i) epsilon, 1 - (1 - delta)^k (1 - delta_i))-DP for each i in 0..=k/2.
+/// Returns the smallest epsilon whose delta_i is within the slack.
+fn optimal_homogeneous_composition(epsilon: f64, delta: f64, k: u64, slack: f64) -> (f64, f64) {
+    use statrs::function::factorial::ln_binomial;
+
+    // log of the normalization term (1 + e^epsilon)^k
+    let ln_normalization = k as f64 * epsilon.exp().ln_1p();
+
+    let mut best = (k as f64 * epsilon, 0.);
+    for i in 1..=k / 2 {
+        let delta_i = (0..i)
+            .map(|l| {
+                let ln_binomial = ln_binomial(k, l);
+                (ln_binomial + (k - l) as f64 * epsilon - ln_normalization).exp()
+                    - (ln_binomial + (k + l - 2 * i) as f64 * epsilon - ln_normalization).exp()
+            })
+            .sum::<f64>();
+
+        // delta_i is increasing in i
+        if delta_i > slack { break }
+        best = ((k - 2 * i) as f64 * epsilon, delta_i);
+    }
+
+    (best.0, 1. - (1. - delta).powi(k as i32) * (1. - best.1))
 }
 
 /// Use a computation graph to partition privacy usages into batches.
@@ -287,27 +473,54 @@ pub fn compute_graph_privacy_usage(
 
     // return the max of the left and right privacy usages
     let max_usage = |l: Result<proto::PrivacyUsage>, r: Result<proto::PrivacyUsage>| -> Result<proto::PrivacyUsage> {
-        let proto::privacy_usage::DistanceApproximate {
-            epsilon: eps_l, delta: del_l
-        } = match l?.distance {
-            Some(proto::privacy_usage::Distance::Approximate(x)) => Ok(x),
-            _ => Err("expected approximate privacy")
-        }?;
-        let proto::privacy_usage::DistanceApproximate {
-            epsilon: eps_r, delta: del_r
-        } = match r?.distance {
-            Some(proto::privacy_usage::Distance::Approximate(x)) => Ok(x),
-            _ => Err("expected approximate privacy")
-        }?;
+        use proto::privacy_usage::Distance;
+        let (l, r) = (l?, r?);
+
+        match (&l.distance, &r.distance) {
+            (Some(Distance::Concentrated(l)), Some(Distance::Concentrated(r))) => return Ok(proto::PrivacyUsage {
+                distance: Some(Distance::Concentrated(proto::privacy_usage::DistanceConcentrated {
+                    rho: l.rho.max(r.rho)
+                }))
+            }),
+            (Some(Distance::Gaussian(l)), Some(Distance::Gaussian(r))) => return Ok(proto::PrivacyUsage {
+                distance: Some(Distance::Gaussian(proto::privacy_usage::DistanceGaussian {
+                    mu: l.mu.max(r.mu)
+                }))
+            }),
+            (Some(Distance::Renyi(l)), Some(Distance::Renyi(r))) if l.orders == r.orders => return Ok(proto::PrivacyUsage {
+                distance: Some(Distance::Renyi(proto::privacy_usage::DistanceRenyi {
+                    orders: l.orders.clone(),
+                    epsilons: l.epsilons.iter().zip(r.epsilons.iter())
+                        .map(|(l, r)| l.max(*r)).collect()
+                }))
+            }),
+            _ => ()
+        };
+
+        // usages of different distances are compared in terms of (epsilon, delta)
+        let (l, r) = (
+            to_approximate(&l, privacy_definition.conversion_delta)?,
+            to_approximate(&r, privacy_definition.conversion_delta)?);
 
         Ok(proto::PrivacyUsage {
-            distance: Some(proto::privacy_usage::Distance::Approximate(proto::privacy_usage::DistanceApproximate {
-                epsilon: eps_l.max(eps_r),
-                delta: del_l.max(del_r),
+            distance: Some(Distance::Approximate(proto::privacy_usage::DistanceApproximate {
+                epsilon: get_epsilon(&l)?.max(get_epsilon(&r)?),
+                delta: get_delta(&l)?.max(get_delta(&r)?),
             }))
         })
     };
 
+    // advanced and optimal composition spend the composition_delta once, when composing at the outer level,
+    //     so the usages within each part of a partition are composed linearly
+    use proto::privacy_definition::Composition;
+    let partition_definition = match Composition::from_i32(privacy_definition.composition) {
+        Some(Composition::Advanced) | Some(Composition::Optimal) => proto::PrivacyDefinition {
+            composition: Composition::Linear as i32,
+            ..privacy_definition.clone()
+        },
+        _ => privacy_definition.clone()
+    };
+
     // compute privacy usage of a subset of the graph,
     //     where the subset is indicated by a collection of node ids
     let compute_all_partitions_usage = |
@@ -316,13 +529,13 @@ pub fn compute_graph_privacy_usage(
         partition_ids.iter()
             .map(|partition_id| compute_graph_privacy_usage(
                 &get_downstream_graph(None, *partition_id)?,
-                privacy_definition, properties, release))
+                &partition_definition, properties, release))
             .fold1(max_usage)
             .unwrap_or_else(|| Ok(zero_usage()))
     };
 
     // compute the overall privacy usage
-    let partitions_usage: proto::PrivacyUsage = partition_ids.into_iter()
+    let partitions_usages = partition_ids.into_iter()
         // for each partition component...
         .map(|partition_node_id| {
             let partition_properties = properties.get(&partition_node_id)
@@ -339,25 +552,27 @@ pub fn compute_graph_privacy_usage(
 
                     let (batches, partition_ids) = batch_partition(
                         &unioned_downstream_graph, &release_privacy_usages)?;
-                    let batch_usages = batches.into_iter()
-                        .map(|(_, batch)| compute_batch_privacy_usage(batch))
-                        .fold1(|l, r| l? + r?)
-                        .unwrap_or_else(|| Ok(zero_usage()))?;
 
-                    batch_usages + compute_all_partitions_usage(partition_ids)?
+                    let mut usages = batches.into_values()
+                        .flatten().cloned()
+                        .collect::<Vec<proto::PrivacyUsage>>();
+                    usages.push(compute_all_partitions_usage(partition_ids)?);
+
+                    compose_privacy_usages(&partition_definition, &usages)
                 })
                 .fold1(max_usage)
                 .unwrap_or_else(|| Ok(zero_usage()))
         })
-        .fold1(|l, r| l? + r?)
-        .unwrap_or_else(|| Ok(zero_usage()))?;
+        .collect::<Result<Vec<proto::PrivacyUsage>>>()?;
 
-    let batch_usages = batches.into_iter()
-        .map(|(_, batch)| compute_batch_privacy_usage(batch))
-        .fold1(|l, r| l? + r?)
-        .unwrap_or_else(|| Ok(zero_usage()))?;
+    // batches and partitions are composed together,
+    //     so that advanced composition may be applied across all releases in the graph
+    let usages = batches.into_values()
+        .flatten().cloned()
+        .chain(partitions_usages)
+        .collect::<Vec<proto::PrivacyUsage>>();
 
-    batch_usages + partitions_usage
+    compose_privacy_usages(privacy_definition, &usages)
 }
 
 // pub fn privacy_usage_reducer(
@@ -421,16 +636,37 @@ pub fn privacy_usage_check(
                 }
             }
         }
+        proto::privacy_usage::Distance::Concentrated(usage) => {
+            if usage.rho <= 0.0 {
+                return Err("rho: privacy parameter rho must be greater than 0".into());
+            }
+        }
+        proto::privacy_usage::Distance::Gaussian(usage) => {
+            if usage.mu <= 0.0 {
+                return Err("mu: privacy parameter mu must be greater than 0".into());
+            }
+        }
+        proto::privacy_usage::Distance::Renyi(_) | proto::privacy_usage::Distance::PrivacyLoss(_) =>
+            return Err("privacy usages reported by the accountant may not be requested".into())
     };
 
     Ok(warnings)
 }
 
+/// Check that a privacy usage is in terms of (epsilon, delta), for mechanisms that are only defined under that distance.
+pub fn approximate_usage_check(privacy_usage: &proto::PrivacyUsage) -> Result<()> {
+    match privacy_usage.distance
+        .as_ref().ok_or_else(|| "usage distance must be defined")? {
+        proto::privacy_usage::Distance::Approximate(_) => Ok(()),
+        _ => Err("privacy_usage: the mechanism is only defined for (epsilon, delta) privacy usages".into())
+    }
+}
+
 pub fn get_epsilon(usage: &proto::PrivacyUsage) -> Result<f64> {
     match usage.distance.clone()
         .ok_or_else(|| Error::from("distance must be defined on a PrivacyUsage"))? {
         proto::privacy_usage::Distance::Approximate(distance) => Ok(distance.epsilon),
-//        _ => Err("epsilon is not defined".into())
+        _ => Err("epsilon is only defined on approximate privacy usages".into())
     }
 }
 
@@ -438,10 +674,261 @@ pub fn get_delta(usage: &proto::PrivacyUsage) -> Result<f64> {
     match usage.distance.clone()
         .ok_or_else(|| Error::from("distance must be defined on a PrivacyUsage"))? {
         proto::privacy_usage::Distance::Approximate(distance) => Ok(distance.delta),
-        // _ => Err("delta is not defined".into())
+        _ => Err("delta is only defined on approximate privacy usages".into())
     }
 }
 
+pub fn get_rho(usage: &proto::PrivacyUsage) -> Result<f64> {
+    match usage.distance.clone()
+        .ok_or_else(|| Error::from("distance must be defined on a PrivacyUsage"))? {
+        proto::privacy_usage::Distance::Concentrated(distance) => Ok(distance.rho),
+        _ => Err("rho is only defined on concentrated privacy usages".into())
+    }
+}
+
+pub fn get_mu(usage: &proto::PrivacyUsage) -> Result<f64> {
+    match usage.distance.clone()
+        .ok_or_else(|| Error::from("distance must be defined on a PrivacyUsage"))? {
+        proto::privacy_usage::Distance::Gaussian(distance) => Ok(distance.mu),
+        _ => Err("mu is only defined on gaussian privacy usages".into())
+    }
+}
+
+fn is_zero_usage(usage: &proto::PrivacyUsage) -> Result<bool> {
+    Ok(match usage.distance.as_ref()
+        .ok_or_else(|| Error::from("distance must be defined on a PrivacyUsage"))? {
+        proto::privacy_usage::Distance::Approximate(distance) => distance.epsilon == 0. && distance.delta == 0.,
+        proto::privacy_usage::Distance::Concentrated(distance) => distance.rho == 0.,
+        proto::privacy_usage::Distance::Gaussian(distance) => distance.mu == 0.,
+        proto::privacy_usage::Distance::Renyi(distance) => distance.epsilons.iter().all(|epsilon| *epsilon == 0.),
+        proto::privacy_usage::Distance::PrivacyLoss(distance) => distance.infinity_mass == 0. && distance.masses.iter()
+            .enumerate().all(|(i, mass)| *mass == 0. || distance.offset + i as i64 <= 0)
+    })
+}
+
+/// Convert a concentrated, Rényi, privacy loss distribution or gaussian privacy usage to an (epsilon, delta) privacy usage at the given delta.
+///
+/// Approximate privacy usages are returned unchanged.
+pub fn to_approximate(usage: &proto::PrivacyUsage, delta: f64) -> Result<proto::PrivacyUsage> {
+    let distance = usage.distance.as_ref()
+        .ok_or_else(|| Error::from("distance must be defined on a PrivacyUsage"))?;
+
+    if let proto::privacy_usage::Distance::Approximate(_) = distance {
+        return Ok(usage.clone())
+    }
+
+    if delta <= 0. || delta >= 1. {
+        return Err("delta: must be within (0, 1) to convert a privacy usage to (epsilon, delta)".into())
+    }
+
+    let epsilon = match distance {
+        // Bun, Steinke. "Concentrated Differential Privacy: Simplifications, Extensions, and Lower Bounds." TCC 2016.
+        proto::privacy_usage::Distance::Concentrated(distance) =>
+            distance.rho + 2. * (distance.rho * (1. / delta).ln()).sqrt(),
+
+        // choose the order that minimizes epsilon
+        // Canonne, Kamath, Steinke. "The Discrete Gaussian for Differential Privacy." NeurIPS 2020. Proposition 12.
+        proto::privacy_usage::Distance::Renyi(distance) => distance.orders.iter()
+            .zip(distance.epsilons.iter())
+            .map(|(alpha, epsilon)| epsilon + (-1. / alpha).ln_1p() - (delta.ln() + alpha.ln()) / (alpha - 1.))
+            .fold(f64::INFINITY, f64::min)
+            .max(0.),
+
+        // the smallest epsilon for which the hockey-stick divergence is at most delta
+        proto::privacy_usage::Distance::PrivacyLoss(distance) =>
+            PrivacyLossDistribution::from(distance.clone()).get_epsilon(delta)?,
+
+        proto::privacy_usage::Distance::Gaussian(distance) => gaussian_dp_epsilon(distance.mu, delta),
+        proto::privacy_usage::Distance::Approximate(_) => unreachable!()
+    };
+
+    Ok(proto::PrivacyUsage {
+        distance: Some(proto::privacy_usage::Distance::Approximate(proto::privacy_usage::DistanceApproximate {
+            epsilon, delta,
+        }))
+    })
+}
+
+/// Check that the privacy definition permits converting privacy usages to (epsilon, delta).
+///
+/// The `conversion_delta` is separate from the `composition_delta`,
+/// so that the slack spent by advanced and optimal composition is not also used as the target of a conversion.
+pub fn check_conversion_delta(privacy_definition: &proto::PrivacyDefinition) -> Result<()> {
+    let delta = privacy_definition.conversion_delta;
+    if delta <= 0. || delta >= 1. {
+        bail!("conversion_delta: must be within (0, 1) to express concentrated, renyi, privacy loss distribution or gaussian privacy usages in terms of (epsilon, delta), but is {}", delta)
+    }
+    Ok(())
+}
+
+/// Delta of a mu-GDP mechanism at the given epsilon.
+///
+/// Dong, Roth, Su. "Gaussian Differential Privacy." JRSS-B 2022. Corollary 2.13.
+pub fn gaussian_dp_delta(mu: f64, epsilon: f64) -> f64 {
+    // standard normal cdf
+    let phi = |x: f64| erf::erfc(-x / 2f64.sqrt()) / 2.;
+    (phi(-epsilon / mu + mu / 2.) - epsilon.exp() * phi(-epsilon / mu - mu / 2.)).max(0.)
+}
+
+/// Smallest epsilon at which a mu-GDP mechanism satisfies (epsilon, delta)-DP.
+pub fn gaussian_dp_epsilon(mu: f64, delta: f64) -> f64 {
+    if gaussian_dp_delta(mu, 0.) <= delta {
+        return 0.
+    }
+
+    // delta is decreasing in epsilon, so bracket the solution and bisect
+    let mut upper = 1.;
+    while gaussian_dp_delta(mu, upper) > delta {
+        upper *= 2.;
+    }
+    let mut lower = 0.;
+    (0..100).for_each(|_| {
+        let middle = (lower + upper) / 2.;
+        if gaussian_dp_delta(mu, middle) > delta { lower = middle } else { upper = middle }
+    });
+    upper
+}
+
+/// Orders at which Rényi divergences are tracked when composing via Rényi differential privacy.
+pub fn get_renyi_orders() -> Vec<f64> {
+    (1..100).map(|v| 1. + v as f64 / 10.)
+        .chain((11..64).map(|v| v as f64))
+        .chain(vec![128., 256., 512.])
+        .collect()
+}
+
+/// Privacy loss of a mechanism, used by the accountant to build Rényi curves and privacy loss distributions.
+pub enum LossModel {
+    /// any epsilon-DP mechanism, bounded by min(epsilon, alpha epsilon^2 / 2)
+    Pure(f64),
+    /// the laplace mechanism with privacy loss parameter epsilon
+    Laplace(f64),
+    /// the gaussian mechanism, where rho = (sensitivity / sigma)^2 / 2
+    Gaussian(f64),
+    /// the discrete laplace (geometric) mechanism with privacy loss parameter epsilon,
+    /// where the outputs on neighboring datasets are shifted by the given number of integer steps
+    DiscreteLaplace(f64, u64),
+    /// any epsilon-bounded range mechanism, such as the exponential mechanism
+    BoundedRange(f64),
+}
+
+impl LossModel {
+    /// Rényi divergence of the mechanism at order alpha.
+    pub fn renyi_epsilon(&self, alpha: f64) -> f64 {
+        match self {
+            // Bun, Steinke. "Concentrated Differential Privacy: Simplifications, Extensions, and Lower Bounds." TCC 2016. Proposition 1.4.
+            LossModel::Pure(epsilon) => epsilon.min(alpha * epsilon.powi(2) / 2.),
+            // Mironov. "Rényi Differential Privacy." CSF 2017. Proposition 6.
+            LossModel::Laplace(epsilon) => {
+                let terms = [
+                    (alpha / (2. * alpha - 1.)).ln() + (alpha - 1.) * epsilon,
+                    ((alpha - 1.) / (2. * alpha - 1.)).ln() - alpha * epsilon];
+                let max = terms[0].max(terms[1]);
+                let log_sum = max + ((terms[0] - max).exp() + (terms[1] - max).exp()).ln();
+                // the laplace mechanism is also epsilon-DP
+                (log_sum / (alpha - 1.)).min(*epsilon)
+            }
+            LossModel::Gaussian(rho) => alpha * rho,
+            // the privacy loss takes the value epsilon (steps - 2k) / steps when the noise is k, for k clamped to [0, steps],
+            //     so the divergence is a geometric series over the interior of the support
+            LossModel::DiscreteLaplace(epsilon, steps) => {
+                let steps = *steps as f64;
+                let ln_normalization = (-epsilon / steps).exp().ln_1p();
+                let mut terms = vec![
+                    (alpha - 1.) * epsilon - ln_normalization,
+                    -alpha * epsilon - ln_normalization];
+                if steps > 1. {
+                    // sum of q^k for k in 1..steps
+                    let ln_ratio = -epsilon * (2. * alpha - 1.) / steps;
+                    terms.push((-(-epsilon / steps).exp_m1()).ln() - ln_normalization + (alpha - 1.) * epsilon
+                        + ln_ratio + (-((steps - 1.) * ln_ratio).exp_m1()).ln() - (-ln_ratio.exp_m1()).ln());
+                }
+                let max = terms.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+                let log_sum = max + terms.iter().map(|term| (term - max).exp()).sum::<f64>().ln();
+                (log_sum / (alpha - 1.)).min(*epsilon)
+            }
+            // Cesar, Rogers. "Bounding, Concentrating, and Truncating: Unifying Privacy Loss Composition for Data Analytics." ALT 2021.
+            //     an epsilon-bounded range mechanism is epsilon^2 / 8-zCDP
+            LossModel::BoundedRange(epsilon) => LossModel::Pure(*epsilon).renyi_epsilon(alpha)
+                .min(alpha * epsilon.powi(2) / 8.)
+        }
+    }
+
+    /// Privacy loss distribution of the mechanism.
+    pub fn privacy_loss_distribution(&self) -> PrivacyLossDistribution {
+        match self {
+            // randomized response dominates every epsilon-DP mechanism
+            LossModel::Pure(epsilon) => PrivacyLossDistribution::randomized_response(*epsilon, 0.),
+            LossModel::Laplace(epsilon) => PrivacyLossDistribution::laplace(*epsilon),
+            LossModel::Gaussian(rho) => PrivacyLossDistribution::gaussian(*rho),
+            LossModel::DiscreteLaplace(epsilon, steps) => PrivacyLossDistribution::discrete_laplace(*epsilon, *steps),
+            // an epsilon-bounded range mechanism is also epsilon-DP, so it is dominated by randomized response as well
+            LossModel::BoundedRange(epsilon) => PrivacyLossDistribution::randomized_response(*epsilon, 0.),
+        }
+    }
+}
+
+/// Evaluate a Rényi curve at each of the Rényi orders.
+pub fn renyi_usage(curve: LossModel) -> proto::PrivacyUsage {
+    let orders = get_renyi_orders();
+    proto::PrivacyUsage {
+        distance: Some(proto::privacy_usage::Distance::Renyi(proto::privacy_usage::DistanceRenyi {
+            epsilons: orders.iter().map(|alpha| curve.renyi_epsilon(*alpha)).collect(),
+            orders,
+        }))
+    }
+}
+
+/// Convert the effective privacy usages a mechanism was evaluated with into actual privacy usages.
+///
+/// When composing via Rényi differential privacy, usages are reported as Rényi curves,
+/// when composing via privacy loss distributions, usages are reported as privacy loss distributions,
+/// and when composing via gaussian differential privacy, gaussian mechanisms are reported in terms of mu.
+/// `get_curve` returns the loss model of an effective usage, when the sensitivity is scaled by the stability multiplier.
+/// Privacy amplification by subsampling is not tracked by these accountants, so subsampled usages are always (epsilon, delta).
+pub fn get_mechanism_privacy_usages(
+    privacy_definition: &proto::PrivacyDefinition,
+    usages: &[proto::PrivacyUsage],
+    data_property: &ArrayProperties,
+    get_curve: impl Fn(&proto::PrivacyUsage, f64) -> Result<LossModel>,
+) -> Result<Vec<proto::PrivacyUsage>> {
+    let sample_proportion = data_property.sample_proportion.unwrap_or(1.);
+    use proto::privacy_definition::Composition;
+    let composition = Composition::from_i32(privacy_definition.composition)
+        .ok_or_else(|| Error::from("composition: unrecognized composition"))?;
+
+    if privacy_definition.group_size == 0 {
+        return Err(Error::from("group size must be greater than zero"))
+    }
+    let stability = (data_property.c_stability * privacy_definition.group_size) as f64;
+
+    usages.iter()
+        .map(|usage| {
+            if sample_proportion == 1. {
+                match composition {
+                    Composition::Renyi =>
+                        return Ok(renyi_usage(get_curve(usage, stability)?)),
+                    Composition::PrivacyLossDistribution =>
+                        return Ok(get_curve(usage, stability)?.privacy_loss_distribution().into()),
+                    // only gaussian mechanisms are reported as mu-GDP
+                    Composition::Gaussian => if let LossModel::Gaussian(rho) = get_curve(usage, stability)? {
+                        return Ok(proto::PrivacyUsage {
+                            distance: Some(proto::privacy_usage::Distance::Gaussian(proto::privacy_usage::DistanceGaussian {
+                                mu: (2. * rho).sqrt()
+                            }))
+                        })
+                    },
+                    _ => ()
+                }
+            }
+            usage.effective_to_actual(
+                sample_proportion,
+                data_property.c_stability,
+                privacy_definition)
+        })
+        .collect()
+}
+
 pub fn spread_privacy_usage(usages: &[proto::PrivacyUsage], length: usize) -> Result<Vec<proto::PrivacyUsage>> {
     if usages.len() == length {
         return Ok(usages.to_owned());
@@ -462,7 +949,21 @@ pub fn spread_privacy_usage(usages: &[proto::PrivacyUsage], length: usize) -> Re
                     epsilon: approx.epsilon / (length as f64),
                     delta: approx.delta / (length as f64),
                 }))
-            }).collect()
+            }).collect(),
+        proto::privacy_usage::Distance::Concentrated(concentrated) => (0..length)
+            .map(|_| proto::PrivacyUsage {
+                distance: Some(proto::privacy_usage::Distance::Concentrated(proto::privacy_usage::DistanceConcentrated {
+                    rho: concentrated.rho / (length as f64),
+                }))
+            }).collect(),
+        proto::privacy_usage::Distance::Gaussian(gaussian) => (0..length)
+            .map(|_| proto::PrivacyUsage {
+                distance: Some(proto::privacy_usage::Distance::Gaussian(proto::privacy_usage::DistanceGaussian {
+                    mu: gaussian.mu / (length as f64).sqrt(),
+                }))
+            }).collect(),
+        proto::privacy_usage::Distance::Renyi(_) | proto::privacy_usage::Distance::PrivacyLoss(_) =>
+            return Err("privacy usages reported by the accountant may not be spread".into())
     })
 }
 
@@ -519,4 +1020,168 @@ pub fn get_c_stability_multiplier(arguments: Vec<Vec<GroupId>>) -> Result<u32> {
         *counts.entry(group_id.index).or_insert(0) += 1);
 
     Ok(*counts.values().max().unwrap())
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod test_privacy {
+    use crate::proto;
+    use crate::utilities::privacy::{compose_privacy_usages, gaussian_dp_delta, get_delta, get_epsilon, get_mu, get_renyi_orders, get_rho, LossModel, to_approximate};
+
+    fn usage(epsilon: f64, delta: f64) -> proto::PrivacyUsage {
+        proto::PrivacyUsage {
+            distance: Some(proto::privacy_usage::Distance::Approximate(proto::privacy_usage::DistanceApproximate {
+                epsilon, delta,
+            }))
+        }
+    }
+
+    fn concentrated(rho: f64) -> proto::PrivacyUsage {
+        proto::PrivacyUsage {
+            distance: Some(proto::privacy_usage::Distance::Concentrated(proto::privacy_usage::DistanceConcentrated {
+                rho
+            }))
+        }
+    }
+
+    fn definition(composition: proto::privacy_definition::Composition, composition_delta: f64) -> proto::PrivacyDefinition {
+        proto::PrivacyDefinition {
+            group_size: 1,
+            composition: composition as i32,
+            composition_delta,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_linear_composition() {
+        use proto::privacy_definition::Composition;
+        let composed = compose_privacy_usages(
+            &definition(Composition::Linear, 0.), &vec![usage(0.1, 1e-7); 10]).unwrap();
+        assert!((get_epsilon(&composed).unwrap() - 1.).abs() < 1e-10);
+        assert!((get_delta(&composed).unwrap() - 1e-6).abs() < 1e-16);
+    }
+
+    #[test]
+    fn test_advanced_composition() {
+        use proto::privacy_definition::Composition;
+        let advanced = definition(Composition::Advanced, 1e-6);
+
+        // epsilon = sqrt(2 k ln(1 / delta')) epsilon_0 + k epsilon_0 (e^epsilon_0 - 1)
+        let composed = compose_privacy_usages(&advanced, &vec![usage(0.01, 0.); 1000]).unwrap();
+        assert!((get_epsilon(&composed).unwrap() - 1.7627598).abs() < 1e-6);
+        assert!((get_delta(&composed).unwrap() - 1e-6).abs() < 1e-16);
+
+        // linear composition is tighter for few releases, so no slack is spent
+        let composed = compose_privacy_usages(&advanced, &[usage(1., 0.), usage(1., 0.)]).unwrap();
+        assert_eq!(get_epsilon(&composed).unwrap(), 2.);
+        assert_eq!(get_delta(&composed).unwrap(), 0.);
+
+        assert!(compose_privacy_usages(&definition(Composition::Advanced, 0.), &[usage(1., 0.)]).is_err());
+    }
+
+    #[test]
+    fn test_optimal_composition() {
+        use proto::privacy_definition::Composition;
+        let usages = vec![usage(0.01, 0.); 1000];
+
+        // the largest i with delta_i within the slack is i = 431, so epsilon = (1000 - 2 * 431) * 0.01
+        let optimal = compose_privacy_usages(
+            &definition(Composition::Optimal, 1e-6), &usages).unwrap();
+        assert!((get_epsilon(&optimal).unwrap() - 1.38).abs() < 1e-10);
+        assert!((get_delta(&optimal).unwrap() - 8.0181678e-7).abs() < 1e-13);
+
+        // heterogeneous usages are bounded by the second of the three bounds
+        let usages = (1..=1000).map(|i| usage(0.01 + (i % 2) as f64 * 0.001, 0.))
+            .collect::<Vec<proto::PrivacyUsage>>();
+        let optimal = compose_privacy_usages(
+            &definition(Composition::Optimal, 1e-6), &usages).unwrap();
+        assert!((get_epsilon(&optimal).unwrap() - 1.7315033).abs() < 1e-6);
+        assert!((get_delta(&optimal).unwrap() - 1e-6).abs() < 1e-16);
+    }
+
+    #[test]
+    fn test_concentrated_composition() {
+        use proto::privacy_definition::Composition;
+        let linear = proto::PrivacyDefinition {
+            conversion_delta: 1e-6,
+            ..definition(Composition::Linear, 0.)
+        };
+
+        let composed = compose_privacy_usages(&linear, &vec![concentrated(0.01); 10]).unwrap();
+        assert!((get_rho(&composed).unwrap() - 0.1).abs() < 1e-10);
+
+        // rho = 0.1 at delta = 1e-6 is converted to epsilon = 0.1 + 2 sqrt(0.1 ln(1e6))
+        let converted = to_approximate(&composed, 1e-6).unwrap();
+        assert!((get_epsilon(&converted).unwrap() - 2.45087).abs() < 1e-4);
+
+        // mixed distances are composed in terms of (epsilon, delta)
+        let composed = compose_privacy_usages(&linear, &[concentrated(0.1), usage(1., 1e-7)]).unwrap();
+        assert!((get_epsilon(&composed).unwrap() - 3.45087).abs() < 1e-4);
+        assert!((get_delta(&composed).unwrap() - 1.1e-6).abs() < 1e-16);
+
+        // a conversion delta is necessary to mix distances, even when a composition delta is given
+        assert!(compose_privacy_usages(
+            &definition(Composition::Linear, 1e-6), &[concentrated(0.1), usage(1., 0.)]).is_err());
+    }
+
+    #[test]
+    fn test_renyi_curves() {
+        get_renyi_orders().into_iter().for_each(|alpha| {
+            let laplace = LossModel::Laplace(0.5).renyi_epsilon(alpha);
+            assert!(laplace > 0.);
+            assert!(laplace <= LossModel::Pure(0.5).renyi_epsilon(alpha) + 1e-12);
+        });
+
+        // the laplace curve approaches epsilon as the order grows
+        assert!((LossModel::Laplace(0.5).renyi_epsilon(512.) - 0.5).abs() < 0.01);
+
+        get_renyi_orders().into_iter().for_each(|alpha| {
+            // with one step, the discrete laplace mechanism is randomized response
+            let randomized_response = (((alpha * 0.5f64).exp() + ((1. - alpha) * 0.5f64).exp()) / (1. + 0.5f64.exp())).ln() / (alpha - 1.);
+            assert!((LossModel::DiscreteLaplace(0.5, 1).renyi_epsilon(alpha) - randomized_response.min(0.5)).abs() < 1e-10);
+
+            // with many steps, the discrete laplace mechanism approaches the laplace mechanism
+            assert!((LossModel::DiscreteLaplace(0.5, 10_000).renyi_epsilon(alpha) - LossModel::Laplace(0.5).renyi_epsilon(alpha)).abs() < 1e-3);
+
+            // bounded range mechanisms are epsilon^2 / 8-zCDP
+            assert!(LossModel::BoundedRange(0.5).renyi_epsilon(alpha) <= alpha * 0.5f64.powi(2) / 8.);
+        });
+    }
+
+    #[test]
+    fn test_renyi_composition() {
+        use proto::privacy_definition::Composition;
+        let renyi = definition(Composition::Renyi, 1e-6);
+
+        let composed = compose_privacy_usages(&renyi, &vec![concentrated(0.001); 100]).unwrap();
+        let epsilon = get_epsilon(&to_approximate(&composed, 1e-6).unwrap()).unwrap();
+
+        // conversion via renyi divergence is tighter than the conversion from zCDP
+        let zcdp = get_epsilon(&to_approximate(&concentrated(0.1), 1e-6).unwrap()).unwrap();
+        assert!(epsilon < zcdp);
+
+        // pure usages are composed as renyi curves
+        let composed = compose_privacy_usages(&renyi, &vec![usage(0.01, 0.); 1000]).unwrap();
+        assert!(get_epsilon(&to_approximate(&composed, 1e-6).unwrap()).unwrap() < 10.);
+    }
+
+    #[test]
+    fn test_gaussian_composition() {
+        use proto::privacy_definition::Composition;
+        let gaussian = |mu| proto::PrivacyUsage {
+            distance: Some(proto::privacy_usage::Distance::Gaussian(proto::privacy_usage::DistanceGaussian { mu }))
+        };
+
+        // mu composes by the root sum of squares
+        let composed = compose_privacy_usages(&definition(Composition::Gaussian, 1e-6), &vec![gaussian(0.1); 100]).unwrap();
+        assert!((get_mu(&composed).unwrap() - 1.).abs() < 1e-12);
+
+        // the conversion is exact
+        let epsilon = get_epsilon(&to_approximate(&composed, 1e-6).unwrap()).unwrap();
+        assert!((gaussian_dp_delta(1., epsilon) - 1e-6).abs() < 1e-12);
+
+        // the exact conversion is tighter than the conversion from zCDP with rho = mu^2 / 2
+        let zcdp = get_epsilon(&to_approximate(&concentrated(0.5), 1e-6).unwrap()).unwrap();
+        assert!(epsilon < zcdp);
+    }
+}
@@ -0,0 +1,269 @@
+//! Privacy filters and odometers, for analyses where later privacy usages are chosen adaptively.
+//!
+//! A filter decides whether the next mechanism may be released without exceeding a fixed global budget,
+//! even when each usage was chosen based on the outcomes of earlier releases.
+//! An odometer bounds the privacy spent so far, at any point in an adaptive analysis.
+
+use std::collections::HashMap;
+
+use crate::proto;
+use crate::base::{Release, ValueProperties};
+use crate::components::Mechanism;
+use crate::errors::*;
+use crate::utilities::get_input_properties;
+use crate::utilities::privacy::{gaussian_dp_delta, get_delta, get_epsilon, get_mu, get_renyi_orders, LossModel, renyi_usage, to_approximate};
+
+/// The smallest epsilon on the grid of budgets that the odometer is reported on.
+const ODOMETER_MIN_EPSILON: f64 = 1e-2;
+
+/// Ratio between consecutive epsilons on the grid of budgets that the odometer is reported on.
+const ODOMETER_GROWTH: f64 = 1.05;
+
+/// Number of budgets on the grid that the odometer is reported on.
+const ODOMETER_STEPS: i32 = 500;
+
+/// Collect the actual privacy usages of every mechanism that has already been released.
+///
+/// Parallel composition over partitions is not considered, so the usages bound the privacy spent.
+pub fn get_released_privacy_usages(
+    graph: &HashMap<u32, proto::Component>,
+    privacy_definition: &proto::PrivacyDefinition,
+    properties: &HashMap<u32, ValueProperties>,
+    release: &Release,
+) -> Result<Vec<proto::PrivacyUsage>> {
+    let mut node_ids = release.iter()
+        .filter(|(_, release_node)| release_node.privacy_usages.is_some())
+        .map(|(node_id, _)| *node_id)
+        .collect::<Vec<u32>>();
+    node_ids.sort_unstable();
+
+    Ok(node_ids.into_iter()
+        .filter_map(|node_id| graph.get(&node_id).map(|component| (node_id, component)))
+        .map(|(node_id, component)| component.get_privacy_usage(
+            privacy_definition,
+            release.get(&node_id).and_then(|v| v.privacy_usages.as_ref()),
+            &get_input_properties(component, properties)?))
+        .collect::<Result<Vec<Option<Vec<proto::PrivacyUsage>>>>>()?
+        .into_iter().flatten().flatten()
+        .collect())
+}
+
+/// Decide if a privacy filter permits the last of the usages to be released.
+///
+/// `usages` are the usages of all prior releases, followed by the usage of the next release.
+/// The filter is derived from the composition in the privacy definition,
+/// and guarantees that the adaptively composed usages never exceed the (epsilon, delta) `budget`.
+pub fn privacy_filter_continues(
+    privacy_definition: &proto::PrivacyDefinition,
+    budget: &proto::PrivacyUsage,
+    usages: &[proto::PrivacyUsage],
+) -> Result<bool> {
+    filter_continues(
+        privacy_definition,
+        (get_epsilon(budget)?, get_delta(budget)?),
+        privacy_definition.composition_delta,
+        usages)
+}
+
+/// Bound the privacy spent by adaptively chosen usages, in terms of (epsilon, delta).
+///
+/// Under linear composition, the usages are summed.
+/// Otherwise, the odometer runs a filter at each budget on a geometric grid of epsilons,
+/// and reports the smallest budget whose filter has not halted.
+/// The `composition_delta` is divided among the filters on the grid.
+///
+/// Rogers, Roth, Ullman, Vadhan. "Privacy Odometers and Filters: Pay-as-you-Go Composition." NeurIPS 2016.
+/// Whitehouse, Ramdas, Rogers, Wu. "Fully-Adaptive Composition in Differential Privacy." ICML 2023.
+pub fn privacy_odometer(
+    privacy_definition: &proto::PrivacyDefinition,
+    usages: &[proto::PrivacyUsage],
+) -> Result<proto::PrivacyUsage> {
+    use proto::privacy_definition::Composition;
+    let composition = Composition::from_i32(privacy_definition.composition)
+        .ok_or_else(|| Error::from("composition: unrecognized composition"))?;
+
+    let approximate = |epsilon, delta| proto::PrivacyUsage {
+        distance: Some(proto::privacy_usage::Distance::Approximate(proto::privacy_usage::DistanceApproximate {
+            epsilon, delta
+        }))
+    };
+
+    if composition == Composition::Linear {
+        let (epsilon, delta) = get_linear_usage(privacy_definition, usages)?;
+        return Ok(approximate(epsilon, delta))
+    }
+
+    let slack = privacy_definition.composition_delta;
+    if slack <= 0. || slack >= 1. {
+        bail!("composition_delta: must be within (0, 1) for a privacy odometer under {:?} composition", composition)
+    }
+
+    // the deltas of approximate usages are spent in addition to the slack of the filter
+    let mechanism_delta = usages.iter()
+        .filter_map(|usage| match &usage.distance {
+            Some(proto::privacy_usage::Distance::Approximate(distance)) => Some(distance.delta),
+            _ => None
+        })
+        .sum::<f64>();
+
+    for step in 0..ODOMETER_STEPS {
+        let epsilon = ODOMETER_MIN_EPSILON * ODOMETER_GROWTH.powi(step);
+        // the slacks of all filters on the grid sum to at most the composition_delta
+        let step_slack = slack * 6. / (std::f64::consts::PI * (step + 1) as f64).powi(2);
+
+        if filter_continues(privacy_definition, (epsilon, mechanism_delta + step_slack), step_slack, usages)? {
+            return Ok(approximate(epsilon, mechanism_delta + slack))
+        }
+    }
+    Err("privacy odometer: the privacy usage exceeds the largest budget of the odometer".into())
+}
+
+fn filter_continues(
+    privacy_definition: &proto::PrivacyDefinition,
+    (budget_epsilon, budget_delta): (f64, f64),
+    slack: f64,
+    usages: &[proto::PrivacyUsage],
+) -> Result<bool> {
+    use proto::privacy_definition::Composition;
+    use proto::privacy_usage::Distance;
+
+    let composition = Composition::from_i32(privacy_definition.composition)
+        .ok_or_else(|| Error::from("composition: unrecognized composition"))?;
+
+    Ok(match composition {
+        // basic composition holds under adaptively chosen usages
+        Composition::Linear => {
+            let (epsilon, delta) = get_linear_usage(privacy_definition, usages)?;
+            epsilon <= budget_epsilon && delta <= budget_delta
+        }
+
+        // Whitehouse, Ramdas, Rogers, Wu. "Fully-Adaptive Composition in Differential Privacy." ICML 2023.
+        Composition::Advanced => {
+            if slack <= 0. || slack >= 1. {
+                bail!("composition_delta: must be within (0, 1) for a privacy filter under advanced composition")
+            }
+            let usages = usages.iter()
+                .map(|usage| Ok((get_epsilon(usage)?, get_delta(usage)?)))
+                .collect::<Result<Vec<(f64, f64)>>>()?;
+            let sum_squares = usages.iter().map(|(epsilon, _)| epsilon.powi(2)).sum::<f64>();
+            let epsilon = (2. * (1. / slack).ln() * sum_squares).sqrt() + sum_squares / 2.;
+            let delta = usages.iter().map(|(_, delta)| delta).sum::<f64>() + slack;
+            epsilon <= budget_epsilon && delta <= budget_delta
+        }
+
+        // the filter continues while any order has remaining budget
+        // Lécuyer. "Practical Privacy Filters and Odometers with Rényi Differential Privacy." 2021.
+        Composition::Renyi => {
+            let orders = get_renyi_orders();
+            let mut totals = vec![0.; orders.len()];
+            usages.iter().try_for_each(|usage| {
+                let curve = match usage.distance.as_ref().ok_or("distance must be defined on a PrivacyUsage")? {
+                    Distance::Renyi(_) => usage.clone(),
+                    Distance::Concentrated(distance) => renyi_usage(LossModel::Gaussian(distance.rho)),
+                    Distance::Gaussian(distance) => renyi_usage(LossModel::Gaussian(distance.mu.powi(2) / 2.)),
+                    Distance::Approximate(distance) if distance.delta == 0. => renyi_usage(LossModel::Pure(distance.epsilon)),
+                    _ => return Err(Error::from("privacy filters under renyi composition require usages with a renyi curve"))
+                };
+                match curve.distance {
+                    Some(Distance::Renyi(curve)) if curve.orders == orders => totals.iter_mut()
+                        .zip(curve.epsilons.iter())
+                        .for_each(|(total, epsilon)| *total += epsilon),
+                    _ => return Err(Error::from("renyi privacy usages must be defined over the same orders"))
+                };
+                Ok(())
+            })?;
+
+            orders.iter().zip(totals.iter())
+                .any(|(alpha, total)| *total <= budget_epsilon - (1. / budget_delta).ln() / (alpha - 1.))
+        }
+
+        // Smith, Thakurta. "Fully Adaptive Composition for Gaussian Differential Privacy." 2022.
+        Composition::Gaussian => {
+            let sum_squares = usages.iter()
+                .map(|usage| get_mu(usage).map(|mu| mu.powi(2)))
+                .collect::<Result<Vec<f64>>>()
+                .map_err(|_| Error::from("privacy filters under gaussian composition require gaussian usages"))?
+                .into_iter().sum::<f64>();
+            sum_squares.sqrt() <= get_gaussian_budget(budget_epsilon, budget_delta)
+        }
+
+        Composition::Optimal | Composition::PrivacyLossDistribution =>
+            bail!("privacy filters are not supported under {:?} composition", composition)
+    })
+}
+
+/// Sum a collection of privacy usages in terms of (epsilon, delta).
+fn get_linear_usage(
+    privacy_definition: &proto::PrivacyDefinition,
+    usages: &[proto::PrivacyUsage],
+) -> Result<(f64, f64)> {
+    usages.iter()
+        .map(|usage| to_approximate(usage, privacy_definition.conversion_delta))
+        .try_fold((0., 0.), |(epsilon, delta), usage| {
+            let usage = usage?;
+            Ok((epsilon + get_epsilon(&usage)?, delta + get_delta(&usage)?))
+        })
+}
+
+/// Largest mu for which mu-GDP implies (epsilon, delta)-DP.
+fn get_gaussian_budget(epsilon: f64, delta: f64) -> f64 {
+    // delta is increasing in mu, so bracket the solution and bisect
+    let mut upper = 1.;
+    while gaussian_dp_delta(upper, epsilon) < delta {
+        upper *= 2.;
+    }
+    let mut lower = 0.;
+    (0..100).for_each(|_| {
+        let middle = (lower + upper) / 2.;
+        if gaussian_dp_delta(middle, epsilon) < delta { lower = middle } else { upper = middle }
+    });
+    lower
+}
+
+
+#[cfg(test)]
+mod test_privacy_filter {
+    use crate::proto;
+    use crate::base::test_usage::approximate_usage as usage;
+    use crate::utilities::privacy_filter::{privacy_filter_continues, privacy_odometer};
+    use crate::utilities::privacy::get_epsilon;
+
+    fn definition(composition: proto::privacy_definition::Composition, composition_delta: f64) -> proto::PrivacyDefinition {
+        proto::PrivacyDefinition {
+            group_size: 1,
+            composition: composition as i32,
+            composition_delta,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_filters() {
+        use proto::privacy_definition::Composition;
+        let budget = usage(1., 1e-6);
+
+        let linear = definition(Composition::Linear, 0.);
+        assert!(privacy_filter_continues(&linear, &budget, &vec![usage(0.1, 0.); 10]).unwrap());
+        assert!(!privacy_filter_continues(&linear, &budget, &vec![usage(0.1, 0.); 11]).unwrap());
+
+        // the advanced and renyi filters permit more releases of small usages than the linear filter
+        let advanced = definition(Composition::Advanced, 5e-7);
+        assert!(privacy_filter_continues(&advanced, &budget, &vec![usage(0.01, 0.); 200]).unwrap());
+        let renyi = definition(Composition::Renyi, 0.);
+        assert!(privacy_filter_continues(&renyi, &budget, &vec![usage(0.01, 0.); 200]).unwrap());
+
+        let optimal = definition(Composition::Optimal, 5e-7);
+        assert!(privacy_filter_continues(&optimal, &budget, &[usage(0.1, 0.)]).is_err());
+    }
+
+    #[test]
+    fn test_odometer() {
+        use proto::privacy_definition::Composition;
+
+        let linear = privacy_odometer(&definition(Composition::Linear, 0.), &vec![usage(0.1, 0.); 10]).unwrap();
+        assert!((get_epsilon(&linear).unwrap() - 1.).abs() < 1e-12);
+
+        let renyi = privacy_odometer(&definition(Composition::Renyi, 1e-6), &vec![usage(0.01, 0.); 1000]).unwrap();
+        assert!(get_epsilon(&renyi).unwrap() < 10.);
+    }
+}
@@ -0,0 +1,363 @@
+//! Numerical privacy accounting via discretized privacy loss distributions.
+//!
+//! The privacy loss distribution (PLD) of a mechanism is the distribution of the privacy loss random variable.
+//! The PLD of a composition is the convolution of the PLDs of each mechanism,
+//! which is computed on a discretized grid of privacy losses via the FFT.
+//!
+//! Koskela, Jälkö, Honkela. "Computing Tight Differential Privacy Guarantees Using FFT." AISTATS 2020.
+//! Meiser, Mohammadi. "Tight on Budget? Tight Bounds for r-Fold Approximate Differential Privacy." CCS 2018.
+
+use num::complex::Complex;
+use statrs::function::erf;
+
+use crate::proto;
+use crate::errors::*;
+
+/// Spacing between privacy losses on the discretization grid.
+pub const DISCRETIZATION: f64 = 1e-4;
+
+/// Probability mass in each tail that may be pessimistically truncated.
+const TAIL_MASS: f64 = 1e-15;
+
+/// Number of grid points below which the convolution is computed directly.
+const DIRECT_CONVOLUTION_LIMIT: usize = 1 << 16;
+
+/// Discretization of a privacy loss distribution.
+///
+/// Probability mass is always rounded up to the next larger privacy loss,
+/// so that the discretized distribution pessimistically bounds the true distribution.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PrivacyLossDistribution {
+    /// spacing between privacy losses on the grid
+    pub discretization: f64,
+    /// the privacy loss of the first mass is offset * discretization
+    pub offset: i64,
+    /// probability mass at each privacy loss on the grid
+    pub masses: Vec<f64>,
+    /// probability that the privacy loss is infinite
+    pub infinity_mass: f64,
+}
+
+impl PrivacyLossDistribution {
+    /// The privacy loss distribution of a mechanism that is identical on neighboring datasets.
+    pub fn identity() -> Self {
+        PrivacyLossDistribution {
+            discretization: DISCRETIZATION,
+            offset: 0,
+            masses: vec![1.],
+            infinity_mass: 0.,
+        }
+    }
+
+    /// Discretize the privacy loss distribution of the laplace mechanism with privacy loss parameter epsilon.
+    pub fn laplace(epsilon: f64) -> Self {
+        // the privacy loss is supported on [-epsilon, epsilon], with point masses at either end
+        let cdf = |loss: f64| if loss < -epsilon {
+            0.
+        } else if loss < epsilon {
+            (-(epsilon - loss) / 2.).exp() / 2.
+        } else {
+            1.
+        };
+        PrivacyLossDistribution::from_cdf(cdf, -epsilon, epsilon)
+    }
+
+    /// Discretize the privacy loss distribution of the gaussian mechanism, where rho = (sensitivity / sigma)^2 / 2.
+    pub fn gaussian(rho: f64) -> Self {
+        // the privacy loss is distributed N(rho, 2 rho)
+        let cdf = |loss: f64| erf::erfc((rho - loss) / (2. * rho.sqrt())) / 2.;
+        // beyond 8.5 standard deviations the tail mass is below TAIL_MASS
+        let width = 8.5 * (2. * rho).sqrt();
+        PrivacyLossDistribution::from_cdf(cdf, rho - width, rho + width)
+    }
+
+    /// Discretize the privacy loss distribution of the discrete laplace (geometric) mechanism with privacy loss parameter epsilon,
+    ///     where the outputs on neighboring datasets are shifted by `steps` integers.
+    ///
+    /// When the noise is k, the privacy loss is epsilon (steps - 2k) / steps, for k clamped to [0, steps].
+    /// With one step, this is the privacy loss distribution of randomized response.
+    pub fn discrete_laplace(epsilon: f64, steps: u64) -> Self {
+        let steps = steps.max(1) as f64;
+        let ln_decay = -epsilon / steps;
+        // the probability that the noise is at least k, for k >= 1, is e^(k ln_decay) / (1 + e^ln_decay)
+        let cdf = |loss: f64| if loss < -epsilon {
+            0.
+        } else if loss < epsilon {
+            let k = (steps * (epsilon - loss) / (2. * epsilon)).ceil();
+            (k * ln_decay).exp() / (1. + ln_decay.exp())
+        } else {
+            1.
+        };
+        PrivacyLossDistribution::from_cdf(cdf, -epsilon, epsilon)
+    }
+
+    /// Discretize the privacy loss distribution of randomized response,
+    ///     which dominates the privacy loss distribution of any (epsilon, delta)-DP mechanism.
+    ///
+    /// Kairouz, Oh, Viswanath. "The Composition Theorem for Differential Privacy." ICML 2015.
+    pub fn randomized_response(epsilon: f64, delta: f64) -> Self {
+        let lower = (-epsilon / DISCRETIZATION).ceil() as i64;
+        let upper = (epsilon / DISCRETIZATION).ceil() as i64;
+
+        let mut masses = vec![0.; (upper - lower + 1) as usize];
+        masses[0] += (1. - delta) / (1. + epsilon.exp());
+        masses[(upper - lower) as usize] += (1. - delta) / (1. + (-epsilon).exp());
+
+        PrivacyLossDistribution {
+            discretization: DISCRETIZATION,
+            offset: lower,
+            masses,
+            infinity_mass: delta,
+        }
+    }
+
+    /// Discretize a privacy loss distribution from its cumulative distribution function,
+    ///     where all but a negligible amount of mass is within [lower, upper].
+    fn from_cdf(cdf: impl Fn(f64) -> f64, lower: f64, upper: f64) -> Self {
+        let lower = (lower / DISCRETIZATION).floor() as i64;
+        let upper = (upper / DISCRETIZATION).ceil() as i64;
+
+        // all mass within ((i - 1) * discretization, i * discretization] is assigned to i * discretization
+        let masses = (lower..=upper)
+            .map(|i| cdf(i as f64 * DISCRETIZATION) - if i == lower { 0. } else { cdf((i - 1) as f64 * DISCRETIZATION) })
+            .map(|mass| mass.max(0.))
+            .collect();
+
+        PrivacyLossDistribution {
+            discretization: DISCRETIZATION,
+            offset: lower,
+            masses,
+            infinity_mass: (1. - cdf(upper as f64 * DISCRETIZATION)).max(0.),
+        }.truncate()
+    }
+
+    /// Compose two privacy loss distributions by convolving them.
+    pub fn compose(&self, other: &Self) -> Result<Self> {
+        if self.discretization != other.discretization {
+            bail!("privacy loss distributions must share the same discretization")
+        }
+        Ok(PrivacyLossDistribution {
+            discretization: self.discretization,
+            offset: self.offset + other.offset,
+            masses: convolve(&self.masses, &other.masses)
+                .into_iter().map(|mass| mass.max(0.)).collect(),
+            infinity_mass: 1. - (1. - self.infinity_mass) * (1. - other.infinity_mass),
+        }.truncate())
+    }
+
+    /// Pessimistically discard negligible mass in the tails,
+    ///     by moving the lower tail up to the first retained loss, and the upper tail to infinity.
+    fn truncate(mut self) -> Self {
+        let mut lower_tail = 0.;
+        let start = self.masses.iter()
+            .position(|mass| {
+                lower_tail += mass;
+                lower_tail > TAIL_MASS
+            })
+            .unwrap_or(0);
+
+        let mut upper_tail = 0.;
+        let end = self.masses.len() - self.masses.iter().rev()
+            .position(|mass| {
+                upper_tail += mass;
+                upper_tail > TAIL_MASS
+            })
+            .unwrap_or(0);
+
+        if start >= end {
+            return self
+        }
+
+        let lower_mass = self.masses[..start].iter().sum::<f64>();
+        let upper_mass = self.masses[end..].iter().sum::<f64>();
+
+        self.masses = self.masses[start..end].to_vec();
+        self.masses[0] += lower_mass;
+        self.offset += start as i64;
+        self.infinity_mass += upper_mass;
+        self
+    }
+
+    /// The smallest delta for which the distribution satisfies (epsilon, delta)-DP.
+    pub fn get_delta(&self, epsilon: f64) -> f64 {
+        self.infinity_mass + self.masses.iter().enumerate()
+            .map(|(i, mass)| ((self.offset + i as i64) as f64 * self.discretization, mass))
+            .filter(|(loss, _)| *loss > epsilon)
+            .map(|(loss, mass)| mass * -(epsilon - loss).exp_m1())
+            .sum::<f64>()
+    }
+
+    /// The smallest epsilon for which the distribution satisfies (epsilon, delta)-DP.
+    pub fn get_epsilon(&self, delta: f64) -> Result<f64> {
+        if self.infinity_mass > delta {
+            bail!("delta ({}) must be larger than the probability of an infinite privacy loss ({})", delta, self.infinity_mass)
+        }
+
+        let mut lower = 0.;
+        let mut upper = ((self.offset + self.masses.len() as i64) as f64 * self.discretization).max(0.);
+        if self.get_delta(lower) <= delta {
+            return Ok(0.)
+        }
+
+        // delta is decreasing in epsilon
+        while upper - lower > self.discretization / 100. {
+            let middle = (lower + upper) / 2.;
+            if self.get_delta(middle) > delta {
+                lower = middle;
+            } else {
+                upper = middle;
+            }
+        }
+        Ok(upper)
+    }
+}
+
+impl From<proto::privacy_usage::DistancePrivacyLoss> for PrivacyLossDistribution {
+    fn from(distance: proto::privacy_usage::DistancePrivacyLoss) -> Self {
+        PrivacyLossDistribution {
+            discretization: distance.discretization,
+            offset: distance.offset,
+            masses: distance.masses,
+            infinity_mass: distance.infinity_mass,
+        }
+    }
+}
+
+impl From<PrivacyLossDistribution> for proto::PrivacyUsage {
+    fn from(distribution: PrivacyLossDistribution) -> Self {
+        proto::PrivacyUsage {
+            distance: Some(proto::privacy_usage::Distance::PrivacyLoss(proto::privacy_usage::DistancePrivacyLoss {
+                discretization: distribution.discretization,
+                offset: distribution.offset,
+                masses: distribution.masses,
+                infinity_mass: distribution.infinity_mass,
+            }))
+        }
+    }
+}
+
+/// Convolve two sequences, via the FFT when the sequences are long.
+fn convolve(left: &[f64], right: &[f64]) -> Vec<f64> {
+    let length = left.len() + right.len() - 1;
+
+    if left.len() * right.len() <= DIRECT_CONVOLUTION_LIMIT {
+        let mut output = vec![0.; length];
+        left.iter().enumerate().for_each(|(i, l)| right.iter().enumerate()
+            .for_each(|(j, r)| output[i + j] += l * r));
+        return output
+    }
+
+    let size = length.next_power_of_two();
+    let to_complex = |values: &[f64]| {
+        let mut values = values.iter().map(|v| Complex::new(*v, 0.)).collect::<Vec<Complex<f64>>>();
+        values.resize(size, Complex::new(0., 0.));
+        values
+    };
+    let mut left = to_complex(left);
+    let mut right = to_complex(right);
+
+    fft(&mut left, false);
+    fft(&mut right, false);
+    left.iter_mut().zip(right.iter()).for_each(|(l, r)| *l *= r);
+    fft(&mut left, true);
+
+    left.into_iter().take(length).map(|v| v.re / size as f64).collect()
+}
+
+/// In-place iterative radix-2 FFT. The length of values must be a power of two.
+/// The inverse transform is not normalized.
+fn fft(values: &mut [Complex<f64>], inverse: bool) {
+    let size = values.len();
+
+    // bit-reversal permutation
+    let mut j = 0;
+    for i in 1..size {
+        let mut bit = size >> 1;
+        while j & bit != 0 {
+            j ^= bit;
+            bit >>= 1;
+        }
+        j |= bit;
+        if i < j {
+            values.swap(i, j);
+        }
+    }
+
+    let sign = if inverse { 1. } else { -1. };
+    let mut width = 2;
+    while width <= size {
+        let angle = sign * 2. * std::f64::consts::PI / width as f64;
+        let root = Complex::new(angle.cos(), angle.sin());
+        values.chunks_mut(width).for_each(|chunk| {
+            let mut twiddle = Complex::new(1., 0.);
+            for k in 0..width / 2 {
+                let even = chunk[k];
+                let odd = chunk[k + width / 2] * twiddle;
+                chunk[k] = even + odd;
+                chunk[k + width / 2] = even - odd;
+                twiddle *= root;
+            }
+        });
+        width <<= 1;
+    }
+}
+
+
+#[cfg(test)]
+mod test_privacy_loss {
+    use crate::utilities::privacy_loss::{convolve, PrivacyLossDistribution};
+
+    #[test]
+    fn test_convolve() {
+        let left = (0..300).map(|v| (v as f64).sin().abs()).collect::<Vec<f64>>();
+        let right = (0..400).map(|v| (v as f64).cos().abs()).collect::<Vec<f64>>();
+
+        let mut expected = vec![0.; 699];
+        left.iter().enumerate().for_each(|(i, l)| right.iter().enumerate()
+            .for_each(|(j, r)| expected[i + j] += l * r));
+
+        convolve(&left, &right).iter().zip(expected.iter())
+            .for_each(|(actual, expected)| assert!((actual - expected).abs() < 1e-9));
+    }
+
+    #[test]
+    fn test_pure_mechanisms() {
+        // the laplace and randomized response mechanisms satisfy (epsilon, 0)-DP
+        let laplace = PrivacyLossDistribution::laplace(1.);
+        assert!((laplace.masses.iter().sum::<f64>() + laplace.infinity_mass - 1.).abs() < 1e-10);
+        assert!(laplace.get_epsilon(1e-10).unwrap() <= 1. + 1e-3);
+
+        let randomized_response = PrivacyLossDistribution::randomized_response(1., 0.);
+        assert!(randomized_response.get_delta(1. + 1e-3) < 1e-12);
+        assert!(laplace.get_delta(0.5) <= randomized_response.get_delta(0.5));
+    }
+
+    #[test]
+    fn test_discrete_laplace() {
+        // with one step, the discrete laplace mechanism is randomized response
+        let discrete_laplace = PrivacyLossDistribution::discrete_laplace(1., 1);
+        let randomized_response = PrivacyLossDistribution::randomized_response(1., 0.);
+        [0., 0.5, 0.9].iter().for_each(|epsilon| assert!(
+            (discrete_laplace.get_delta(*epsilon) - randomized_response.get_delta(*epsilon)).abs() < 1e-3));
+
+        // with many steps, the discrete laplace mechanism approaches the laplace mechanism
+        let discrete_laplace = PrivacyLossDistribution::discrete_laplace(1., 1000);
+        let laplace = PrivacyLossDistribution::laplace(1.);
+        assert!((discrete_laplace.masses.iter().sum::<f64>() + discrete_laplace.infinity_mass - 1.).abs() < 1e-10);
+        assert!(discrete_laplace.get_delta(0.5) < randomized_response.get_delta(0.5));
+        assert!((discrete_laplace.get_delta(0.5) - laplace.get_delta(0.5)).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_gaussian_composition() {
+        // composing gaussian mechanisms is equivalent to a single gaussian mechanism with summed rho
+        let composed = (0..10).map(|_| PrivacyLossDistribution::gaussian(0.01))
+            .fold(PrivacyLossDistribution::identity(), |l, r| l.compose(&r).unwrap());
+        let single = PrivacyLossDistribution::gaussian(0.1);
+
+        let composed = composed.get_epsilon(1e-6).unwrap();
+        let single = single.get_epsilon(1e-6).unwrap();
+        assert!(composed >= single - 1e-3);
+        assert!(composed <= single + 1e-2);
+    }
+}